@@ -143,6 +143,26 @@ where
     }
 }
 
+impl<C> std::iter::Sum<Amount<C>> for Result<Amount<C>>
+where
+    C: Constraint,
+{
+    fn sum<I: Iterator<Item = Amount<C>>>(iter: I) -> Self {
+        iter.fold(Ok(Amount(0, PhantomData)), |total, amount| {
+            total + amount
+        })
+    }
+}
+
+impl<'amt, C> std::iter::Sum<&'amt Amount<C>> for Result<Amount<C>>
+where
+    C: Constraint + 'amt,
+{
+    fn sum<I: Iterator<Item = &'amt Amount<C>>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
 impl<C> From<Amount<C>> for i64 {
     fn from(amount: Amount<C>) -> Self {
         amount.0
@@ -621,6 +641,24 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn sum_amounts() -> Result<()> {
+        zebra_test::init();
+
+        let amounts: Vec<Amount<NonNegative>> = vec![1.try_into()?, 2.try_into()?, 3.try_into()?];
+
+        let total: Amount<NonNegative> = amounts.iter().sum::<Result<_>>()?;
+        assert_eq!(total, Amount::try_from(6)?);
+
+        let too_big: Vec<Amount<NonNegative>> = vec![MAX_MONEY.try_into()?, 1.try_into()?];
+        too_big
+            .iter()
+            .sum::<Result<Amount<NonNegative>>>()
+            .expect_err("sum should reject overflow past MAX_MONEY");
+
+        Ok(())
+    }
+
     #[test]
     fn ordering_constraints() -> Result<()> {
         zebra_test::init();
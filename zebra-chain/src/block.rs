@@ -18,18 +18,21 @@ use std::fmt;
 
 pub use commitment::{Commitment, CommitmentError};
 pub use hash::Hash;
-pub use header::{BlockTimeError, CountedHeader, Header};
+pub use header::{BlockTimeError, CountedHeader, Header, MEDIAN_TIME_PAST_BLOCK_SPAN};
 pub use height::Height;
+pub use merkle::MerkleRootError;
 pub use serialize::MAX_BLOCK_BYTES;
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     fmt::DisplayToDebug,
     parameters::Network,
     serialization::{TrustedPreallocate, MAX_PROTOCOL_MESSAGE_LEN},
-    transaction::Transaction,
+    transaction::{self, Transaction},
     transparent,
+    value_balance::{ValueBalance, ValueBalanceError},
 };
 
 /// A Zcash block, containing a header and a list of transactions.
@@ -57,11 +60,7 @@ impl Block {
     pub fn coinbase_height(&self) -> Option<Height> {
         self.transactions
             .get(0)
-            .and_then(|tx| tx.inputs().get(0))
-            .and_then(|input| match input {
-                transparent::Input::Coinbase { ref height, .. } => Some(*height),
-                _ => None,
-            })
+            .and_then(|tx| tx.coinbase_height())
     }
 
     /// Compute the hash of this block.
@@ -84,6 +83,74 @@ impl Block {
             Some(height) => Commitment::from_bytes(self.header.commitment_bytes, network, height),
         }
     }
+
+    /// Checks that the `merkle_root` in this block's header matches the
+    /// Merkle root computed from its transactions.
+    ///
+    /// Also checks for duplicate transaction hashes, which would otherwise
+    /// let a block with duplicate transactions have the same Merkle root as
+    /// an equivalent block without duplicates. See [`merkle::Root`] for
+    /// details.
+    pub fn check_transaction_merkle_root(&self) -> Result<(), MerkleRootError> {
+        let transaction_hashes: Vec<transaction::Hash> =
+            self.transactions.iter().map(|tx| tx.hash()).collect();
+
+        let merkle_root = transaction_hashes.iter().cloned().collect();
+        if self.header.merkle_root != merkle_root {
+            return Err(MerkleRootError::Mismatch {
+                expected: self.header.merkle_root,
+                actual: merkle_root,
+            });
+        }
+
+        let unique_hashes: HashSet<&transaction::Hash> = transaction_hashes.iter().collect();
+        if unique_hashes.len() != transaction_hashes.len() {
+            return Err(MerkleRootError::DuplicateTransaction);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the [`transparent::Utxo`]s created by this block's
+    /// transactions, keyed by the [`transparent::OutPoint`] that spends them.
+    ///
+    /// Returns `None` if this block does not have a coinbase height, since
+    /// the height is needed to construct each [`transparent::Utxo`].
+    pub fn unspent_outputs(&self) -> Option<HashMap<transparent::OutPoint, transparent::Utxo>> {
+        let height = self.coinbase_height()?;
+
+        let mut unspent_outputs = HashMap::new();
+        for transaction in &self.transactions {
+            let hash = transaction.hash();
+            let from_coinbase = transaction.is_coinbase();
+            for (index, output) in transaction.outputs().iter().cloned().enumerate() {
+                let index = index as u32;
+                unspent_outputs.insert(
+                    transparent::OutPoint { hash, index },
+                    transparent::Utxo {
+                        output,
+                        height,
+                        from_coinbase,
+                    },
+                );
+            }
+        }
+
+        Some(unspent_outputs)
+    }
+
+    /// Returns the change this block causes to the transparent, Sprout,
+    /// Sapling, and Orchard value pools.
+    ///
+    /// The transparent pool component is always zero: see
+    /// [`Transaction::value_balance`](transaction::Transaction::value_balance)
+    /// for details.
+    pub fn chain_value_pool_change(&self) -> Result<ValueBalance, ValueBalanceError> {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.value_balance())
+            .sum()
+    }
 }
 
 impl<'a> From<&'a Block> for Hash {
@@ -20,10 +20,21 @@ impl Arbitrary for Block {
     fn arbitrary_with(ledger_state: Self::Parameters) -> Self::Strategy {
         let transactions_strategy = Transaction::vec_strategy(ledger_state, 2);
 
+        // The coinbase height and uniqueness rules are already enforced by
+        // `Transaction::vec_strategy` (and the `transparent::Input` strategy
+        // it delegates to for the coinbase transaction), so the only
+        // consensus rule left unenforced here is that the header's merkle
+        // root must match the generated transactions - fix that up after
+        // generating both, rather than pulling the transactions into the
+        // `Header` strategy itself.
         (any::<Header>(), transactions_strategy)
-            .prop_map(|(header, transactions)| Self {
-                header,
-                transactions,
+            .prop_map(|(mut header, transactions)| {
+                header.merkle_root = transactions.iter().map(|tx| tx.hash()).collect();
+
+                Self {
+                    header,
+                    transactions,
+                }
             })
             .boxed()
     }
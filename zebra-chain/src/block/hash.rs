@@ -1,8 +1,8 @@
-use std::{fmt, io};
+use std::{fmt, io, str::FromStr};
 
 #[cfg(any(test, feature = "proptest-impl"))]
 use proptest_derive::Arbitrary;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::serialization::{
     sha256d, ReadZcashExt, SerializationError, ZcashDeserialize, ZcashSerialize,
@@ -18,10 +18,55 @@ use super::Header;
 ///
 /// Note: Zebra displays transaction and block hashes in big-endian byte-order,
 /// following the u256 convention set by Bitcoin and zcashd.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
 pub struct Hash(pub [u8; 32]);
 
+impl Serialize for Hash {
+    /// Serializes `Hash` as hex in human-readable formats like JSON, and as
+    /// raw bytes in non-human-readable formats like bincode.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            struct HexHashVisitor;
+
+            impl<'de> de::Visitor<'de> for HexHashVisitor {
+                type Value = Hash;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a hex-encoded block hash")
+                }
+
+                fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Hash::from_str(s).map_err(de::Error::custom)
+                }
+            }
+
+            deserializer.deserialize_str(HexHashVisitor)
+        } else {
+            Ok(Hash(<[u8; 32]>::deserialize(deserializer)?))
+        }
+    }
+}
+
 impl fmt::Display for Hash {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut reversed_bytes = self.0;
@@ -92,7 +92,43 @@ pub enum BlockTimeError {
     ),
 }
 
+/// The number of recent blocks used to calculate the median-time-past.
+///
+/// `PoWMedianBlockSpan` in the Zcash specification.
+pub const MEDIAN_TIME_PAST_BLOCK_SPAN: usize = 11;
+
 impl Header {
+    /// Returns the median-time-past of `headers`: the median `time` of up to
+    /// the most recent [`MEDIAN_TIME_PAST_BLOCK_SPAN`] (11) headers.
+    ///
+    /// `headers` must be in reverse height order, most recent first. If more
+    /// than `MEDIAN_TIME_PAST_BLOCK_SPAN` headers are supplied, only the
+    /// first `MEDIAN_TIME_PAST_BLOCK_SPAN` are used.
+    ///
+    /// Implements `MedianTime` from the Zcash specification, shared by the
+    /// difficulty adjustment algorithm's time check and transaction lock time
+    /// evaluation. Unlike the specification, this also accepts fewer than 11
+    /// headers, returning the median of however many are available, which is
+    /// needed near the start of a chain, before 11 ancestors exist.
+    ///
+    /// Returns `None` if `headers` is empty.
+    pub fn median_time_past<'a>(
+        headers: impl IntoIterator<Item = &'a Header>,
+    ) -> Option<DateTime<Utc>> {
+        let mut times: Vec<DateTime<Utc>> = headers
+            .into_iter()
+            .take(MEDIAN_TIME_PAST_BLOCK_SPAN)
+            .map(|header| header.time)
+            .collect();
+
+        if times.is_empty() {
+            return None;
+        }
+
+        times.sort_unstable();
+        Some(times[times.len() / 2])
+    }
+
     /// TODO: Inline this function into zebra_consensus::block::check::time_is_valid_at.
     /// See https://github.com/ZcashFoundation/zebra/issues/1021 for more details.
     pub fn time_is_valid_at(
@@ -17,6 +17,12 @@ use std::{
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Height(pub u32);
 
+impl std::fmt::Display for Height {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl std::str::FromStr for Height {
     type Err = SerializationError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
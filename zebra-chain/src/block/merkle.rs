@@ -6,6 +6,8 @@ use std::{fmt, io::Write};
 #[cfg(any(any(test, feature = "proptest-impl"), feature = "proptest-impl"))]
 use proptest_derive::Arbitrary;
 
+use thiserror::Error;
+
 use crate::serialization::sha256d;
 use crate::transaction::{self, Transaction};
 
@@ -78,6 +80,39 @@ fn hash(h1: &[u8; 32], h2: &[u8; 32]) -> [u8; 32] {
     w.finish()
 }
 
+impl Root {
+    /// Computes the Merkle root of `transactions`.
+    pub fn from_transactions<'a, T>(transactions: impl IntoIterator<Item = &'a T>) -> Self
+    where
+        T: AsRef<Transaction> + 'a,
+    {
+        transactions.into_iter().collect()
+    }
+}
+
+/// An error indicating that a block's Merkle root doesn't match its
+/// transactions.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum MerkleRootError {
+    /// The block's `merkle_root` header field doesn't match the Merkle root
+    /// computed from its transactions.
+    #[error("bad merkle root: expected {expected:?}, actual {actual:?}")]
+    Mismatch {
+        /// The merkle root in the block header.
+        expected: Root,
+        /// The merkle root computed from the block's transactions.
+        actual: Root,
+    },
+
+    /// The block contains duplicate transaction hashes.
+    ///
+    /// Bitcoin's Merkle tree construction is malleable: blocks with
+    /// duplicate transactions can have the same Merkle root as blocks
+    /// without duplicates. See the [`Root`] documentation for details.
+    #[error("duplicate transaction hash")]
+    DuplicateTransaction,
+}
+
 impl<T> std::iter::FromIterator<T> for Root
 where
     T: std::convert::AsRef<Transaction>,
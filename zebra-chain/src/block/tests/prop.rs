@@ -1,7 +1,7 @@
 use std::env;
 use std::io::ErrorKind;
 
-use proptest::{arbitrary::any, prelude::*, test_runner::Config};
+use proptest::{arbitrary::any, collection::vec, prelude::*, test_runner::Config};
 use zebra_test::prelude::*;
 
 use crate::serialization::{SerializationError, ZcashDeserializeInto, ZcashSerialize};
@@ -57,6 +57,51 @@ proptest! {
     }
 }
 
+proptest! {
+    /// The median-time-past of an odd number of headers is the middle time,
+    /// once they're sorted.
+    #[test]
+    fn median_time_past_odd(mut headers in vec(any::<Header>(), 1..(MEDIAN_TIME_PAST_BLOCK_SPAN + 1))
+        .prop_filter("need an odd number of headers", |h| h.len() % 2 == 1)) {
+        zebra_test::init();
+
+        let median = Header::median_time_past(&headers).expect("headers is non-empty");
+
+        headers.sort_by_key(|header| header.time);
+        prop_assert_eq!(median, headers[headers.len() / 2].time);
+    }
+
+    /// The median-time-past of an even number of headers is the later of the
+    /// two middle times, once they're sorted, matching zcashd and bitcoind.
+    #[test]
+    fn median_time_past_even(mut headers in vec(any::<Header>(), 2..=MEDIAN_TIME_PAST_BLOCK_SPAN)
+        .prop_filter("need an even number of headers", |h| h.len() % 2 == 0)) {
+        zebra_test::init();
+
+        let median = Header::median_time_past(&headers).expect("headers is non-empty");
+
+        headers.sort_by_key(|header| header.time);
+        prop_assert_eq!(median, headers[headers.len() / 2].time);
+    }
+
+    /// The median-time-past only uses the most recent `MEDIAN_TIME_PAST_BLOCK_SPAN`
+    /// headers, even if more are supplied, and it is always between the
+    /// earliest and latest of those headers.
+    #[test]
+    fn median_time_past_short_chain_and_bounds(headers in vec(any::<Header>(), 1..30)) {
+        zebra_test::init();
+
+        let relevant_headers = &headers[..headers.len().min(MEDIAN_TIME_PAST_BLOCK_SPAN)];
+        let min_time = relevant_headers.iter().map(|header| header.time).min().unwrap();
+        let max_time = relevant_headers.iter().map(|header| header.time).max().unwrap();
+
+        let median = Header::median_time_past(&headers).expect("headers is non-empty");
+
+        prop_assert!(median >= min_time);
+        prop_assert!(median <= max_time);
+    }
+}
+
 proptest! {
     // The block roundtrip test can be really slow, so we use fewer cases by
     // default. Set the PROPTEST_CASES env var to override this default.
@@ -9,6 +9,14 @@ use crate::transaction::LockTime;
 use super::super::{serialize::MAX_BLOCK_BYTES, *};
 use super::generate; // XXX this should be rewritten as strategies
 
+#[test]
+fn median_time_past_empty() {
+    zebra_test::init();
+
+    let no_headers: Vec<Header> = Vec::new();
+    assert_eq!(Header::median_time_past(&no_headers), None);
+}
+
 #[test]
 fn blockheaderhash_debug() {
     zebra_test::init();
@@ -24,6 +24,7 @@ extern crate serde;
 pub mod amount;
 pub mod block;
 pub mod fmt;
+pub mod orchard;
 pub mod parameters;
 pub mod primitives;
 pub mod sapling;
@@ -32,6 +33,8 @@ pub mod shutdown;
 pub mod sprout;
 pub mod transaction;
 pub mod transparent;
+pub mod unified_address;
+pub mod value_balance;
 pub mod work;
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,403 @@
+//! Orchard shielded data for `V5` `Transaction`s.
+//!
+//! Orchard actions bundle a Sapling-style spend and output into a single
+//! description, so unlike [`crate::sapling::ShieldedData`] there is no
+//! split between spends and outputs.
+//!
+//! Zebra does not implement the Orchard circuit or note encryption, so the
+//! cryptographic fields in this module are opaque, fixed-size byte arrays,
+//! rather than validated curve points. This is enough to parse and
+//! re-serialize `V5` transactions; verifying Orchard proofs and signatures
+//! is out of scope for this module.
+
+use std::{fmt, io};
+
+use byteorder::{ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    amount::Amount,
+    block::MAX_BLOCK_BYTES,
+    sapling::note::{EncryptedNote, WrappedNoteKey},
+    serialization::{
+        serde_helpers, ReadZcashExt, SerializationError, TrustedPreallocate, WriteZcashExt,
+        ZcashDeserialize, ZcashSerialize,
+    },
+};
+
+#[cfg(any(test, feature = "proptest-impl"))]
+use proptest::{arbitrary::Arbitrary, collection::vec, prelude::*};
+
+pub mod tree;
+
+#[cfg(test)]
+mod tests;
+
+bitflags::bitflags! {
+    /// Per-bundle flags, as described in [protocol specification §7.5][ps].
+    ///
+    /// [ps]: https://zips.z.cash/protocol/protocol.pdf#orchardencoding
+    #[derive(Deserialize, Serialize)]
+    pub struct Flags: u8 {
+        /// The bundle is allowed to have spends.
+        const ENABLE_SPENDS = 0b0000_0001;
+        /// The bundle is allowed to have outputs.
+        const ENABLE_OUTPUTS = 0b0000_0010;
+    }
+}
+
+impl ZcashSerialize for Flags {
+    fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        writer.write_u8(self.bits())
+    }
+}
+
+impl ZcashDeserialize for Flags {
+    fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Self::from_bits(reader.read_u8()?).ok_or(SerializationError::Parse(
+            "invalid Orchard flags: unknown bits set",
+        ))
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Arbitrary for Flags {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        any::<u8>()
+            .prop_map(|bits| Self::from_bits_truncate(bits))
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+/// A nullifier for an Orchard note.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct Nullifier(pub [u8; 32]);
+
+/// A value commitment to the net value of an Orchard [`Action`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct ValueCommitment(pub [u8; 32]);
+
+/// A note commitment for the output note of an Orchard [`Action`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct NoteCommitment(pub [u8; 32]);
+
+/// An encoding of an ephemeral Pallas public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct EphemeralKey(pub [u8; 32]);
+
+/// A randomized RedPallas validating key, used to verify a
+/// [`SpendAuthSig`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct ValidatingKey(pub [u8; 32]);
+
+/// A RedPallas spend authorization signature.
+#[derive(Serialize, Deserialize)]
+pub struct SpendAuthSig(#[serde(with = "serde_helpers::BigArray")] pub [u8; 64]);
+
+// These impls all only exist because of array length restrictions.
+
+impl fmt::Debug for SpendAuthSig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SpendAuthSig")
+            .field(&hex::encode(&self.0[..]))
+            .finish()
+    }
+}
+
+impl Copy for SpendAuthSig {}
+
+impl Clone for SpendAuthSig {
+    fn clone(&self) -> Self {
+        let mut bytes = [0; 64];
+        bytes[..].copy_from_slice(&self.0[..]);
+        Self(bytes)
+    }
+}
+
+impl Eq for SpendAuthSig {}
+
+impl PartialEq for SpendAuthSig {
+    fn eq(&self, other: &Self) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Arbitrary for SpendAuthSig {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (vec(any::<u8>(), 64))
+            .prop_map(|v| {
+                let mut bytes = [0; 64];
+                bytes.copy_from_slice(v.as_slice());
+                Self(bytes)
+            })
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+/// A RedPallas binding signature, which ties an Orchard bundle's value
+/// commitments to its `value_balance`.
+#[derive(Serialize, Deserialize)]
+pub struct BindingSig(#[serde(with = "serde_helpers::BigArray")] pub [u8; 64]);
+
+// These impls all only exist because of array length restrictions.
+
+impl fmt::Debug for BindingSig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("BindingSig")
+            .field(&hex::encode(&self.0[..]))
+            .finish()
+    }
+}
+
+impl Copy for BindingSig {}
+
+impl Clone for BindingSig {
+    fn clone(&self) -> Self {
+        let mut bytes = [0; 64];
+        bytes[..].copy_from_slice(&self.0[..]);
+        Self(bytes)
+    }
+}
+
+impl Eq for BindingSig {}
+
+impl PartialEq for BindingSig {
+    fn eq(&self, other: &Self) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Arbitrary for BindingSig {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (vec(any::<u8>(), 64))
+            .prop_map(|v| {
+                let mut bytes = [0; 64];
+                bytes.copy_from_slice(v.as_slice());
+                Self(bytes)
+            })
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+/// A Halo2 proof, used to prove the validity of an Orchard [`Action`] bundle.
+///
+/// Unlike the Groth16 proofs used by Sprout and Sapling, Halo2 proofs don't
+/// have a fixed size, so this wraps a `Vec<u8>` rather than a byte array.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Halo2Proof(pub Vec<u8>);
+
+impl ZcashSerialize for Halo2Proof {
+    fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        writer.write_compactsize(self.0.len() as u64)?;
+        writer.write_all(&self.0)
+    }
+}
+
+impl ZcashDeserialize for Halo2Proof {
+    fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let len = reader.read_compactsize()?;
+        let mut bytes = vec![0; len as usize];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Arbitrary for Halo2Proof {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        vec(any::<u8>(), 0..512).prop_map(Self).boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+/// An _Action Description_, as described in [protocol specification §7.5][ps].
+///
+/// Every Orchard action bundles together a spend and an output, so unlike
+/// `sapling::Spend`/`sapling::Output`, there is no separate `Action` type
+/// for each.
+///
+/// [ps]: https://zips.z.cash/protocol/protocol.pdf#actiondescription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct Action {
+    /// A value commitment to the net value of the spend and output notes.
+    pub cv: ValueCommitment,
+    /// The nullifier of the spent note.
+    pub nullifier: Nullifier,
+    /// The randomized validating key for this action's spend authorizing
+    /// signature.
+    pub rk: ValidatingKey,
+    /// The note commitment for the output note.
+    pub cm_x: NoteCommitment,
+    /// An encoding of an ephemeral Pallas public key.
+    pub ephemeral_key: EphemeralKey,
+    /// A ciphertext component for the encrypted output note.
+    pub enc_ciphertext: EncryptedNote,
+    /// A ciphertext component for the encrypted output note.
+    pub out_ciphertext: WrappedNoteKey,
+}
+
+impl ZcashSerialize for Action {
+    fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        writer.write_all(&self.cv.0[..])?;
+        writer.write_32_bytes(&self.nullifier.0)?;
+        writer.write_32_bytes(&self.rk.0)?;
+        writer.write_32_bytes(&self.cm_x.0)?;
+        writer.write_all(&self.ephemeral_key.0[..])?;
+        self.enc_ciphertext.zcash_serialize(&mut writer)?;
+        self.out_ciphertext.zcash_serialize(&mut writer)?;
+        // The bundle's single Halo2 proof and each Action's spend_auth_sig
+        // are serialized separately, after all the Actions, by the `V5` arm
+        // of `Transaction`'s `ZcashSerialize`/`ZcashDeserialize` impls.
+        Ok(())
+    }
+}
+
+impl ZcashDeserialize for Action {
+    fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(Action {
+            cv: ValueCommitment(reader.read_32_bytes()?),
+            nullifier: Nullifier(reader.read_32_bytes()?),
+            rk: ValidatingKey(reader.read_32_bytes()?),
+            cm_x: NoteCommitment(reader.read_32_bytes()?),
+            ephemeral_key: EphemeralKey(reader.read_32_bytes()?),
+            enc_ciphertext: EncryptedNote::zcash_deserialize(&mut reader)?,
+            out_ciphertext: WrappedNoteKey::zcash_deserialize(&mut reader)?,
+        })
+    }
+}
+
+/// The size of an `Action`, excluding its `spend_auth_sig`, which is
+/// serialized separately, in its own array, at the end of the bundle.
+///
+/// An `Action` contains: a 32 byte cv, a 32 byte nullifier, a 32 byte rk, a
+/// 32 byte cm_x, a 32 byte ephemeral_key, a 580 byte enc_ciphertext, and an
+/// 80 byte out_ciphertext.
+pub(crate) const ACTION_INITIAL_SIZE: u64 = 32 + 32 + 32 + 32 + 32 + 580 + 80;
+
+/// The size of an `Action`, including its associated `spend_auth_sig`.
+///
+/// This is the size of actions in the initial array, there is another array
+/// of spend_auth_sigs required in the transaction format.
+pub(crate) const ACTION_FULL_SIZE: u64 = ACTION_INITIAL_SIZE + 64;
+
+/// The maximum number of actions in a valid Zcash on-chain transaction.
+///
+/// If a transaction contains more actions than can fit in maximally large block, it might be
+/// valid on the network and in the mempool, but it can never be mined into a block. So
+/// rejecting these large edge-case transactions can never break consensus.
+impl TrustedPreallocate for Action {
+    fn max_allocation() -> u64 {
+        // Since a serialized Vec<Action> uses at least one byte for its length,
+        // and the associated fields are required,
+        // a valid max allocation can never exceed this size
+        (MAX_BLOCK_BYTES - 1) / ACTION_FULL_SIZE
+    }
+}
+
+/// A bundle of [`Action`] descriptions and signature data, as described in
+/// [protocol specification §7.5][ps].
+///
+/// Orchard bundles are optional, but Zcash transactions must include a
+/// binding signature if and only if there is at least one `Action`. This
+/// wrapper type bundles at least one `Action` with the required signature
+/// data, so that an `Option<ShieldedData>` correctly models the presence or
+/// absence of any shielded data, mirroring
+/// [`crate::sapling::ShieldedData`].
+///
+/// [ps]: https://zips.z.cash/protocol/protocol.pdf#orchardencoding
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
+pub struct ShieldedData {
+    /// The flags for this transaction's Orchard bundle.
+    pub flags: Flags,
+    /// The net value of Orchard spends minus outputs.
+    pub value_balance: Amount,
+    /// The shared anchor for all `Action`s in this transaction.
+    pub shared_anchor: tree::Root,
+    /// The Halo2 proof, covering every `Action` in this bundle.
+    pub proof: Halo2Proof,
+    /// The first `Action` in this transaction.
+    ///
+    /// Storing this separately ensures that it is impossible to construct
+    /// an invalid `ShieldedData` with no actions.
+    pub first: Action,
+    /// The rest of the `Action`s in this transaction.
+    pub rest: Vec<Action>,
+    /// A signature authorizing the first `Action`'s spend.
+    ///
+    /// Storing this separately from `rest_spend_auth_sigs`, for the same
+    /// reason `first`/`rest` are split.
+    pub first_spend_auth_sig: SpendAuthSig,
+    /// The signatures authorizing the rest of the `Action`s' spends.
+    pub rest_spend_auth_sigs: Vec<SpendAuthSig>,
+    /// A signature on the transaction hash.
+    pub binding_sig: BindingSig,
+}
+
+impl ShieldedData {
+    /// Iterate over the [`Action`]s in this transaction.
+    pub fn actions(&self) -> impl Iterator<Item = &Action> {
+        std::iter::once(&self.first).chain(self.rest.iter())
+    }
+
+    /// Iterate over the spend authorizing signatures in this transaction,
+    /// zipped with their corresponding [`Action`].
+    pub fn actions_with_auth_sigs(&self) -> impl Iterator<Item = (&Action, &SpendAuthSig)> {
+        self.actions().zip(
+            std::iter::once(&self.first_spend_auth_sig).chain(self.rest_spend_auth_sigs.iter()),
+        )
+    }
+
+    /// Collect the [`Nullifier`]s for this transaction.
+    pub fn nullifiers(&self) -> impl Iterator<Item = &Nullifier> {
+        self.actions().map(|action| &action.nullifier)
+    }
+
+    /// Collect the note commitments for this transaction.
+    pub fn note_commitments(&self) -> impl Iterator<Item = &NoteCommitment> {
+        self.actions().map(|action| &action.cm_x)
+    }
+}
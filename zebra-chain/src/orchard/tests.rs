@@ -0,0 +1,2 @@
+mod preallocate;
+mod prop;
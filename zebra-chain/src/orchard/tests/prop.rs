@@ -0,0 +1,32 @@
+use proptest::prelude::*;
+
+use crate::{
+    block, orchard,
+    parameters::ConsensusBranchId,
+    serialization::{ZcashDeserializeInto, ZcashSerialize},
+    transaction::{LockTime, Transaction},
+};
+
+proptest! {
+    #[test]
+    fn shielded_data_roundtrip(shielded in any::<orchard::ShieldedData>()) {
+        zebra_test::init();
+
+        // orchard shielded data doesn't serialize by itself, so we have to
+        // stick it in a transaction
+        let tx = Transaction::V5 {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            lock_time: LockTime::min_lock_time(),
+            expiry_height: block::Height(0),
+            consensus_branch_id: ConsensusBranchId::from(0),
+            sapling_shielded_data: None,
+            orchard_shielded_data: Some(shielded),
+        };
+
+        let data = tx.zcash_serialize_to_vec().expect("tx should serialize");
+        let tx_parsed = data.zcash_deserialize_into().expect("randomized tx should deserialize");
+
+        prop_assert_eq![tx, tx_parsed];
+    }
+}
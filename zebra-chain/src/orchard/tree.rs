@@ -0,0 +1,97 @@
+//! Note Commitment Trees.
+//!
+//! A note commitment tree is an incremental Merkle tree of fixed depth
+//! used to store note commitments that Orchard transfers produce.
+//!
+//! A root of a note commitment tree is associated with each treestate.
+
+use std::fmt;
+
+#[cfg(any(test, feature = "proptest-impl"))]
+use proptest_derive::Arbitrary;
+
+use super::NoteCommitment;
+
+const MERKLE_DEPTH: usize = 32;
+
+/// The index of a note's commitment at the leafmost layer of its Note
+/// Commitment Tree.
+///
+/// https://zips.z.cash/protocol/protocol.pdf#merkletree
+pub struct Position(pub(crate) u64);
+
+/// Orchard note commitment tree root node hash.
+///
+/// A root of a note commitment tree is associated with each treestate.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize, Hash)]
+#[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
+pub struct Root(pub [u8; 32]);
+
+impl fmt::Debug for Root {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Root").field(&hex::encode(&self.0)).finish()
+    }
+}
+
+impl From<[u8; 32]> for Root {
+    fn from(bytes: [u8; 32]) -> Root {
+        Self(bytes)
+    }
+}
+
+impl From<Root> for [u8; 32] {
+    fn from(root: Root) -> Self {
+        root.0
+    }
+}
+
+/// An error returned when appending a note commitment to a [`NoteCommitmentTree`]
+/// that has no room left.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum NoteCommitmentTreeError {
+    /// the note commitment tree is full
+    #[error("the note commitment tree is full")]
+    Full,
+}
+
+/// Orchard Note Commitment Tree.
+///
+/// Stores every note commitment appended to the tree so far, in leaf order.
+///
+/// Zebra does not implement the Sinsemilla hash used by `MerkleCRH^Orchard`
+/// (see [`crate::orchard`]'s module documentation for why its cryptographic
+/// primitives are opaque), so this tree can only track appended leaves:
+/// it has no `root` method, unlike the Sprout and Sapling note commitment
+/// trees.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NoteCommitmentTree {
+    /// The note commitments in this tree, in the order they were appended.
+    leaves: Vec<NoteCommitment>,
+}
+
+impl NoteCommitmentTree {
+    /// Appends a note commitment to the note commitment tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NoteCommitmentTreeError::Full`] if the tree already has the
+    /// maximum number of leaves for `MERKLE_DEPTH`.
+    pub fn append(&mut self, cm_x: NoteCommitment) -> Result<(), NoteCommitmentTreeError> {
+        if self.leaves.len() >= 1usize << MERKLE_DEPTH {
+            return Err(NoteCommitmentTreeError::Full);
+        }
+
+        self.leaves.push(cm_x);
+        Ok(())
+    }
+
+    /// Returns the position the next appended note commitment will occupy.
+    pub fn position(&self) -> Position {
+        Position(self.leaves.len() as u64)
+    }
+
+    /// Returns the number of note commitments in this tree.
+    pub fn count(&self) -> usize {
+        self.leaves.len()
+    }
+}
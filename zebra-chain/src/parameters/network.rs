@@ -4,6 +4,12 @@ use std::fmt;
 use proptest_derive::Arbitrary;
 
 /// An enum describing the possible network choices.
+//
+// TODO: add `Regtest` and a custom-network escape hatch (genesis block,
+// activation heights, and address prefixes), so the whole stack can run
+// single-node test networks. Touches every exhaustive `match` on `Network`
+// across zebra-chain, zebra-network, zebra-consensus, and zebra-state, so it
+// needs its own dedicated change; unimplemented so far.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
 pub enum Network {
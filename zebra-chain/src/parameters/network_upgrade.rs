@@ -76,7 +76,11 @@ pub(crate) const TESTNET_ACTIVATION_HEIGHTS: &[(block::Height, NetworkUpgrade)]
 
 /// The Consensus Branch Id, used to bind transactions and blocks to a
 /// particular network upgrade.
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(
+    any(test, feature = "proptest-impl"),
+    derive(proptest_derive::Arbitrary)
+)]
 pub struct ConsensusBranchId(u32);
 
 impl From<ConsensusBranchId> for u32 {
@@ -85,6 +89,12 @@ impl From<ConsensusBranchId> for u32 {
     }
 }
 
+impl From<u32> for ConsensusBranchId {
+    fn from(value: u32) -> ConsensusBranchId {
+        ConsensusBranchId(value)
+    }
+}
+
 /// Network Upgrade Consensus Branch Ids.
 ///
 /// Branch ids are the same for mainnet and testnet. If there is a testnet
@@ -181,6 +191,28 @@ impl NetworkUpgrade {
             .next()
     }
 
+    /// Returns the current network upgrade for `height`, using `overrides`
+    /// instead of `network`'s built-in activation heights.
+    ///
+    /// `overrides` doesn't need to cover every network upgrade: any upgrade
+    /// that isn't present is treated as not yet activated. Returns `None` if
+    /// `height` is before every overridden activation height.
+    ///
+    /// This lets protocol developers bring up a custom test network with
+    /// upgrades activated at heights of their choosing, without waiting for
+    /// real mainnet/testnet activation heights to be decided. It's only
+    /// available to tests and the `proptest-impl` feature, since Zebra's
+    /// block and transaction verifiers don't consult it yet: wiring
+    /// `overrides` through branch-id lookup and the difficulty rules is
+    /// follow-up work.
+    #[cfg(any(test, feature = "proptest-impl"))]
+    pub fn current_with_overrides(
+        height: block::Height,
+        overrides: &BTreeMap<block::Height, NetworkUpgrade>,
+    ) -> Option<NetworkUpgrade> {
+        overrides.range(..=height).map(|(_, nu)| *nu).next_back()
+    }
+
     /// Returns a BTreeMap of NetworkUpgrades and their ConsensusBranchIds.
     ///
     /// Branch ids are the same for mainnet and testnet.
@@ -89,6 +89,51 @@ impl std::str::FromStr for Address {
     }
 }
 
+impl Address {
+    /// Constructs an `Address` from its network, diversifier, and
+    /// transmission key.
+    pub fn new(
+        network: Network,
+        diversifier: keys::Diversifier,
+        transmission_key: keys::TransmissionKey,
+    ) -> Self {
+        Self {
+            network,
+            diversifier,
+            transmission_key,
+        }
+    }
+
+    /// Returns the network for this address.
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the diversifier for this address.
+    pub fn diversifier(&self) -> keys::Diversifier {
+        self.diversifier
+    }
+
+    /// Returns the transmission key for this address.
+    pub fn transmission_key(&self) -> keys::TransmissionKey {
+        self.transmission_key
+    }
+}
+
+impl From<(keys::FullViewingKey, keys::Diversifier)> for Address {
+    /// Derives the diversified payment address seen by the holder of
+    /// `full_viewing_key` for `diversifier`.
+    fn from((fvk, diversifier): (keys::FullViewingKey, keys::Diversifier)) -> Self {
+        let incoming_viewing_key = keys::IncomingViewingKey::from((
+            fvk.authorizing_key(),
+            fvk.nullifier_deriving_key(),
+        ));
+        let transmission_key = keys::TransmissionKey::from((incoming_viewing_key, diversifier));
+
+        Self::new(fvk.network(), diversifier, transmission_key)
+    }
+}
+
 #[cfg(test)]
 impl Arbitrary for Address {
     type Parameters = ();
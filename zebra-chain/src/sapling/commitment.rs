@@ -25,6 +25,26 @@ use pedersen_hashes::*;
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct CommitmentRandomness(jubjub::Fr);
 
+impl From<CommitmentRandomness> for [u8; 32] {
+    fn from(rcm: CommitmentRandomness) -> [u8; 32] {
+        rcm.0.to_bytes()
+    }
+}
+
+impl TryFrom<[u8; 32]> for CommitmentRandomness {
+    type Error = &'static str;
+
+    fn try_from(bytes: [u8; 32]) -> Result<Self, Self::Error> {
+        let possible_scalar = jubjub::Fr::from_bytes(&bytes);
+
+        if possible_scalar.is_some().into() {
+            Ok(Self(possible_scalar.unwrap()))
+        } else {
+            Err("Invalid jubjub::Fr value")
+        }
+    }
+}
+
 /// Note commitments for the output notes.
 #[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
 pub struct NoteCommitment(#[serde(with = "serde_helpers::AffinePoint")] pub jubjub::AffinePoint);
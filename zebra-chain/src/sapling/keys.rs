@@ -601,6 +601,13 @@ impl PartialEq<[u8; 32]> for IncomingViewingKey {
     }
 }
 
+impl IncomingViewingKey {
+    /// Returns the network for this incoming viewing key.
+    pub fn network(&self) -> Network {
+        self.network
+    }
+}
+
 /// A _Diversifier_, as described in [protocol specification §4.2.2][ps].
 ///
 /// Combined with an _IncomingViewingKey_, produces a _diversified
@@ -866,6 +873,23 @@ impl FromStr for FullViewingKey {
     }
 }
 
+impl FullViewingKey {
+    /// Returns the network for this full viewing key.
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the authorizing key for this full viewing key.
+    pub fn authorizing_key(&self) -> AuthorizingKey {
+        self.authorizing_key
+    }
+
+    /// Returns the nullifier deriving key for this full viewing key.
+    pub fn nullifier_deriving_key(&self) -> NullifierDerivingKey {
+        self.nullifier_deriving_key
+    }
+}
+
 /// An ephemeral public key for Sapling key agreement.
 ///
 /// https://zips.z.cash/protocol/protocol.pdf#concretesaplingkeyagreement
@@ -5,6 +5,7 @@
 
 mod ciphertexts;
 mod nullifiers;
+mod plaintext;
 
 #[cfg(any(test, feature = "proptest-impl"))]
 mod arbitrary;
@@ -23,6 +24,8 @@ pub use ciphertexts::{EncryptedNote, WrappedNoteKey};
 
 pub use nullifiers::Nullifier;
 
+pub use plaintext::{NotePlaintext, LEAD_BYTE_POST_ZIP_212, LEAD_BYTE_PRE_ZIP_212};
+
 /// A Note represents that a value is spendable by the recipient who
 /// holds the spending key corresponding to a given shielded payment
 /// address.
@@ -0,0 +1,120 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    io,
+};
+
+use crate::{
+    amount::{Amount, NonNegative},
+    serialization::{SerializationError, ZcashDeserialize, ZcashSerialize},
+    transaction::Memo,
+};
+
+use super::super::{commitment::CommitmentRandomness, keys::Diversifier};
+
+/// The lead byte of a note plaintext encoded before [ZIP-212] activates for
+/// the note's pool.
+///
+/// [ZIP-212]: https://zips.z.cash/zip-0212
+pub const LEAD_BYTE_PRE_ZIP_212: u8 = 0x01;
+
+/// The lead byte of a note plaintext encoded once [ZIP-212] is active for
+/// the note's pool, which changes how the note's commitment randomness is
+/// derived but not the plaintext's wire format.
+///
+/// [ZIP-212]: https://zips.z.cash/zip-0212
+pub const LEAD_BYTE_POST_ZIP_212: u8 = 0x02;
+
+/// A decrypted Sapling note plaintext, as described in
+/// [protocol specification §5.5][ps], versioned per [ZIP-212].
+///
+/// This is the plaintext recovered by trial-decrypting an [`EncryptedNote`]
+/// ciphertext; it is not itself sent over the wire. Note decryption is not
+/// implemented here - see [`NotePlaintext::zcash_deserialize`] for parsing
+/// an already-decrypted buffer.
+///
+/// [ps]: https://zips.z.cash/protocol/protocol.pdf#saplingnoteptconstruct
+/// [`EncryptedNote`]: super::EncryptedNote
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotePlaintext {
+    /// Either [`LEAD_BYTE_PRE_ZIP_212`] or [`LEAD_BYTE_POST_ZIP_212`].
+    pub lead_byte: u8,
+    /// The diversifier of the recipient's shielded payment address.
+    pub diversifier: Diversifier,
+    /// The value of the note, in zatoshi.
+    pub value: Amount<NonNegative>,
+    /// Pre-ZIP-212, the note's commitment randomness `rcm` directly;
+    /// post-ZIP-212, the seed `rseed` that `rcm` is derived from. Either
+    /// way, this field is the 32 bytes that follow `value` in the
+    /// plaintext.
+    pub rseed: [u8; 32],
+    /// The note memo.
+    pub memo: Memo,
+}
+
+impl NotePlaintext {
+    /// Returns `true` if this plaintext uses the [ZIP-212] `rseed` encoding,
+    /// based on its `lead_byte`.
+    ///
+    /// [ZIP-212]: https://zips.z.cash/zip-0212
+    pub fn is_zip_212(&self) -> bool {
+        self.lead_byte == LEAD_BYTE_POST_ZIP_212
+    }
+
+    /// Returns the note's commitment randomness, interpreting `rseed`
+    /// according to the pre-ZIP-212 encoding.
+    ///
+    /// Returns `None` if `rseed` isn't a canonical encoding of a
+    /// [`jubjub::Fr`] value.
+    ///
+    /// This only covers the pre-ZIP-212 case; deriving `rcm` from a
+    /// post-ZIP-212 `rseed` additionally needs the note's position in the
+    /// commitment tree, via PRF^expand, which isn't implemented here.
+    pub fn rcm_pre_zip_212(&self) -> Option<CommitmentRandomness> {
+        self.rseed.try_into().ok()
+    }
+}
+
+impl ZcashSerialize for NotePlaintext {
+    fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        writer.write_all(&[self.lead_byte])?;
+        writer.write_all(&<[u8; 11]>::from(self.diversifier))?;
+        writer.write_all(&self.value.to_bytes())?;
+        writer.write_all(&self.rseed)?;
+        writer.write_all(&self.memo.0[..])?;
+        Ok(())
+    }
+}
+
+impl ZcashDeserialize for NotePlaintext {
+    fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let mut lead_byte = [0; 1];
+        reader.read_exact(&mut lead_byte)?;
+        let lead_byte = lead_byte[0];
+        if lead_byte != LEAD_BYTE_PRE_ZIP_212 && lead_byte != LEAD_BYTE_POST_ZIP_212 {
+            return Err(SerializationError::Parse(
+                "bad note plaintext lead byte: expected 0x01 or 0x02",
+            ));
+        }
+
+        let mut diversifier_bytes = [0; 11];
+        reader.read_exact(&mut diversifier_bytes)?;
+
+        let mut value_bytes = [0; 8];
+        reader.read_exact(&mut value_bytes)?;
+        let value = Amount::try_from(u64::from_le_bytes(value_bytes))?;
+
+        let mut rseed = [0; 32];
+        reader.read_exact(&mut rseed)?;
+
+        let mut memo_bytes = [0; 512];
+        reader.read_exact(&mut memo_bytes)?;
+
+        Ok(NotePlaintext {
+            lead_byte,
+            diversifier: diversifier_bytes.into(),
+            value,
+            rseed,
+            memo: Memo(Box::new(memo_bytes)),
+        })
+    }
+}
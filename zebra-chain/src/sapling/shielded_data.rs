@@ -128,6 +128,12 @@ where
             .cloned()
             .map(move |spend| Spend::<PerSpendAnchor>::from((spend, self.shared_anchor.clone())))
     }
+
+    /// Collect the anchors for this transaction's [`Spend`]s, regardless of
+    /// the underlying transaction version.
+    pub fn anchors(&self) -> impl Iterator<Item = tree::Root> + '_ {
+        self.spends_per_anchor().map(|spend| spend.per_spend_anchor)
+    }
 }
 
 impl<AnchorV> ShieldedData<AnchorV>
@@ -202,6 +208,20 @@ where
 
         key_bytes.into()
     }
+
+    /// Returns a RedJubjub batch verification item for `binding_sig`, the
+    /// signature binding this transaction's Sapling value balance to its
+    /// Spend and Output value commitments, checked against `sighash`.
+    ///
+    /// This lets callers (such as zebra-consensus) queue the signature for
+    /// batch verification without re-deriving [`Self::binding_verification_key`]
+    /// by hand at every call site.
+    pub fn binding_verification_batch_item(
+        &self,
+        sighash: &blake2b_simd::Hash,
+    ) -> redjubjub::batch::Item {
+        (self.binding_verification_key(), self.binding_sig, sighash).into()
+    }
 }
 
 // Technically, it's possible to construct two equivalent representations
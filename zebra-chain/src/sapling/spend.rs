@@ -68,6 +68,19 @@ impl From<(Spend<PerSpendAnchor>, FieldNotPresent)> for Spend<PerSpendAnchor> {
     }
 }
 
+impl<AnchorV: AnchorVariant> Spend<AnchorV> {
+    /// Returns a RedJubjub batch verification item for `spend_auth_sig`,
+    /// the signature authorizing this spend, checked against `sighash` and
+    /// `rk`.
+    ///
+    /// This lets callers (such as zebra-consensus) queue the signature for
+    /// batch verification without pulling `rk` and `spend_auth_sig` out of
+    /// `self` by hand at every call site.
+    pub fn redjubjub_batch_item(&self, sighash: &blake2b_simd::Hash) -> redjubjub::batch::Item {
+        (self.rk, self.spend_auth_sig, sighash).into()
+    }
+}
+
 impl Spend<PerSpendAnchor> {
     /// Encodes the primary inputs for the proof statement as 7 Bls12_381 base
     /// field elements, to match bellman::groth16::verify_proof.
@@ -96,34 +96,80 @@ impl From<Root> for [u8; 32] {
     }
 }
 
-/// Sapling Note Commitment Tree
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-struct NoteCommitmentTree {
-    /// The root node of the tree (often used as an anchor).
-    root: Root,
-    /// The height of the tree (maximum height for Sapling is 32).
-    height: u8,
-    /// The number of leaves (note commitments) in this tree.
-    count: u32,
+/// An error returned when appending a note commitment to a [`NoteCommitmentTree`]
+/// that has no room left.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum NoteCommitmentTreeError {
+    /// the note commitment tree is full
+    #[error("the note commitment tree is full")]
+    Full,
+}
+
+/// Sapling Note Commitment Tree.
+///
+/// Stores every note commitment appended to the tree so far, in leaf order,
+/// so that appending a note and recomputing the root are both supported.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NoteCommitmentTree {
+    /// The note commitments in this tree, in the order they were appended.
+    leaves: Vec<NoteCommitment>,
 }
 
-impl From<Vec<NoteCommitment>> for NoteCommitmentTree {
-    fn from(_values: Vec<NoteCommitment>) -> Self {
-        unimplemented!();
+impl NoteCommitmentTree {
+    /// Appends a note commitment to the note commitment tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NoteCommitmentTreeError::Full`] if the tree already has the
+    /// maximum number of leaves for `MERKLE_DEPTH`.
+    pub fn append(&mut self, cm: NoteCommitment) -> Result<(), NoteCommitmentTreeError> {
+        if self.leaves.len() >= 1usize << MERKLE_DEPTH {
+            return Err(NoteCommitmentTreeError::Full);
+        }
+
+        self.leaves.push(cm);
+        Ok(())
+    }
+
+    /// Returns the position the next appended note commitment will occupy.
+    pub fn position(&self) -> Position {
+        Position(self.leaves.len() as u64)
+    }
+
+    /// Returns the number of note commitments in this tree.
+    pub fn count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns the current root of this tree.
+    pub fn root(&self) -> Root {
+        let cm_us: Vec<jubjub::Fq> = self.leaves.iter().map(|cm| cm.0.get_u()).collect();
+        NoteCommitmentTreeRoot::from(cm_us).0
+    }
+}
+
+/// The root computed from a complete list of leaves, using the recursive
+/// `MerkleCRH^Sapling` algorithm described in the protocol specification.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct NoteCommitmentTreeRoot(Root);
+
+impl From<Vec<NoteCommitment>> for NoteCommitmentTreeRoot {
+    fn from(values: Vec<NoteCommitment>) -> Self {
+        NoteCommitmentTreeRoot::from(
+            values
+                .into_iter()
+                .map(|cm| cm.0.get_u())
+                .collect::<Vec<_>>(),
+        )
     }
 }
 
-impl From<Vec<jubjub::Fq>> for NoteCommitmentTree {
+impl From<Vec<jubjub::Fq>> for NoteCommitmentTreeRoot {
     fn from(values: Vec<jubjub::Fq>) -> Self {
         if values.is_empty() {
-            return NoteCommitmentTree {
-                root: Root::default(),
-                height: 0,
-                count: 0,
-            };
+            return NoteCommitmentTreeRoot(Root::default());
         }
 
-        let count = values.len() as u32;
         let mut height = 0u8;
         let mut current_layer: VecDeque<[u8; 32]> =
             values.into_iter().map(|cm_u| cm_u.to_bytes()).collect();
@@ -148,19 +194,15 @@ impl From<Vec<jubjub::Fq>> for NoteCommitmentTree {
 
         assert!(current_layer.len() == 1);
 
-        NoteCommitmentTree {
-            root: Root(current_layer.pop_front().unwrap()),
-            height,
-            count,
-        }
+        NoteCommitmentTreeRoot(Root(current_layer.pop_front().unwrap()))
     }
 }
 
-impl NoteCommitmentTree {
-    /// Get the Jubjub-based Pedersen hash of root node of this merkle tree of
-    /// commitment notes.
-    pub fn hash(&self) -> [u8; 32] {
-        self.root.0
+impl NoteCommitmentTreeRoot {
+    /// Returns the root hash computed from the leaves this was built from.
+    #[cfg(test)]
+    fn hash(&self) -> [u8; 32] {
+        self.0.0
     }
 }
 
@@ -270,9 +312,9 @@ mod tests {
 
             leaves.push(jubjub::Fq::from_bytes(&bytes).unwrap());
 
-            let tree = NoteCommitmentTree::from(leaves.clone());
+            let tree_root = NoteCommitmentTreeRoot::from(leaves.clone());
 
-            assert_eq!(hex::encode(tree.hash()), roots[i]);
+            assert_eq!(hex::encode(tree_root.hash()), roots[i]);
         }
     }
 }
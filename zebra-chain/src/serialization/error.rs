@@ -23,4 +23,47 @@ pub enum SerializationError {
         #[from]
         source: crate::amount::Error,
     },
+    /// A lower-level error, annotated with the message or structure type and
+    /// field being parsed when it occurred, and the byte offset into the
+    /// input at which parsing stopped, if the reader tracks one.
+    ///
+    /// Wrapping every field read across the workspace in this context would
+    /// mean changing `ZcashDeserialize`'s signature, which is generic over
+    /// `io::Read` and has no byte offset available unless the reader also
+    /// implements `io::Seek`. Instead, callers that already know which type
+    /// and field they're decoding - such as the peer message codec, which
+    /// reads each message body from a `Cursor` - can opt in with
+    /// [`SerializationError::context`], so a malformed peer message or
+    /// corrupted database entry no longer requires hexdump archaeology.
+    #[error("{source} (while parsing `{field}` of {message_type}, offset: {offset:?})")]
+    Context {
+        /// The underlying error.
+        #[source]
+        source: Box<SerializationError>,
+        /// The field being parsed when `source` occurred.
+        field: String,
+        /// The message or structure type that `field` belongs to.
+        message_type: String,
+        /// The byte offset into the input at which parsing stopped, if known.
+        offset: Option<u64>,
+    },
+}
+
+impl SerializationError {
+    /// Adds parsing context to this error: which `field` of `message_type`
+    /// was being parsed, and (if available) the byte `offset` into the
+    /// input at which parsing stopped.
+    pub fn context(
+        self,
+        field: impl Into<String>,
+        message_type: impl Into<String>,
+        offset: Option<u64>,
+    ) -> Self {
+        SerializationError::Context {
+            source: Box::new(self),
+            field: field.into(),
+            message_type: message_type.into(),
+            offset,
+        }
+    }
 }
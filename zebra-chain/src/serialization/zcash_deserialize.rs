@@ -104,6 +104,18 @@ impl ZcashDeserialize for Vec<u8> {
     }
 }
 
+// `Vec<u8>::zcash_deserialize` above already does a single bounded allocation
+// followed by one `read_exact()`, rather than growing the vector
+// incrementally, so scripts, Equihash solutions, and other variable-length
+// byte fields only ever pay for one copy out of the underlying reader.
+// (Equihash solutions and the Sprout/Sapling zk-SNARK proof types don't even
+// pay that: they're fixed-size byte arrays stored inline, with no heap
+// allocation at all.) Avoiding that one remaining copy would mean slicing
+// directly from the network layer's `bytes::Bytes` receive buffer instead of
+// reading into a fresh `Vec`, which isn't possible while `ZcashDeserialize`
+// is generic over `io::Read`: doing so would mean threading a concrete
+// `Bytes`-backed reader through every implementation in the workspace.
+
 #[cfg(test)]
 mod test_u8_deserialize {
     use super::MAX_U8_ALLOCATION;
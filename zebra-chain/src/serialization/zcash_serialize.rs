@@ -27,6 +27,67 @@ pub trait ZcashSerialize: Sized {
         self.zcash_serialize(&mut data)?;
         Ok(data)
     }
+
+    /// Serialize `self`, invoking `on_chunk` with each piece of the output
+    /// (up to `chunk_size` bytes) as it becomes available, instead of
+    /// buffering the entire serialized form in one `Vec` first.
+    ///
+    /// This lets large values - such as blocks - be written out to a peer
+    /// connection or to disk incrementally. The final, possibly shorter,
+    /// chunk is always delivered via one last call to `on_chunk`.
+    ///
+    /// This is a synchronous, allocation-bounded alternative to a true
+    /// `AsyncWrite`-based streaming serializer. Adding a real async
+    /// serializer would mean giving zebra-chain - which is otherwise a
+    /// synchronous, runtime-agnostic data-types crate - a dependency on an
+    /// async I/O runtime, and rewriting every `ZcashSerialize` impl to
+    /// stream field-by-field instead of delegating to this generic
+    /// `io::Write`-based one. That's out of scope here; this method covers
+    /// the same underlying complaint (not holding the whole value in memory
+    /// at once) without either cost.
+    fn zcash_serialize_to_chunks(
+        &self,
+        chunk_size: usize,
+        mut on_chunk: impl FnMut(&[u8]) -> Result<(), io::Error>,
+    ) -> Result<(), io::Error> {
+        struct ChunkWriter<'a> {
+            buf: Vec<u8>,
+            capacity: usize,
+            on_chunk: &'a mut dyn FnMut(&[u8]) -> Result<(), io::Error>,
+        }
+
+        impl<'a> io::Write for ChunkWriter<'a> {
+            fn write(&mut self, mut data: &[u8]) -> Result<usize, io::Error> {
+                let written = data.len();
+                while !data.is_empty() {
+                    let space = self.capacity - self.buf.len();
+                    let take = space.min(data.len());
+                    self.buf.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if self.buf.len() == self.capacity {
+                        (self.on_chunk)(&self.buf)?;
+                        self.buf.clear();
+                    }
+                }
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> Result<(), io::Error> {
+                Ok(())
+            }
+        }
+
+        let mut writer = ChunkWriter {
+            buf: Vec::with_capacity(chunk_size),
+            capacity: chunk_size.max(1),
+            on_chunk: &mut on_chunk,
+        };
+        self.zcash_serialize(&mut writer)?;
+        if !writer.buf.is_empty() {
+            (writer.on_chunk)(&writer.buf)?;
+        }
+        Ok(())
+    }
 }
 
 impl<T: ZcashSerialize> ZcashSerialize for Vec<T> {
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     amount::{Amount, NonNegative},
     block::MAX_BLOCK_BYTES,
-    primitives::{x25519, Bctv14Proof, Groth16Proof, ZkSnarkProof},
+    primitives::{ed25519, x25519, Bctv14Proof, Groth16Proof, ZkSnarkProof},
     serialization::{
         ReadZcashExt, SerializationError, TrustedPreallocate, WriteZcashExt, ZcashDeserialize,
         ZcashDeserializeInto, ZcashSerialize,
@@ -50,6 +50,39 @@ pub struct JoinSplit<P: ZkSnarkProof> {
     pub enc_ciphertexts: [note::EncryptedNote; 2],
 }
 
+impl<P: ZkSnarkProof> JoinSplit<P> {
+    /// Compute the hSig hash for this JoinSplit description, binding it to
+    /// the `joinsplit_pub_key` used to sign all of a transaction's JoinSplit
+    /// descriptions.
+    ///
+    /// hSig = BLAKE2b-256("ZcashComputehSig", randomSeed || nullifier_1 ||
+    /// nullifier_2 || joinSplitPubKey)
+    ///
+    /// This is one of the primary inputs to the JoinSplit's BCTV14 or
+    /// Groth16 proof, and is also used to check the non-malleability of the
+    /// JoinSplit signature - see [protocol specification §4.10][ps].
+    ///
+    /// Computing the *rest* of the proof's primary inputs also needs the
+    /// interstitial treestate between this and any other JoinSplits in the
+    /// same transaction, which isn't available from a `JoinSplit` alone;
+    /// that part of proof verification is not yet implemented.
+    ///
+    /// [ps]: https://zips.z.cash/protocol/protocol.pdf#sproutnonmalleability
+    pub fn h_sig(&self, joinsplit_pub_key: &ed25519::VerificationKeyBytes) -> [u8; 32] {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(b"ZcashComputehSig")
+            .to_state()
+            .update(&self.random_seed[..])
+            .update(&<[u8; 32]>::from(self.nullifiers[0])[..])
+            .update(&<[u8; 32]>::from(self.nullifiers[1])[..])
+            .update(joinsplit_pub_key.as_ref())
+            .finalize();
+
+        *hash.as_array()
+    }
+}
+
 impl<P: ZkSnarkProof> ZcashSerialize for JoinSplit<P> {
     fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
         self.vpub_old.zcash_serialize(&mut writer)?;
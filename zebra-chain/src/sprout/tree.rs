@@ -104,29 +104,69 @@ impl From<Root> for [u8; 32] {
     }
 }
 
-/// Sprout Note Commitment Tree
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// An error returned when appending a note commitment to a [`NoteCommitmentTree`]
+/// that has no room left.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum NoteCommitmentTreeError {
+    /// the note commitment tree is full
+    #[error("the note commitment tree is full")]
+    Full,
+}
+
+/// Sprout Note Commitment Tree.
+///
+/// Stores every note commitment appended to the tree so far, in leaf order,
+/// so that appending a note and recomputing the root are both supported.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Arbitrary))]
-struct NoteCommitmentTree {
-    /// The root node of the tree (often used as an anchor).
-    root: Root,
-    /// The height of the tree (maximum height for Sprout is 29).
-    height: u8,
-    /// The number of leaves (note commitments) in this tree.
-    count: u32,
+pub struct NoteCommitmentTree {
+    /// The note commitments in this tree, in the order they were appended.
+    leaves: Vec<NoteCommitment>,
 }
 
-impl From<Vec<NoteCommitment>> for NoteCommitmentTree {
+impl NoteCommitmentTree {
+    /// Appends a note commitment to the note commitment tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NoteCommitmentTreeError::Full`] if the tree already has the
+    /// maximum number of leaves for `MERKLE_DEPTH`.
+    pub fn append(&mut self, cm: NoteCommitment) -> Result<(), NoteCommitmentTreeError> {
+        if self.leaves.len() >= 1usize << MERKLE_DEPTH {
+            return Err(NoteCommitmentTreeError::Full);
+        }
+
+        self.leaves.push(cm);
+        Ok(())
+    }
+
+    /// Returns the position the next appended note commitment will occupy.
+    pub fn position(&self) -> Position {
+        Position(self.leaves.len() as u64)
+    }
+
+    /// Returns the number of note commitments in this tree.
+    pub fn count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns the current root of this tree.
+    pub fn root(&self) -> Root {
+        NoteCommitmentTreeRoot::from(self.leaves.clone()).0
+    }
+}
+
+/// The root computed from a complete list of leaves, using the recursive
+/// `MerkleCRH^Sprout` algorithm described in the protocol specification.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct NoteCommitmentTreeRoot(Root);
+
+impl From<Vec<NoteCommitment>> for NoteCommitmentTreeRoot {
     fn from(values: Vec<NoteCommitment>) -> Self {
         if values.is_empty() {
-            return NoteCommitmentTree {
-                root: Root::default(),
-                height: 0,
-                count: 0,
-            };
+            return NoteCommitmentTreeRoot(Root::default());
         }
 
-        let count = values.len() as u32;
         let mut height = 0u8;
         let mut current_layer: VecDeque<[u8; 32]> =
             values.into_iter().map(|cm| cm.into()).collect();
@@ -153,19 +193,15 @@ impl From<Vec<NoteCommitment>> for NoteCommitmentTree {
 
         assert!(current_layer.len() == 1);
 
-        NoteCommitmentTree {
-            root: Root(current_layer.pop_front().unwrap()),
-            height,
-            count,
-        }
+        NoteCommitmentTreeRoot(Root(current_layer.pop_front().unwrap()))
     }
 }
 
-impl NoteCommitmentTree {
-    /// Get the Jubjub-based Pedersen hash of root node of this merkle tree of
-    /// commitment notes.
-    pub fn hash(&self) -> [u8; 32] {
-        self.root.0
+impl NoteCommitmentTreeRoot {
+    /// Returns the root hash computed from the leaves this was built from.
+    #[cfg(test)]
+    fn hash(&self) -> [u8; 32] {
+        self.0.0
     }
 }
 
@@ -275,9 +311,21 @@ mod tests {
 
             leaves.push(NoteCommitment::from(bytes));
 
-            let tree = NoteCommitmentTree::from(leaves.clone());
+            let tree_root = NoteCommitmentTreeRoot::from(leaves.clone());
+
+            assert_eq!(hex::encode(tree_root.hash()), roots[i]);
+        }
+
+        let mut tree = NoteCommitmentTree::default();
+        for (i, cm) in commitments.iter().enumerate() {
+            let mut bytes = [0u8; 32];
+            let _ = hex::decode_to_slice(cm, &mut bytes);
+
+            tree.append(NoteCommitment::from(bytes))
+                .expect("tree should have room for the test vector's commitments");
 
-            assert_eq!(hex::encode(tree.hash()), roots[i]);
+            assert_eq!(tree.position().0, (i + 1) as u64);
+            assert_eq!(hex::encode(<[u8; 32]>::from(tree.root())), roots[i]);
         }
     }
 }
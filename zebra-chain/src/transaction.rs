@@ -1,31 +1,47 @@
 //! Transactions and transaction-related structures.
 
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+mod builder;
 mod hash;
 mod joinsplit;
 mod lock_time;
 mod memo;
 mod serialize;
 mod sighash;
+mod txid;
+mod unmined;
+mod wtxid;
+mod zip317;
 
 #[cfg(any(test, feature = "proptest-impl"))]
 mod arbitrary;
 #[cfg(test)]
 mod tests;
 
+pub use builder::{Builder, BuilderError};
 pub use hash::Hash;
 pub use joinsplit::JoinSplitData;
 pub use lock_time::LockTime;
 pub use memo::Memo;
 pub use sapling::FieldNotPresent;
 pub use sighash::HashType;
+pub use unmined::UnminedTx;
+pub use wtxid::WtxId;
+pub use zip317::{conventional_fee, GRACE_ACTIONS, MARGINAL_FEE};
 
 use crate::{
-    block,
-    parameters::NetworkUpgrade,
+    amount::{Amount, NegativeAllowed},
+    block, orchard,
+    parameters::{ConsensusBranchId, NetworkUpgrade},
     primitives::{Bctv14Proof, Groth16Proof},
-    sapling, sprout, transparent,
+    sapling,
+    serialization::ZcashSerialize,
+    sprout, transparent,
+    value_balance::{ValueBalance, ValueBalanceError},
 };
 
 /// A Zcash transaction.
@@ -106,8 +122,13 @@ pub enum Transaction {
         inputs: Vec<transparent::Input>,
         /// The transparent outputs from the transaction.
         outputs: Vec<transparent::Output>,
-        /// The rest of the transaction as bytes
-        rest: Vec<u8>,
+        /// The consensus branch id for this transaction, which binds it to a
+        /// particular network upgrade.
+        consensus_branch_id: ConsensusBranchId,
+        /// The sapling shielded data for this transaction, if any.
+        sapling_shielded_data: Option<sapling::ShieldedData<sapling::SharedAnchor>>,
+        /// The orchard shielded data for this transaction, if any.
+        orchard_shielded_data: Option<orchard::ShieldedData>,
     },
 }
 
@@ -117,6 +138,14 @@ impl Transaction {
         Hash::from(self)
     }
 
+    /// Compute the size of this transaction in its canonical wire format, in
+    /// bytes.
+    pub fn serialized_size(&self) -> usize {
+        self.zcash_serialize_to_vec()
+            .expect("Transactions must serialize into a vec")
+            .len()
+    }
+
     /// Access the transparent inputs of this transaction, regardless of version.
     pub fn inputs(&self) -> &[transparent::Input] {
         match self {
@@ -150,6 +179,25 @@ impl Transaction {
         }
     }
 
+    /// Returns `true` if this transaction's lock time allows it to be mined
+    /// at `height`, with the given `median_time_past`.
+    ///
+    /// A lock time of zero, or a `LockTime::Height` strictly less than
+    /// `height`, or a `LockTime::Time` strictly less than `median_time_past`,
+    /// allow the transaction to be mined.
+    ///
+    /// https://zips.z.cash/protocol/protocol.pdf#txnencodingandconsensus
+    pub fn lock_time_is_valid(
+        &self,
+        height: block::Height,
+        median_time_past: DateTime<Utc>,
+    ) -> bool {
+        match self.lock_time() {
+            LockTime::Height(lock_height) => lock_height.0 == 0 || lock_height < height,
+            LockTime::Time(lock_time) => lock_time < median_time_past,
+        }
+    }
+
     /// Get this transaction's expiry height, if any.
     pub fn expiry_height(&self) -> Option<block::Height> {
         match self {
@@ -161,6 +209,25 @@ impl Transaction {
         }
     }
 
+    /// Returns `true` if this transaction's expiry height allows it to be
+    /// mined at `height`.
+    ///
+    /// Transactions with no expiry height, or an expiry height of zero, never
+    /// expire. Coinbase transactions are exempt from the expiry height
+    /// consensus rule.
+    ///
+    /// https://zips.z.cash/protocol/protocol.pdf#txnencodingandconsensus
+    pub fn expiry_height_is_valid(&self, height: block::Height) -> bool {
+        if self.is_coinbase() {
+            return true;
+        }
+
+        match self.expiry_height() {
+            None | Some(block::Height(0)) => true,
+            Some(expiry_height) => height <= expiry_height,
+        }
+    }
+
     /// Access the sprout::Nullifiers in this transaction, regardless of version.
     pub fn sprout_nullifiers(&self) -> Box<dyn Iterator<Item = &sprout::Nullifier> + '_> {
         // This function returns a boxed iterator because the different
@@ -221,17 +288,251 @@ impl Transaction {
                 sapling_shielded_data: Some(sapling_shielded_data),
                 ..
             } => Box::new(sapling_shielded_data.nullifiers()),
+            Transaction::V5 {
+                sapling_shielded_data: Some(sapling_shielded_data),
+                ..
+            } => Box::new(sapling_shielded_data.nullifiers()),
+            // No JoinSplits
+            Transaction::V1 { .. }
+            | Transaction::V2 { .. }
+            | Transaction::V3 { .. }
+            | Transaction::V4 {
+                sapling_shielded_data: None,
+                ..
+            }
+            | Transaction::V5 {
+                sapling_shielded_data: None,
+                ..
+            } => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Access the orchard::Nullifiers in this transaction, regardless of version.
+    pub fn orchard_nullifiers(&self) -> Box<dyn Iterator<Item = &orchard::Nullifier> + '_> {
+        match self {
+            Transaction::V5 {
+                orchard_shielded_data: Some(orchard_shielded_data),
+                ..
+            } => Box::new(orchard_shielded_data.nullifiers()),
+            Transaction::V1 { .. }
+            | Transaction::V2 { .. }
+            | Transaction::V3 { .. }
+            | Transaction::V4 { .. }
+            | Transaction::V5 {
+                orchard_shielded_data: None,
+                ..
+            } => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Access the sprout note commitment tree anchors in this transaction,
+    /// regardless of version.
+    pub fn sprout_anchors(&self) -> Box<dyn Iterator<Item = sprout::tree::Root> + '_> {
+        // This function returns a boxed iterator because the different
+        // transaction variants end up having different iterator types
+        match self {
+            // JoinSplits with Bctv14 Proofs
+            Transaction::V2 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            }
+            | Transaction::V3 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            } => Box::new(joinsplit_data.joinsplits().map(|joinsplit| joinsplit.anchor)),
+            // JoinSplits with Groth Proofs
+            Transaction::V4 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            } => Box::new(joinsplit_data.joinsplits().map(|joinsplit| joinsplit.anchor)),
+            // Maybe JoinSplits, maybe not, we're still deciding
             Transaction::V5 { .. } => {
-                unimplemented!("v5 transaction format as specified in ZIP-225")
+                unimplemented!(
+                    "v5 transaction format as specified in ZIP-225 after decision on 2021-03-12"
+                )
             }
             // No JoinSplits
             Transaction::V1 { .. }
+            | Transaction::V2 {
+                joinsplit_data: None,
+                ..
+            }
+            | Transaction::V3 {
+                joinsplit_data: None,
+                ..
+            }
+            | Transaction::V4 {
+                joinsplit_data: None,
+                ..
+            } => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Access the sapling note commitment tree anchors in this transaction,
+    /// regardless of version.
+    pub fn sapling_anchors(&self) -> Box<dyn Iterator<Item = sapling::tree::Root> + '_> {
+        // This function returns a boxed iterator because the different
+        // transaction variants end up having different iterator types
+        match self {
+            Transaction::V4 {
+                sapling_shielded_data: Some(sapling_shielded_data),
+                ..
+            } => Box::new(sapling_shielded_data.anchors()),
+            Transaction::V5 {
+                sapling_shielded_data: Some(sapling_shielded_data),
+                ..
+            } => Box::new(sapling_shielded_data.anchors()),
+            // No Spends
+            Transaction::V1 { .. }
             | Transaction::V2 { .. }
             | Transaction::V3 { .. }
             | Transaction::V4 {
                 sapling_shielded_data: None,
                 ..
+            }
+            | Transaction::V5 {
+                sapling_shielded_data: None,
+                ..
+            } => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Access the orchard note commitment tree anchor in this transaction, if
+    /// it has any [`orchard::Action`](crate::orchard::Action)s.
+    pub fn orchard_anchor(&self) -> Option<orchard::tree::Root> {
+        match self {
+            Transaction::V5 {
+                orchard_shielded_data: Some(orchard_shielded_data),
+                ..
+            } => Some(orchard_shielded_data.shared_anchor),
+            Transaction::V1 { .. }
+            | Transaction::V2 { .. }
+            | Transaction::V3 { .. }
+            | Transaction::V4 { .. }
+            | Transaction::V5 {
+                orchard_shielded_data: None,
+                ..
+            } => None,
+        }
+    }
+
+    /// Returns the change in the transparent, Sprout, Sapling, and Orchard
+    /// value pools caused by this transaction.
+    ///
+    /// The transparent pool component is always zero, because computing it
+    /// requires the values of this transaction's transparent inputs, which
+    /// aren't available from a [`Transaction`] alone.
+    pub fn value_balance(&self) -> Result<ValueBalance, ValueBalanceError> {
+        let transparent = Amount::try_from(0).expect("0 is always a valid Amount");
+
+        Ok(ValueBalance::from_components(
+            transparent,
+            self.sprout_value_balance()?,
+            self.sapling_value_balance(),
+            self.orchard_value_balance(),
+        ))
+    }
+
+    /// Returns the net value added to the Sprout pool by this
+    /// transaction's JoinSplits, regardless of transaction version.
+    ///
+    /// Each JoinSplit removes `vpub_old` from the transparent pool into the
+    /// Sprout pool, and removes `vpub_new` from the Sprout pool into the
+    /// transparent pool, so the Sprout pool's value changes by
+    /// `vpub_old - vpub_new` for each JoinSplit.
+    fn sprout_value_balance(&self) -> Result<Amount<NegativeAllowed>, ValueBalanceError> {
+        let joinsplits: Box<dyn Iterator<Item = _>> = match self {
+            Transaction::V2 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            }
+            | Transaction::V3 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            } => Box::new(joinsplit_data.joinsplits()),
+            Transaction::V4 {
+                joinsplit_data: Some(joinsplit_data),
+                ..
+            } => Box::new(joinsplit_data.joinsplits()),
+            // Maybe JoinSplits, maybe not, we're still deciding
+            // (ZIP-225 hasn't finalised the Sprout fields of `Transaction::V5`).
+            Transaction::V5 { .. } => {
+                unimplemented!("Transaction::V5 does not yet support Sprout JoinSplits")
+            }
+            Transaction::V1 { .. }
+            | Transaction::V2 {
+                joinsplit_data: None,
+                ..
+            }
+            | Transaction::V3 {
+                joinsplit_data: None,
+                ..
+            }
+            | Transaction::V4 {
+                joinsplit_data: None,
+                ..
             } => Box::new(std::iter::empty()),
+        };
+
+        let zero = Amount::try_from(0).expect("0 is always a valid Amount");
+
+        joinsplits.fold(Ok(zero), |total, joinsplit| {
+            let vpub_old: Amount<NegativeAllowed> = joinsplit
+                .vpub_old
+                .constrain()
+                .map_err(ValueBalanceError::Sprout)?;
+            let vpub_new: Amount<NegativeAllowed> = joinsplit
+                .vpub_new
+                .constrain()
+                .map_err(ValueBalanceError::Sprout)?;
+            let delta = (vpub_old - vpub_new).map_err(ValueBalanceError::Sprout)?;
+
+            (total? + delta).map_err(ValueBalanceError::Sprout)
+        })
+    }
+
+    /// Returns the net value removed from the Sapling pool by this
+    /// transaction's Sapling spends and outputs, or zero if it has none.
+    fn sapling_value_balance(&self) -> Amount<NegativeAllowed> {
+        match self {
+            Transaction::V4 {
+                sapling_shielded_data: Some(sapling_shielded_data),
+                ..
+            } => sapling_shielded_data.value_balance,
+            Transaction::V5 {
+                sapling_shielded_data: Some(sapling_shielded_data),
+                ..
+            } => sapling_shielded_data.value_balance,
+            Transaction::V1 { .. }
+            | Transaction::V2 { .. }
+            | Transaction::V3 { .. }
+            | Transaction::V4 {
+                sapling_shielded_data: None,
+                ..
+            }
+            | Transaction::V5 {
+                sapling_shielded_data: None,
+                ..
+            } => Amount::try_from(0).expect("0 is always a valid Amount"),
+        }
+    }
+
+    /// Returns the net value removed from the Orchard pool by this
+    /// transaction's Orchard actions, or zero if it has none.
+    fn orchard_value_balance(&self) -> Amount<NegativeAllowed> {
+        match self {
+            Transaction::V5 {
+                orchard_shielded_data: Some(orchard_shielded_data),
+                ..
+            } => orchard_shielded_data.value_balance,
+            Transaction::V1 { .. }
+            | Transaction::V2 { .. }
+            | Transaction::V3 { .. }
+            | Transaction::V4 { .. }
+            | Transaction::V5 {
+                orchard_shielded_data: None,
+                ..
+            } => Amount::try_from(0).expect("0 is always a valid Amount"),
         }
     }
 
@@ -251,6 +552,19 @@ impl Transaction {
             )
     }
 
+    /// Returns the height set in this transaction's coinbase input, if any.
+    ///
+    /// Returns `None` for non-coinbase transactions, which is required by
+    /// subsidy validation and block template construction, both of which
+    /// need the height encoded in a coinbase transaction without requiring
+    /// the rest of the block.
+    pub fn coinbase_height(&self) -> Option<block::Height> {
+        self.inputs().get(0).and_then(|input| match input {
+            transparent::Input::Coinbase { height, .. } => Some(*height),
+            _ => None,
+        })
+    }
+
     /// Calculate the sighash for the current transaction
     ///
     /// # Details
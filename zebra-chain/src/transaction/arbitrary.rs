@@ -7,20 +7,30 @@ use proptest::{arbitrary::any, array, collection::vec, option, prelude::*};
 use crate::LedgerState;
 use crate::{
     amount::Amount,
-    block,
-    parameters::NetworkUpgrade,
+    block, orchard,
+    parameters::{ConsensusBranchId, NetworkUpgrade},
     primitives::{Bctv14Proof, Groth16Proof, ZkSnarkProof},
     sapling, sprout, transparent,
 };
 
 use super::{FieldNotPresent, JoinSplitData, LockTime, Memo, Transaction};
 
+/// The maximum number of transparent inputs/outputs, shielded spends/outputs,
+/// or joinsplits generated for a single `Transaction` by the default
+/// `Arbitrary` strategies below.
+///
+/// Recursive `Arbitrary` strategies over `Transaction` and `Block` multiply
+/// this out across every nested collection, so keeping it small is what
+/// keeps proptest runs fast. The `v1_strategy`..`v5_strategy` constructors
+/// also use this fixed limit: they take no item count parameter.
+const DEFAULT_ARBITRARY_ITEMS: usize = 4;
+
 impl Transaction {
     /// Generate a proptest strategy for V1 Transactions
     pub fn v1_strategy(ledger_state: LedgerState) -> BoxedStrategy<Self> {
         (
-            transparent::Input::vec_strategy(ledger_state, 10),
-            vec(any::<transparent::Output>(), 0..10),
+            transparent::Input::vec_strategy(ledger_state, DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<transparent::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
             any::<LockTime>(),
         )
             .prop_map(|(inputs, outputs, lock_time)| Transaction::V1 {
@@ -34,8 +44,8 @@ impl Transaction {
     /// Generate a proptest strategy for V2 Transactions
     pub fn v2_strategy(ledger_state: LedgerState) -> BoxedStrategy<Self> {
         (
-            transparent::Input::vec_strategy(ledger_state, 10),
-            vec(any::<transparent::Output>(), 0..10),
+            transparent::Input::vec_strategy(ledger_state, DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<transparent::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
             any::<LockTime>(),
             option::of(any::<JoinSplitData<Bctv14Proof>>()),
         )
@@ -53,8 +63,8 @@ impl Transaction {
     /// Generate a proptest strategy for V3 Transactions
     pub fn v3_strategy(ledger_state: LedgerState) -> BoxedStrategy<Self> {
         (
-            transparent::Input::vec_strategy(ledger_state, 10),
-            vec(any::<transparent::Output>(), 0..10),
+            transparent::Input::vec_strategy(ledger_state, DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<transparent::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
             any::<LockTime>(),
             any::<block::Height>(),
             option::of(any::<JoinSplitData<Bctv14Proof>>()),
@@ -74,8 +84,8 @@ impl Transaction {
     /// Generate a proptest strategy for V4 Transactions
     pub fn v4_strategy(ledger_state: LedgerState) -> BoxedStrategy<Self> {
         (
-            transparent::Input::vec_strategy(ledger_state, 10),
-            vec(any::<transparent::Output>(), 0..10),
+            transparent::Input::vec_strategy(ledger_state, DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<transparent::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
             any::<LockTime>(),
             any::<block::Height>(),
             option::of(any::<sapling::ShieldedData<sapling::PerSpendAnchor>>()),
@@ -106,17 +116,29 @@ impl Transaction {
         (
             any::<LockTime>(),
             any::<block::Height>(),
-            transparent::Input::vec_strategy(ledger_state, 10),
-            vec(any::<transparent::Output>(), 0..10),
-            any::<Vec<u8>>(),
+            transparent::Input::vec_strategy(ledger_state, DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<transparent::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
+            any::<ConsensusBranchId>(),
+            option::of(any::<sapling::ShieldedData<sapling::SharedAnchor>>()),
+            option::of(any::<orchard::ShieldedData>()),
         )
             .prop_map(
-                |(lock_time, expiry_height, inputs, outputs, rest)| Transaction::V5 {
+                |(
+                    lock_time,
+                    expiry_height,
+                    inputs,
+                    outputs,
+                    consensus_branch_id,
+                    sapling_shielded_data,
+                    orchard_shielded_data,
+                )| Transaction::V5 {
                     lock_time,
                     expiry_height,
                     inputs,
                     outputs,
-                    rest,
+                    consensus_branch_id,
+                    sapling_shielded_data,
+                    orchard_shielded_data,
                 },
             )
             .boxed()
@@ -182,7 +204,7 @@ impl<P: ZkSnarkProof + Arbitrary + 'static> Arbitrary for JoinSplitData<P> {
     fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
         (
             any::<sprout::JoinSplit<P>>(),
-            vec(any::<sprout::JoinSplit<P>>(), 0..10),
+            vec(any::<sprout::JoinSplit<P>>(), 0..DEFAULT_ARBITRARY_ITEMS),
             array::uniform32(any::<u8>()),
             vec(any::<u8>(), 64),
         )
@@ -212,8 +234,8 @@ impl Arbitrary for sapling::ShieldedData<sapling::PerSpendAnchor> {
                 any::<sapling::Spend<sapling::PerSpendAnchor>>().prop_map(Either::Left),
                 any::<sapling::Output>().prop_map(Either::Right)
             ],
-            vec(any::<sapling::Spend<sapling::PerSpendAnchor>>(), 0..10),
-            vec(any::<sapling::Output>(), 0..10),
+            vec(any::<sapling::Spend<sapling::PerSpendAnchor>>(), 0..DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<sapling::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
             vec(any::<u8>(), 64),
         )
             .prop_map(
@@ -236,6 +258,43 @@ impl Arbitrary for sapling::ShieldedData<sapling::PerSpendAnchor> {
     type Strategy = BoxedStrategy<Self>;
 }
 
+impl Arbitrary for sapling::ShieldedData<sapling::SharedAnchor> {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (
+            any::<Amount>(),
+            any::<sapling::tree::Root>(),
+            prop_oneof![
+                any::<sapling::Spend<sapling::SharedAnchor>>().prop_map(Either::Left),
+                any::<sapling::Output>().prop_map(Either::Right)
+            ],
+            vec(any::<sapling::Spend<sapling::SharedAnchor>>(), 0..DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<sapling::Output>(), 0..DEFAULT_ARBITRARY_ITEMS),
+            vec(any::<u8>(), 64),
+        )
+            .prop_map(
+                |(value_balance, shared_anchor, first, rest_spends, rest_outputs, sig_bytes)| {
+                    Self {
+                        value_balance,
+                        shared_anchor,
+                        first,
+                        rest_spends,
+                        rest_outputs,
+                        binding_sig: redjubjub::Signature::from({
+                            let mut b = [0u8; 64];
+                            b.copy_from_slice(sig_bytes.as_slice());
+                            b
+                        }),
+                    }
+                },
+            )
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
 impl Arbitrary for Transaction {
     type Parameters = LedgerState;
 
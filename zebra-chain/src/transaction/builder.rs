@@ -0,0 +1,170 @@
+//! An incremental builder for assembling transactions.
+
+use thiserror::Error;
+
+use crate::{block, transparent};
+
+use super::{LockTime, Transaction};
+
+/// An error returned when a [`Builder`] cannot produce a valid transaction.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum BuilderError {
+    /// a transaction must have at least one input
+    #[error("a transaction must have at least one input")]
+    NoInputs,
+    /// a transaction must have at least one output
+    #[error("a transaction must have at least one output")]
+    NoOutputs,
+}
+
+/// Incrementally assembles a transparent `V4` transaction.
+///
+/// `Builder` only supports transparent inputs and outputs for now. Shielded
+/// support can be added by extending this type with Sapling/Orchard bundle
+/// builders, once Zebra can construct the corresponding proofs.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    lock_time: LockTime,
+    expiry_height: block::Height,
+    inputs: Vec<transparent::Input>,
+    outputs: Vec<transparent::Output>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            lock_time: LockTime::Height(block::Height(0)),
+            expiry_height: block::Height(0),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl Builder {
+    /// Returns a new, empty transaction builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the transaction's lock time. Defaults to `LockTime::Height(block::Height(0))`.
+    pub fn with_lock_time(mut self, lock_time: LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Sets the transaction's expiry height. Defaults to `block::Height(0)`,
+    /// meaning the transaction never expires.
+    pub fn with_expiry_height(mut self, expiry_height: block::Height) -> Self {
+        self.expiry_height = expiry_height;
+        self
+    }
+
+    /// Appends a transparent input to the transaction.
+    pub fn add_input(mut self, input: transparent::Input) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Appends a transparent output to the transaction.
+    pub fn add_output(mut self, output: transparent::Output) -> Self {
+        self.outputs.push(output);
+        self
+    }
+
+    /// Consumes the builder, returning the assembled transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuilderError::NoInputs`] or [`BuilderError::NoOutputs`] if
+    /// the transaction has no transparent inputs or outputs.
+    pub fn finish(self) -> Result<Transaction, BuilderError> {
+        if self.inputs.is_empty() {
+            return Err(BuilderError::NoInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(BuilderError::NoOutputs);
+        }
+
+        Ok(Transaction::V4 {
+            inputs: self.inputs,
+            outputs: self.outputs,
+            lock_time: self.lock_time,
+            expiry_height: self.expiry_height,
+            joinsplit_data: None,
+            sapling_shielded_data: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amount::Amount,
+        serialization::{ZcashDeserializeInto, ZcashSerialize},
+        transparent::{CoinbaseData, OutPoint, Script},
+    };
+
+    fn input() -> transparent::Input {
+        transparent::Input::PrevOut {
+            outpoint: OutPoint {
+                hash: [0; 32].into(),
+                index: 0,
+            },
+            unlock_script: Script(vec![]),
+            sequence: 0,
+        }
+    }
+
+    fn output() -> transparent::Output {
+        transparent::Output {
+            value: Amount::try_from(0).unwrap(),
+            lock_script: Script(vec![]),
+        }
+    }
+
+    #[test]
+    fn empty_builder_rejects_missing_inputs() {
+        zebra_test::init();
+
+        assert_eq!(Builder::new().add_output(output()).finish(), Err(BuilderError::NoInputs));
+    }
+
+    #[test]
+    fn empty_builder_rejects_missing_outputs() {
+        zebra_test::init();
+
+        assert_eq!(Builder::new().add_input(input()).finish(), Err(BuilderError::NoOutputs));
+    }
+
+    #[test]
+    fn builder_assembles_serializable_transaction() {
+        zebra_test::init();
+
+        let transaction = Builder::new()
+            .add_input(input())
+            .add_output(output())
+            .with_expiry_height(block::Height(1))
+            .finish()
+            .expect("builder with an input and an output should succeed");
+
+        let data = transaction
+            .zcash_serialize_to_vec()
+            .expect("built transaction should serialize");
+        let round_tripped: Transaction = data
+            .zcash_deserialize_into()
+            .expect("serialized transaction should deserialize");
+
+        assert_eq!(transaction, round_tripped);
+    }
+
+    #[allow(dead_code)]
+    fn coinbase_input() -> transparent::Input {
+        transparent::Input::Coinbase {
+            height: block::Height(1),
+            data: CoinbaseData(vec![]),
+            sequence: 0,
+        }
+    }
+}
@@ -1,9 +1,9 @@
 #![allow(clippy::unit_arg)]
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 #[cfg(any(test, feature = "proptest-impl"))]
 use proptest_derive::Arbitrary;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::serialization::{sha256d, SerializationError, ZcashSerialize};
 
@@ -13,12 +13,71 @@ use super::Transaction;
 ///
 /// Note: Zebra displays transaction and block hashes in big-endian byte-order,
 /// following the u256 convention set by Bitcoin and zcashd.
-#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
 pub struct Hash(pub [u8; 32]);
 
+impl Serialize for Hash {
+    /// Serializes `Hash` as hex in human-readable formats like JSON, and as
+    /// raw bytes in non-human-readable formats like bincode.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            struct HexHashVisitor;
+
+            impl<'de> de::Visitor<'de> for HexHashVisitor {
+                type Value = Hash;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a hex-encoded transaction hash")
+                }
+
+                fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Hash::from_str(s).map_err(de::Error::custom)
+                }
+            }
+
+            deserializer.deserialize_str(HexHashVisitor)
+        } else {
+            Ok(Hash(<[u8; 32]>::deserialize(deserializer)?))
+        }
+    }
+}
+
 impl<'a> From<&'a Transaction> for Hash {
     fn from(transaction: &'a Transaction) -> Self {
+        // `V5` transactions are supposed to use the NU5 transaction digest
+        // algorithm specified in ZIP-244, rather than a flat double-SHA256
+        // over the whole transaction. [`txid::txid_digest`] implements that
+        // algorithm, but Zebra has no official ZIP-244 test vectors to check
+        // it against (see its module documentation), so it isn't wired in
+        // here yet: a self-consistent-but-wrong hash would silently diverge
+        // from the rest of the network for gossip, wtxid, and merkle roots.
+        if let Transaction::V5 { .. } = transaction {
+            unimplemented!(
+                "V5 transaction ID: the NU5 digest algorithm in ZIP-244 isn't verified against \
+                 official test vectors yet"
+            );
+        }
+
         let mut hash_writer = sha256d::Writer::default();
         transaction
             .zcash_serialize(&mut hash_writer)
@@ -48,4 +48,14 @@ impl<P: ZkSnarkProof> JoinSplitData<P> {
     pub fn joinsplits(&self) -> impl Iterator<Item = &JoinSplit<P>> {
         std::iter::once(&self.first).chain(self.rest.iter())
     }
+
+    /// Returns a batch verification item for the JoinSplit signature over
+    /// `sighash`, checked against `pub_key`.
+    ///
+    /// This lets callers (such as zebra-consensus) queue the signature for
+    /// batch verification without pulling `pub_key` and `sig` out of `self`
+    /// by hand at every call site.
+    pub fn ed25519_batch_item(&self, sighash: &blake2b_simd::Hash) -> ed25519::batch::Item {
+        (self.pub_key, self.sig, sighash).into()
+    }
 }
@@ -56,6 +56,32 @@ impl LockTime {
     pub fn max_lock_time() -> LockTime {
         LockTime::Time(Utc.timestamp(Self::MAX_TIMESTAMP, 0))
     }
+
+    /// Returns `true` if this `LockTime` unlocks at a block height.
+    pub fn is_block_height(&self) -> bool {
+        matches!(self, LockTime::Height(_))
+    }
+
+    /// Returns `true` if this `LockTime` unlocks at a time.
+    pub fn is_time(&self) -> bool {
+        matches!(self, LockTime::Time(_))
+    }
+}
+
+impl PartialOrd for LockTime {
+    /// `LockTime`s only have a partial order: a height and a time are
+    /// incomparable, matching the consensus rule that nLockTime is checked
+    /// against either the block height or the median time, but never both
+    /// (see [`Transaction::lock_time_is_valid`](super::Transaction::lock_time_is_valid)).
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (LockTime::Height(a), LockTime::Height(b)) => a.partial_cmp(b),
+            (LockTime::Time(a), LockTime::Time(b)) => a.partial_cmp(b),
+            (LockTime::Height(_), LockTime::Time(_)) | (LockTime::Time(_), LockTime::Height(_)) => {
+                None
+            }
+        }
+    }
 }
 
 impl ZcashSerialize for LockTime {
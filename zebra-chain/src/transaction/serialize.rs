@@ -187,18 +187,72 @@ impl ZcashSerialize for Transaction {
                 expiry_height,
                 inputs,
                 outputs,
-                rest,
+                consensus_branch_id,
+                sapling_shielded_data,
+                orchard_shielded_data,
             } => {
                 // Write version 5 and set the fOverwintered bit.
                 writer.write_u32::<LittleEndian>(5 | (1 << 31))?;
                 writer.write_u32::<LittleEndian>(TX_V5_VERSION_GROUP_ID)?;
+                writer.write_u32::<LittleEndian>((*consensus_branch_id).into())?;
                 lock_time.zcash_serialize(&mut writer)?;
                 writer.write_u32::<LittleEndian>(expiry_height.0)?;
                 inputs.zcash_serialize(&mut writer)?;
                 outputs.zcash_serialize(&mut writer)?;
 
-                // write the rest
-                writer.write_all(rest)?;
+                // As with the `V4` arm above, the Sapling zkproofs and
+                // spend_auth_sigs are placed in their own trailing arrays,
+                // rather than inline with each Spend, so we pull the
+                // component parts out of `ShieldedData` and interleave them
+                // manually.
+
+                match sapling_shielded_data {
+                    None => {
+                        // Signal no shielded spends and no shielded outputs.
+                        // Unlike `V4`, the value balance, anchor, and binding
+                        // signature are omitted entirely when there is no
+                        // Sapling shielded data.
+                        writer.write_compactsize(0)?;
+                        writer.write_compactsize(0)?;
+                    }
+                    Some(sd) => {
+                        writer.write_compactsize(sd.spends().count() as u64)?;
+                        for spend in sd.spends() {
+                            spend.zcash_serialize(&mut writer)?;
+                        }
+                        writer.write_compactsize(sd.outputs().count() as u64)?;
+                        for output in sd.outputs() {
+                            output.zcash_serialize(&mut writer)?;
+                        }
+                        sd.value_balance.zcash_serialize(&mut writer)?;
+                        writer.write_all(&sd.shared_anchor.0[..])?;
+                        for spend in sd.spends() {
+                            spend.zkproof.zcash_serialize(&mut writer)?;
+                        }
+                        for spend in sd.spends() {
+                            writer.write_all(&<[u8; 64]>::from(spend.spend_auth_sig)[..])?;
+                        }
+                        writer.write_all(&<[u8; 64]>::from(sd.binding_sig)[..])?;
+                    }
+                }
+
+                match orchard_shielded_data {
+                    None => writer.write_compactsize(0)?,
+                    Some(osd) => {
+                        writer.write_compactsize(osd.actions().count() as u64)?;
+                        for action in osd.actions() {
+                            action.zcash_serialize(&mut writer)?;
+                        }
+                        osd.flags.zcash_serialize(&mut writer)?;
+                        osd.value_balance.zcash_serialize(&mut writer)?;
+                        writer.write_all(&osd.shared_anchor.0[..])?;
+                        osd.proof.zcash_serialize(&mut writer)?;
+                        for sig in osd.actions_with_auth_sigs().map(|(_, sig)| sig) {
+                            writer.write_all(&sig.0[..])?;
+                        }
+                        writer.write_all(&osd.binding_sig.0[..])?;
+                    }
+                }
             }
         }
         Ok(())
@@ -311,25 +365,117 @@ impl ZcashDeserialize for Transaction {
                     joinsplit_data,
                 })
             }
-            (5, false) => {
+            (5, true) => {
                 let id = reader.read_u32::<LittleEndian>()?;
                 if id != TX_V5_VERSION_GROUP_ID {
                     return Err(SerializationError::Parse("expected TX_V5_VERSION_GROUP_ID"));
                 }
+                let consensus_branch_id = ConsensusBranchId::from(reader.read_u32::<LittleEndian>()?);
                 let lock_time = LockTime::zcash_deserialize(&mut reader)?;
                 let expiry_height = block::Height(reader.read_u32::<LittleEndian>()?);
                 let inputs = Vec::zcash_deserialize(&mut reader)?;
                 let outputs = Vec::zcash_deserialize(&mut reader)?;
 
-                let mut rest = Vec::new();
-                reader.read_to_end(&mut rest)?;
+                // As in the `V4` arm above, the Sapling zkproofs and
+                // spend_auth_sigs are pulled out of their own trailing
+                // arrays, and assembled back onto each `Spend`.
+
+                let spend_count = reader.read_compactsize()?;
+                let mut shielded_spends = Vec::with_capacity(spend_count as usize);
+                for _ in 0..spend_count {
+                    use crate::sapling::{commitment::ValueCommitment, note::Nullifier};
+                    shielded_spends.push(sapling::Spend::<sapling::SharedAnchor> {
+                        cv: ValueCommitment::zcash_deserialize(&mut reader)?,
+                        per_spend_anchor: FieldNotPresent,
+                        nullifier: Nullifier::from(reader.read_32_bytes()?),
+                        rk: reader.read_32_bytes()?.into(),
+                        // The zkproof and spend_auth_sig are deserialized
+                        // separately below, from their own trailing arrays.
+                        zkproof: Groth16Proof::from([0; 192]),
+                        spend_auth_sig: [0; 64].into(),
+                    });
+                }
+                let mut shielded_outputs = Vec::zcash_deserialize(&mut reader)?;
+
+                let sapling_shielded_data = if !shielded_spends.is_empty() || !shielded_outputs.is_empty() {
+                    let value_balance = (&mut reader).zcash_deserialize_into()?;
+                    let shared_anchor = sapling::tree::Root(reader.read_32_bytes()?);
+
+                    for spend in shielded_spends.iter_mut() {
+                        spend.zkproof = Groth16Proof::zcash_deserialize(&mut reader)?;
+                    }
+                    for spend in shielded_spends.iter_mut() {
+                        spend.spend_auth_sig = reader.read_64_bytes()?.into();
+                    }
+
+                    let binding_sig = reader.read_64_bytes()?.into();
+
+                    use futures::future::Either::*;
+                    if !shielded_spends.is_empty() {
+                        Some(sapling::ShieldedData {
+                            value_balance,
+                            shared_anchor,
+                            first: Left(shielded_spends.remove(0)),
+                            rest_spends: shielded_spends,
+                            rest_outputs: shielded_outputs,
+                            binding_sig,
+                        })
+                    } else {
+                        Some(sapling::ShieldedData {
+                            value_balance,
+                            shared_anchor,
+                            first: Right(shielded_outputs.remove(0)),
+                            rest_spends: shielded_spends,
+                            rest_outputs: shielded_outputs,
+                            binding_sig,
+                        })
+                    }
+                } else {
+                    None
+                };
+
+                let actions: Vec<orchard::Action> = Vec::zcash_deserialize(&mut reader)?;
+
+                let orchard_shielded_data = if !actions.is_empty() {
+                    let flags = orchard::Flags::zcash_deserialize(&mut reader)?;
+                    let value_balance = (&mut reader).zcash_deserialize_into()?;
+                    let shared_anchor = orchard::tree::Root(reader.read_32_bytes()?);
+                    let proof = orchard::Halo2Proof::zcash_deserialize(&mut reader)?;
+
+                    let mut spend_auth_sigs = Vec::with_capacity(actions.len());
+                    for _ in 0..actions.len() {
+                        spend_auth_sigs.push(orchard::SpendAuthSig(reader.read_64_bytes()?));
+                    }
+                    let binding_sig = orchard::BindingSig(reader.read_64_bytes()?);
+
+                    let mut actions = actions;
+                    let first = actions.remove(0);
+                    let mut rest_spend_auth_sigs = spend_auth_sigs;
+                    let first_spend_auth_sig = rest_spend_auth_sigs.remove(0);
+
+                    Some(orchard::ShieldedData {
+                        flags,
+                        value_balance,
+                        shared_anchor,
+                        proof,
+                        first,
+                        rest: actions,
+                        first_spend_auth_sig,
+                        rest_spend_auth_sigs,
+                        binding_sig,
+                    })
+                } else {
+                    None
+                };
 
                 Ok(Transaction::V5 {
                     lock_time,
                     expiry_height,
                     inputs,
                     outputs,
-                    rest,
+                    consensus_branch_id,
+                    sapling_shielded_data,
+                    orchard_shielded_data,
                 })
             }
             (_, _) => Err(SerializationError::Parse("bad tx header")),
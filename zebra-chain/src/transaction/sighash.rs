@@ -1,4 +1,4 @@
-use super::Transaction;
+use super::{txid, Transaction};
 use crate::{
     parameters::{
         ConsensusBranchId, NetworkUpgrade, OVERWINTER_VERSION_GROUP_ID, SAPLING_VERSION_GROUP_ID,
@@ -26,9 +26,13 @@ const ZCASH_SHIELDED_OUTPUTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZcashSOutputHas
 bitflags::bitflags! {
     /// The different SigHash types, as defined in https://zips.z.cash/zip-0143
     pub struct HashType: u32 {
+        /// Sign all the outputs.
         const ALL = 0b0000_0001;
+        /// Sign none of the outputs, so they can be changed by anyone.
         const NONE = 0b0000_0010;
+        /// Sign only the output with the same index as this input.
         const SINGLE = Self::ALL.bits | Self::NONE.bits;
+        /// Sign only this input, so other inputs can be added by anyone.
         const ANYONECANPAY = 0b1000_0000;
     }
 }
@@ -72,6 +76,11 @@ impl<'a> SigHasher<'a> {
 
     pub(super) fn sighash(self) -> Hash {
         use NetworkUpgrade::*;
+
+        if let Nu5 = self.network_upgrade {
+            return self.hash_sighash_zip244();
+        }
+
         let mut hash = blake2b_simd::Params::new()
             .hash_length(32)
             .personal(&self.personal())
@@ -85,14 +94,39 @@ impl<'a> SigHasher<'a> {
             Sapling | Blossom | Heartwood | Canopy => self
                 .hash_sighash_zip243(&mut hash)
                 .expect("serialization into hasher never fails"),
-            Nu5 => unimplemented!(
-                "Nu5 upgrade uses a new transaction digest algorithm, as specified in ZIP-244"
-            ),
+            Nu5 => unreachable!("handled above"),
         }
 
         hash.finalize()
     }
 
+    /// Sighash implementation for the Nu5 network upgrade, as specified in
+    /// ZIP-244.
+    ///
+    /// Unlike ZIP-143/ZIP-243, the signature hash is not a single linear
+    /// BLAKE2b personalization over the concatenated fields; instead it
+    /// combines the transaction's txid and authorizing-data digests (see
+    /// [`txid`]) with a digest of the transparent input being signed.
+    fn hash_sighash_zip244(&self) -> Hash {
+        let txid_digest = txid::txid_digest(self.trans);
+        let auth_digest = txid::auth_digest(self.trans);
+        let transparent_sig_digest = txid::transparent_sig_digest(
+            self.input
+                .as_ref()
+                .map(|(prevout, input, _)| (prevout, *input)),
+        );
+
+        blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(&txid::txid_personalization(self.consensus_branch_id()))
+            .to_state()
+            .update(&self.hash_type.bits().to_le_bytes())
+            .update(txid_digest.as_bytes())
+            .update(auth_digest.as_bytes())
+            .update(transparent_sig_digest.as_bytes())
+            .finalize()
+    }
+
     fn consensus_branch_id(&self) -> ConsensusBranchId {
         self.network_upgrade.branch_id().expect(ZIP143_EXPLANATION)
     }
@@ -3,7 +3,10 @@ use std::io::Cursor;
 
 use super::super::*;
 
-use crate::serialization::{ZcashDeserialize, ZcashDeserializeInto, ZcashSerialize};
+use crate::{
+    serialization::{ZcashDeserialize, ZcashDeserializeInto, ZcashSerialize},
+    LedgerState,
+};
 
 proptest! {
     #[test]
@@ -16,6 +19,16 @@ proptest! {
         prop_assert_eq![tx, tx2];
     }
 
+    #[test]
+    fn v5_transaction_roundtrip(tx in Transaction::v5_strategy(LedgerState::default())) {
+        zebra_test::init();
+
+        let data = tx.zcash_serialize_to_vec().expect("tx should serialize");
+        let tx2 = data.zcash_deserialize_into().expect("randomized tx should deserialize");
+
+        prop_assert_eq![tx, tx2];
+    }
+
     #[test]
     fn transaction_hash_display_fromstr_roundtrip(hash in any::<Hash>()) {
         zebra_test::init();
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use super::{Transaction, WtxId};
+
+/// A verified, not-yet-mined transaction, with its wide transaction ID and
+/// serialized size precomputed.
+///
+/// Gossip, inventory routing, and the future mempool all need to look up and
+/// compare transactions by ID repeatedly. Precomputing the ID and size here,
+/// once, avoids reserializing or re-hashing the transaction on every lookup.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnminedTx {
+    /// The unmined transaction itself.
+    pub transaction: Arc<Transaction>,
+    /// The transaction's precomputed wide transaction ID.
+    pub id: WtxId,
+    /// The transaction's precomputed size, in bytes, in its canonical wire
+    /// format.
+    pub size: usize,
+}
+
+impl From<Arc<Transaction>> for UnminedTx {
+    fn from(transaction: Arc<Transaction>) -> Self {
+        let id = transaction.wtx_id();
+        let size = transaction.serialized_size();
+
+        Self {
+            transaction,
+            id,
+            size,
+        }
+    }
+}
+
+impl From<Transaction> for UnminedTx {
+    fn from(transaction: Transaction) -> Self {
+        Arc::new(transaction).into()
+    }
+}
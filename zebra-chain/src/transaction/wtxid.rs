@@ -0,0 +1,45 @@
+use super::{txid, Hash, Transaction};
+
+/// A Zcash "wide transaction ID", used to key transactions in gossip, the
+/// mempool, and inventory routing from NU5 onward.
+///
+/// `V5` transactions have a non-malleable transaction ID and a separate
+/// authorizing data digest; earlier transaction versions have no authorizing
+/// data digest, so their wide ID only carries their regular [`Hash`].
+///
+/// https://zips.z.cash/zip-0239
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct WtxId {
+    /// The non-malleable transaction ID.
+    pub id: Hash,
+    /// The authorizing data digest, for `V5` transactions.
+    pub auth_digest: Option<Hash>,
+}
+
+impl Transaction {
+    /// Computes this transaction's wide transaction ID.
+    ///
+    /// For earlier transaction versions, which have no separate authorizing
+    /// data digest, this is the txid alone.
+    ///
+    /// # Panics
+    ///
+    /// - for `V5` transactions: their ZIP-244 authorizing data digest is
+    ///   computed, but the txid half comes from [`Transaction::hash`], which
+    ///   panics for `V5` transactions.
+    pub fn wtx_id(&self) -> WtxId {
+        let auth_digest = match self {
+            Transaction::V5 { .. } => {
+                let mut bytes = [0; 32];
+                bytes.copy_from_slice(txid::auth_digest(self).as_bytes());
+                Some(Hash(bytes))
+            }
+            _ => None,
+        };
+
+        WtxId {
+            id: self.hash(),
+            auth_digest,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! ZIP-317 conventional transaction fees.
+//!
+//! [ZIP-317]: https://zips.z.cash/zip-0317
+
+use std::convert::TryInto;
+
+use crate::amount::{Amount, NonNegative};
+use crate::sapling;
+
+use super::Transaction;
+
+/// The marginal fee for the ZIP-317 fee calculation, in zatoshis.
+pub const MARGINAL_FEE: u64 = 5_000;
+
+/// The number of grace actions for the ZIP-317 fee calculation.
+///
+/// Transactions with fewer logical actions than this are still charged as if
+/// they had this many.
+pub const GRACE_ACTIONS: u64 = 2;
+
+/// Returns the number of Sapling outputs in `transaction`, regardless of
+/// transaction version.
+fn sapling_outputs_count(transaction: &Transaction) -> u64 {
+    let outputs: Box<dyn Iterator<Item = &sapling::Output>> = match transaction {
+        Transaction::V4 {
+            sapling_shielded_data: Some(sapling_shielded_data),
+            ..
+        } => Box::new(sapling_shielded_data.outputs()),
+        Transaction::V5 {
+            sapling_shielded_data: Some(sapling_shielded_data),
+            ..
+        } => Box::new(sapling_shielded_data.outputs()),
+        _ => Box::new(std::iter::empty()),
+    };
+
+    outputs.count() as u64
+}
+
+/// Returns the number of logical actions in `transaction`, as defined by
+/// [ZIP-317]'s conventional fee formula.
+///
+/// [ZIP-317]: https://zips.z.cash/zip-0317#fee-calculation
+fn conventional_actions(transaction: &Transaction) -> u64 {
+    let tx_in = transaction.inputs().len() as u64;
+    let tx_out = transaction.outputs().len() as u64;
+    // Each Sprout JoinSplit spends two notes, so it contains two nullifiers.
+    let tx_joinsplits = transaction.sprout_nullifiers().count() as u64 / 2;
+    // Sapling has one nullifier per spend.
+    let tx_sapling_spends = transaction.sapling_nullifiers().count() as u64;
+    let tx_sapling_outputs = sapling_outputs_count(transaction);
+    // Orchard has one nullifier per action.
+    let tx_orchard_actions = transaction.orchard_nullifiers().count() as u64;
+
+    std::cmp::max(tx_in, tx_out)
+        + 2 * tx_joinsplits
+        + tx_sapling_spends
+        + tx_sapling_outputs
+        + tx_orchard_actions
+}
+
+/// Returns the ZIP-317 conventional fee for `transaction`.
+///
+/// This is `MARGINAL_FEE` multiplied by the number of logical actions in the
+/// transaction, with a minimum of `GRACE_ACTIONS` logical actions.
+pub fn conventional_fee(transaction: &Transaction) -> Amount<NonNegative> {
+    let actions = std::cmp::max(conventional_actions(transaction), GRACE_ACTIONS);
+
+    (MARGINAL_FEE * actions)
+        .try_into()
+        .expect("conventional fees for well-formed transactions fit in an Amount")
+}
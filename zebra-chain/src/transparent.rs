@@ -113,3 +113,16 @@ pub struct Output {
     /// The lock script defines the conditions under which this output can be spent.
     pub lock_script: Script,
 }
+
+/// An unspent `Output`, with accompanying metadata needed by the state's
+/// UTXO handling and the coinbase maturity rule.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
+pub struct Utxo {
+    /// The output itself.
+    pub output: Output,
+    /// The height at which the output was created.
+    pub height: block::Height,
+    /// Whether the output originated in a coinbase transaction.
+    pub from_coinbase: bool,
+}
@@ -41,7 +41,7 @@ mod magics {
 /// to a Bitcoin address just by removing the "t".)
 ///
 /// https://zips.z.cash/protocol/protocol.pdf#transparentaddrencoding
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub enum Address {
     /// P2SH (Pay to Script Hash) addresses
     PayToScriptHash {
@@ -192,6 +192,14 @@ impl ToAddressWithNetwork for PublicKey {
 }
 
 impl Address {
+    /// Returns the network for this address.
+    pub fn network(&self) -> Network {
+        match *self {
+            Address::PayToScriptHash { network, .. } => network,
+            Address::PayToPublicKeyHash { network, .. } => network,
+        }
+    }
+
     /// A hash of a transparent address payload, as used in
     /// transparent pay-to-script-hash and pay-to-publickey-hash
     /// addresses.
@@ -2,16 +2,21 @@
 
 #![allow(clippy::unit_arg)]
 
-use crate::serialization::{SerializationError, WriteZcashExt, ZcashDeserialize, ZcashSerialize};
+use crate::{
+    parameters::Network,
+    serialization::{SerializationError, WriteZcashExt, ZcashDeserialize, ZcashSerialize},
+};
 
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, io};
 
+use super::Address;
+
+#[cfg(any(test, feature = "proptest-impl"))]
+use proptest::{arbitrary::Arbitrary, collection::vec, prelude::*};
+
 /// An encoding of a Bitcoin script.
-#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Hash)]
-#[cfg_attr(
-    any(test, feature = "proptest-impl"),
-    derive(proptest_derive::Arbitrary)
-)]
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub struct Script(pub Vec<u8>);
 
 impl fmt::Debug for Script {
@@ -22,6 +27,53 @@ impl fmt::Debug for Script {
     }
 }
 
+impl Serialize for Script {
+    /// Serializes `Script` as hex in human-readable formats like JSON, and as
+    /// a raw byte vector in non-human-readable formats like bincode.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&hex::encode(&self.0))
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Script {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            struct HexScriptVisitor;
+
+            impl<'de> de::Visitor<'de> for HexScriptVisitor {
+                type Value = Script;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a hex-encoded script")
+                }
+
+                fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    hex::decode(s)
+                        .map(Script)
+                        .map_err(|_| de::Error::custom("invalid hex in script"))
+                }
+            }
+
+            deserializer.deserialize_str(HexScriptVisitor)
+        } else {
+            Ok(Script(Vec::deserialize(deserializer)?))
+        }
+    }
+}
+
 impl ZcashSerialize for Script {
     fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
         writer.write_compactsize(self.0.len() as u64)?;
@@ -31,11 +83,194 @@ impl ZcashSerialize for Script {
 }
 
 impl ZcashDeserialize for Script {
+    // This reads the script bytes into a freshly allocated `Vec`, via
+    // `Vec<u8>`'s single-allocation `zcash_deserialize` impl. A zero-copy
+    // version that sliced the script directly out of the underlying
+    // `bytes::Bytes` receive buffer would need `ZcashDeserialize` itself to
+    // be specialised on that buffer type, rather than generic over
+    // `io::Read`.
     fn zcash_deserialize<R: io::Read>(reader: R) -> Result<Self, SerializationError> {
         Ok(Script(Vec::zcash_deserialize(reader)?))
     }
 }
 
+/// `OP_DUP`, as defined in the Bitcoin Script opcode list.
+const OP_DUP: u8 = 0x76;
+/// `OP_HASH160`, as defined in the Bitcoin Script opcode list.
+const OP_HASH160: u8 = 0xa9;
+/// `OP_EQUALVERIFY`, as defined in the Bitcoin Script opcode list.
+const OP_EQUALVERIFY: u8 = 0x88;
+/// `OP_CHECKSIG`, as defined in the Bitcoin Script opcode list.
+const OP_CHECKSIG: u8 = 0xac;
+/// `OP_EQUAL`, as defined in the Bitcoin Script opcode list.
+const OP_EQUAL: u8 = 0x87;
+/// `OP_RETURN`, as defined in the Bitcoin Script opcode list.
+const OP_RETURN: u8 = 0x6a;
+
+/// `OP_PUSHDATA1`, as defined in the Bitcoin Script opcode list: the next
+/// byte is the number of bytes to push.
+const OP_PUSHDATA1: u8 = 0x4c;
+/// `OP_PUSHDATA2`, as defined in the Bitcoin Script opcode list: the next 2
+/// bytes (little-endian) are the number of bytes to push.
+const OP_PUSHDATA2: u8 = 0x4d;
+/// `OP_PUSHDATA4`, as defined in the Bitcoin Script opcode list: the next 4
+/// bytes (little-endian) are the number of bytes to push.
+const OP_PUSHDATA4: u8 = 0x4e;
+
+/// A single structural element of a [`Script`]: either literal data pushed
+/// onto the stack, or any other opcode.
+///
+/// Returned by [`Script::opcodes`], which walks a script's raw bytes without
+/// needing the C++ script library.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Opcode<'a> {
+    /// Data pushed onto the stack by one of the push opcodes (`OP_0` through
+    /// `OP_PUSHDATA4`).
+    Push(&'a [u8]),
+    /// Any other opcode, which doesn't carry its own data.
+    Op(u8),
+}
+
+/// An iterator over the [`Opcode`]s in a [`Script`], as returned by
+/// [`Script::opcodes`].
+///
+/// If the script is malformed, for example if it ends partway through a
+/// push's declared length, the iterator stops and yields no further items,
+/// rather than panicking.
+#[derive(Clone, Debug)]
+pub struct Opcodes<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Iterator for Opcodes<'a> {
+    type Item = Opcode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&opcode, rest) = self.remaining.split_first()?;
+
+        let header_len = match opcode {
+            0x00..=0x4b => 0,
+            OP_PUSHDATA1 => 1,
+            OP_PUSHDATA2 => 2,
+            OP_PUSHDATA4 => 4,
+            _ => {
+                self.remaining = rest;
+                return Some(Opcode::Op(opcode));
+            }
+        };
+
+        let header = rest.get(..header_len)?;
+        let push_len = match opcode {
+            0x00..=0x4b => opcode as usize,
+            OP_PUSHDATA1 => header[0] as usize,
+            OP_PUSHDATA2 => u16::from_le_bytes([header[0], header[1]]) as usize,
+            OP_PUSHDATA4 => u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize,
+            _ => unreachable!("header_len is only set for push opcodes"),
+        };
+
+        let data = rest.get(header_len..header_len + push_len)?;
+        self.remaining = &rest[header_len + push_len..];
+        Some(Opcode::Push(data))
+    }
+}
+
+impl Script {
+    /// Returns an iterator over this script's [`Opcode`]s: the opcodes and
+    /// literal data pushes that make up its structure.
+    pub fn opcodes(&self) -> Opcodes<'_> {
+        Opcodes {
+            remaining: &self.0,
+        }
+    }
+
+    /// Returns `true` if this is a standard pay-to-public-key-hash script:
+    /// `OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG`.
+    pub fn is_p2pkh(&self) -> bool {
+        matches!(
+            self.0.as_slice(),
+            [OP_DUP, OP_HASH160, 0x14, .., OP_EQUALVERIFY, OP_CHECKSIG] if self.0.len() == 25
+        )
+    }
+
+    /// Returns `true` if this is a standard pay-to-script-hash script:
+    /// `OP_HASH160 <20 bytes> OP_EQUAL`.
+    pub fn is_p2sh(&self) -> bool {
+        matches!(
+            self.0.as_slice(),
+            [OP_HASH160, 0x14, .., OP_EQUAL] if self.0.len() == 23
+        )
+    }
+
+    /// Returns `true` if this script starts with `OP_RETURN`, marking its
+    /// output as provably unspendable and available to carry arbitrary data.
+    pub fn is_op_return(&self) -> bool {
+        self.0.first() == Some(&OP_RETURN)
+    }
+
+    /// Returns the transparent [`Address`] that this script pays to, if it's
+    /// a standard pay-to-public-key-hash or pay-to-script-hash script.
+    ///
+    /// Returns `None` for non-standard scripts. This is named `address`
+    /// rather than `to_address`, because [`Script`] already implements the
+    /// private `ToAddressWithNetwork::to_address` in `address.rs`, which
+    /// hashes the *entire* script to build a P2SH address *for* that script
+    /// (used when constructing a redeem script's address), rather than
+    /// parsing an existing output script's *embedded* address.
+    pub fn address(&self, network: Network) -> Option<Address> {
+        if self.is_p2pkh() {
+            let mut pub_key_hash = [0; 20];
+            pub_key_hash.copy_from_slice(&self.0[3..23]);
+            Some(Address::PayToPublicKeyHash {
+                network,
+                pub_key_hash,
+            })
+        } else if self.is_p2sh() {
+            let mut script_hash = [0; 20];
+            script_hash.copy_from_slice(&self.0[2..22]);
+            Some(Address::PayToScriptHash {
+                network,
+                script_hash,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Script {
+    /// Returns a strategy that generates a `Script` with a length similar to
+    /// real Zcash scripts (most are well under 100 bytes).
+    ///
+    /// This is the default [`Arbitrary`] strategy, used by `any::<Script>()`.
+    /// Using a small, bounded length keeps property tests over nested
+    /// structures like [`crate::transaction::Transaction`] fast.
+    pub fn arbitrary_small() -> BoxedStrategy<Self> {
+        vec(any::<u8>(), 0..100).prop_map(Script).boxed()
+    }
+
+    /// Returns a strategy that generates a `Script` with a length covering
+    /// the entire range that's valid on the network, including scripts that
+    /// are much larger than any script seen on the real chains.
+    ///
+    /// This strategy is opt-in: use it explicitly (for example, in nightly
+    /// fuzzing jobs) instead of `any::<Script>()`, since it's much slower.
+    pub fn arbitrary_large() -> BoxedStrategy<Self> {
+        vec(any::<u8>(), 0..10_000).prop_map(Script).boxed()
+    }
+}
+
+#[cfg(any(test, feature = "proptest-impl"))]
+impl Arbitrary for Script {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        Script::arbitrary_small()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
 #[cfg(test)]
 mod proptests {
     use std::io::Cursor;
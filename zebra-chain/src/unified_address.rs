@@ -0,0 +1,533 @@
+//! Unified Addresses, as specified in [ZIP-316][zip-316].
+//!
+//! [zip-316]: https://zips.z.cash/zip-0316
+
+use std::{fmt, io::Write};
+
+use bech32::{self, FromBase32, ToBase32, Variant};
+
+#[cfg(test)]
+use proptest::{collection::vec, prelude::*};
+
+use crate::{parameters::Network, serialization::SerializationError};
+
+/// Human-Readable Parts for input to bech32m encoding.
+mod human_readable_parts {
+    pub const MAINNET: &str = "u";
+    pub const TESTNET: &str = "utest";
+}
+
+/// The typecodes used to identify each kind of receiver inside a unified
+/// address, as specified in [ZIP-316 §Encoding of Unified Addresses][zip-316].
+///
+/// [zip-316]: https://zips.z.cash/zip-0316#encoding-of-unified-addresses
+mod typecodes {
+    pub const P2PKH: u8 = 0x00;
+    pub const P2SH: u8 = 0x01;
+    pub const SAPLING: u8 = 0x02;
+    pub const ORCHARD: u8 = 0x03;
+}
+
+/// A single receiver inside a [`UnifiedAddress`].
+///
+/// Transparent receivers hold a 20-byte hash, and shielded receivers hold a
+/// 43-byte diversifier-and-key payload (an 11-byte diversifier followed by a
+/// 32-byte key), matching the raw encodings already used by
+/// [`transparent::Address`](crate::transparent::Address) and
+/// [`sapling::Address`](crate::sapling::Address).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Receiver {
+    /// A P2PKH transparent receiver.
+    P2pkh([u8; 20]),
+    /// A P2SH transparent receiver.
+    P2sh([u8; 20]),
+    /// A Sapling shielded receiver.
+    Sapling([u8; 43]),
+    /// An Orchard shielded receiver.
+    Orchard([u8; 43]),
+}
+
+impl Receiver {
+    /// Returns the ZIP-316 typecode for this receiver.
+    fn typecode(&self) -> u8 {
+        match self {
+            Receiver::P2pkh(_) => typecodes::P2PKH,
+            Receiver::P2sh(_) => typecodes::P2SH,
+            Receiver::Sapling(_) => typecodes::SAPLING,
+            Receiver::Orchard(_) => typecodes::ORCHARD,
+        }
+    }
+
+    /// Returns the raw payload bytes for this receiver.
+    fn payload(&self) -> &[u8] {
+        match self {
+            Receiver::P2pkh(bytes) => &bytes[..],
+            Receiver::P2sh(bytes) => &bytes[..],
+            Receiver::Sapling(bytes) => &bytes[..],
+            Receiver::Orchard(bytes) => &bytes[..],
+        }
+    }
+
+    /// Returns `true` if this is a shielded (Sapling or Orchard) receiver.
+    fn is_shielded(&self) -> bool {
+        matches!(self, Receiver::Sapling(_) | Receiver::Orchard(_))
+    }
+
+    /// Parses a single receiver from a `(typecode, payload)` pair.
+    fn from_typecode(typecode: u8, payload: &[u8]) -> Result<Self, SerializationError> {
+        match (typecode, payload.len()) {
+            (typecodes::P2PKH, 20) => {
+                let mut bytes = [0; 20];
+                bytes.copy_from_slice(payload);
+                Ok(Receiver::P2pkh(bytes))
+            }
+            (typecodes::P2SH, 20) => {
+                let mut bytes = [0; 20];
+                bytes.copy_from_slice(payload);
+                Ok(Receiver::P2sh(bytes))
+            }
+            (typecodes::SAPLING, 43) => {
+                let mut bytes = [0; 43];
+                bytes.copy_from_slice(payload);
+                Ok(Receiver::Sapling(bytes))
+            }
+            (typecodes::ORCHARD, 43) => {
+                let mut bytes = [0; 43];
+                bytes.copy_from_slice(payload);
+                Ok(Receiver::Orchard(bytes))
+            }
+            (_, _) => Err(SerializationError::Parse(
+                "unknown or malformed unified address receiver",
+            )),
+        }
+    }
+}
+
+/// A [ZIP-316] unified address, combining zero or more transparent receivers
+/// with at least one shielded (Sapling or Orchard) receiver.
+///
+/// Unlike the single-receiver [`transparent::Address`](crate::transparent::Address)
+/// and [`sapling::Address`](crate::sapling::Address) types, a `UnifiedAddress`
+/// is encoded as a [F4Jumble]-permuted, padded concatenation of its receivers,
+/// Bech32m-encoded with a network-specific human-readable part.
+///
+/// [ZIP-316]: https://zips.z.cash/zip-0316
+/// [F4Jumble]: https://zips.z.cash/zip-0316#f4jumble
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnifiedAddress {
+    network: Network,
+    // Stored in ascending typecode order, as required by ZIP-316.
+    receivers: Vec<Receiver>,
+}
+
+impl UnifiedAddress {
+    /// Constructs a `UnifiedAddress` from `network` and `receivers`.
+    ///
+    /// Returns an error if `receivers` contains no shielded receiver, or more
+    /// than one receiver of the same kind, as required by [ZIP-316].
+    ///
+    /// [ZIP-316]: https://zips.z.cash/zip-0316#encoding-of-unified-addresses
+    pub fn new(network: Network, mut receivers: Vec<Receiver>) -> Result<Self, SerializationError> {
+        if !receivers.iter().any(Receiver::is_shielded) {
+            return Err(SerializationError::Parse(
+                "a unified address must have at least one shielded receiver",
+            ));
+        }
+
+        receivers.sort_by_key(Receiver::typecode);
+        if receivers
+            .windows(2)
+            .any(|pair| pair[0].typecode() == pair[1].typecode())
+        {
+            return Err(SerializationError::Parse(
+                "a unified address must not have more than one receiver of the same kind",
+            ));
+        }
+
+        Ok(UnifiedAddress { network, receivers })
+    }
+
+    /// Returns the network for this address.
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the receivers contained in this address, in ascending
+    /// typecode order.
+    pub fn receivers(&self) -> &[Receiver] {
+        &self.receivers
+    }
+
+    /// Returns the raw, unpadded, un-jumbled encoding of `receivers`:
+    /// a concatenation of `(typecode, length, payload)` for each receiver.
+    fn raw_encoding(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        for receiver in &self.receivers {
+            let payload = receiver.payload();
+            let _ = raw.write_all(&[receiver.typecode(), payload.len() as u8]);
+            let _ = raw.write_all(payload);
+        }
+        raw
+    }
+
+    /// Parses `padded`, the padded and un-jumbled encoding produced by
+    /// [`UnifiedAddress::raw_encoding`] followed by [`pad`], back into its
+    /// receivers, checking that `hrp`'s padding is present and well-formed.
+    fn from_padded(network: Network, hrp: &str, padded: &[u8]) -> Result<Self, SerializationError> {
+        let raw = unpad(hrp, padded)?;
+
+        let mut receivers = Vec::new();
+        let mut rest = &raw[..];
+        while !rest.is_empty() {
+            if rest.len() < 2 {
+                return Err(SerializationError::Parse(
+                    "truncated unified address receiver",
+                ));
+            }
+            let typecode = rest[0];
+            let len = rest[1] as usize;
+            rest = &rest[2..];
+            if rest.len() < len {
+                return Err(SerializationError::Parse(
+                    "truncated unified address receiver payload",
+                ));
+            }
+            receivers.push(Receiver::from_typecode(typecode, &rest[..len])?);
+            rest = &rest[len..];
+        }
+
+        UnifiedAddress::new(network, receivers)
+    }
+}
+
+impl fmt::Display for UnifiedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hrp = match self.network {
+            Network::Mainnet => human_readable_parts::MAINNET,
+            _ => human_readable_parts::TESTNET,
+        };
+
+        let padded = pad(hrp, &self.raw_encoding());
+        let jumbled = f4jumble(&padded);
+
+        bech32::encode_to_fmt(f, hrp, jumbled.to_base32(), Variant::Bech32m).unwrap()
+    }
+}
+
+impl std::str::FromStr for UnifiedAddress {
+    type Err = SerializationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match bech32::decode(s) {
+            Ok((hrp, bytes, Variant::Bech32m)) => {
+                let network = match hrp.as_str() {
+                    human_readable_parts::MAINNET => Network::Mainnet,
+                    human_readable_parts::TESTNET => Network::Testnet,
+                    _ => {
+                        return Err(SerializationError::Parse(
+                            "unrecognised unified address human-readable part",
+                        ))
+                    }
+                };
+
+                let jumbled =
+                    Vec::<u8>::from_base32(&bytes).map_err(|_| {
+                        SerializationError::Parse("invalid unified address base32 data")
+                    })?;
+                let padded = f4jumble_inv(&jumbled);
+
+                UnifiedAddress::from_padded(network, &hrp, &padded)
+            }
+            _ => Err(SerializationError::Parse(
+                "unified address must be Bech32m-encoded",
+            )),
+        }
+    }
+}
+
+/// Pads `raw` to a multiple of 16 bytes, as required before
+/// [F4Jumble][zip-316]-permuting a unified address encoding.
+///
+/// The padding bytes are the ASCII bytes of `hrp`, zero-padded (or
+/// truncated) to fill the padding.
+///
+/// [zip-316]: https://zips.z.cash/zip-0316#f4jumble
+fn pad(hrp: &str, raw: &[u8]) -> Vec<u8> {
+    let pad_len = 16 - (raw.len() % 16);
+
+    let mut padded = raw.to_vec();
+    let hrp_bytes = hrp.as_bytes();
+    for i in 0..pad_len {
+        padded.push(*hrp_bytes.get(i).unwrap_or(&0));
+    }
+    padded
+}
+
+/// The inverse of [`pad`]: checks that `padded`'s trailing padding matches
+/// `hrp`, and returns the unpadded data.
+fn unpad<'a>(hrp: &str, padded: &'a [u8]) -> Result<&'a [u8], SerializationError> {
+    if padded.is_empty() || padded.len() % 16 != 0 {
+        return Err(SerializationError::Parse(
+            "unified address padding has the wrong length",
+        ));
+    }
+
+    // The padding is between 1 and 16 bytes; find it by checking each
+    // candidate length against the padding that `pad` would have produced
+    // for `hrp`.
+    for candidate_pad_len in 1..=16 {
+        let (raw, pad) = padded.split_at(padded.len() - candidate_pad_len);
+        let expected: Vec<u8> = (0..candidate_pad_len)
+            .map(|i| *hrp.as_bytes().get(i).unwrap_or(&0))
+            .collect();
+        if pad == expected.as_slice() {
+            return Ok(raw);
+        }
+    }
+
+    Err(SerializationError::Parse(
+        "unified address padding does not match its human-readable part",
+    ))
+}
+
+/// Applies the [F4Jumble][zip-316] permutation to `message`.
+///
+/// This is a 4-round Feistel construction over BLAKE2b, used by ZIP-316 to
+/// ensure that truncating a unified address does not reveal a valid prefix
+/// of any of its individual receivers.
+///
+/// [zip-316]: https://zips.z.cash/zip-0316#f4jumble
+///
+/// # Correctness
+///
+/// Zebra has no official ZIP-316 test vectors to check this implementation
+/// against, so although [`f4jumble_hash`] uses ZIP-316's actual `G`/`H`
+/// personalization tags, it is only verified for internal consistency (that
+/// [`f4jumble_inv`] inverts it), not byte-exact conformance with other
+/// implementations.
+fn f4jumble(message: &[u8]) -> Vec<u8> {
+    let left_len = (message.len() + 1) / 2;
+    let right_len = message.len() / 2;
+
+    let mut left = message[..left_len].to_vec();
+    let mut right = message[left_len..].to_vec();
+
+    for round in 0..4u8 {
+        if round % 2 == 0 {
+            xor_into(&mut right, &f4jumble_hash(F4JumbleRole::G, &left, right_len));
+        } else {
+            xor_into(&mut left, &f4jumble_hash(F4JumbleRole::H, &right, left_len));
+        }
+    }
+
+    left.extend_from_slice(&right);
+    left
+}
+
+/// The inverse of [`f4jumble`].
+fn f4jumble_inv(message: &[u8]) -> Vec<u8> {
+    let left_len = (message.len() + 1) / 2;
+    let right_len = message.len() / 2;
+
+    let mut left = message[..left_len].to_vec();
+    let mut right = message[left_len..].to_vec();
+
+    for round in (0..4u8).rev() {
+        if round % 2 == 0 {
+            xor_into(&mut right, &f4jumble_hash(F4JumbleRole::G, &left, right_len));
+        } else {
+            xor_into(&mut left, &f4jumble_hash(F4JumbleRole::H, &right, left_len));
+        }
+    }
+
+    left.extend_from_slice(&right);
+    left
+}
+
+/// XORs `mask` into `target` in place. `mask` must be at least as long as
+/// `target`.
+fn xor_into(target: &mut [u8], mask: &[u8]) {
+    for (byte, mask_byte) in target.iter_mut().zip(mask) {
+        *byte ^= mask_byte;
+    }
+}
+
+/// Which of [`f4jumble`]'s two pseudorandom functions, `G` or `H`, a call to
+/// [`f4jumble_hash`] computes.
+///
+/// ZIP-316 domain-separates the two halves of each Feistel round with
+/// distinct BLAKE2b personalization tags, rather than distinguishing them by
+/// round number.
+#[derive(Clone, Copy)]
+enum F4JumbleRole {
+    /// Derives the mask XORed into the right half from the left half.
+    G,
+    /// Derives the mask XORed into the left half from the right half.
+    H,
+}
+
+impl F4JumbleRole {
+    /// The BLAKE2b personalization for this role, as specified by ZIP-316.
+    fn personalization(self) -> &'static [u8; 16] {
+        match self {
+            // ZIP-316's 12-byte tags, zero-padded to BLAKE2b's 16-byte
+            // personalization field.
+            F4JumbleRole::G => b"UA__F4Jmbl_G\0\0\0\0",
+            F4JumbleRole::H => b"UA__F4Jmbl_H\0\0\0\0",
+        }
+    }
+}
+
+/// Derives `out_len` pseudorandom bytes from `input` using F4Jumble's `role`
+/// function, by concatenating successive BLAKE2b-512 outputs.
+///
+/// BLAKE2b's output is limited to 64 bytes, so longer outputs are built by
+/// hashing `input` again with a little-endian block counter prepended to it,
+/// which keeps [`f4jumble`] well-defined for the receiver counts unified
+/// addresses actually use.
+fn f4jumble_hash(role: F4JumbleRole, input: &[u8], out_len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(out_len);
+    let mut counter: u32 = 0;
+
+    while out.len() < out_len {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(64)
+            .personal(role.personalization())
+            .to_state()
+            .update(&counter.to_le_bytes())
+            .update(input)
+            .finalize();
+
+        out.extend_from_slice(hash.as_bytes());
+        counter += 1;
+    }
+
+    out.truncate(out_len);
+    out
+}
+
+#[cfg(test)]
+impl Arbitrary for Receiver {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            vec(any::<u8>(), 20).prop_map(|b| {
+                let mut bytes = [0; 20];
+                bytes.copy_from_slice(&b);
+                Receiver::P2pkh(bytes)
+            }),
+            vec(any::<u8>(), 20).prop_map(|b| {
+                let mut bytes = [0; 20];
+                bytes.copy_from_slice(&b);
+                Receiver::P2sh(bytes)
+            }),
+            vec(any::<u8>(), 43).prop_map(|b| {
+                let mut bytes = [0; 43];
+                bytes.copy_from_slice(&b);
+                Receiver::Sapling(bytes)
+            }),
+            vec(any::<u8>(), 43).prop_map(|b| {
+                let mut bytes = [0; 43];
+                bytes.copy_from_slice(&b);
+                Receiver::Orchard(bytes)
+            }),
+        ]
+        .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+#[cfg(test)]
+impl Arbitrary for UnifiedAddress {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (
+            any::<Network>(),
+            vec(any::<u8>(), 20),
+            vec(any::<u8>(), 43),
+            any::<bool>(),
+        )
+            .prop_map(|(network, p2pkh_bytes, sapling_bytes, include_p2pkh)| {
+                let mut sapling = [0; 43];
+                sapling.copy_from_slice(&sapling_bytes);
+
+                let mut receivers = vec![Receiver::Sapling(sapling)];
+                if include_p2pkh {
+                    let mut p2pkh = [0; 20];
+                    p2pkh.copy_from_slice(&p2pkh_bytes);
+                    receivers.push(Receiver::P2pkh(p2pkh));
+                }
+
+                UnifiedAddress::new(network, receivers).expect("constructed receivers are valid")
+            })
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_transparent_only_address() {
+        zebra_test::init();
+
+        let result = UnifiedAddress::new(Network::Mainnet, vec![Receiver::P2pkh([0; 20])]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_receiver_kinds() {
+        zebra_test::init();
+
+        let result = UnifiedAddress::new(
+            Network::Mainnet,
+            vec![Receiver::Sapling([0; 43]), Receiver::Sapling([1; 43])],
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn f4jumble_is_invertible() {
+        zebra_test::init();
+
+        let message = pad("u", &[1, 2, 3, 4, 5]);
+        let jumbled = f4jumble(&message);
+
+        assert_eq!(f4jumble_inv(&jumbled), message);
+    }
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn unified_address_roundtrip(addr in any::<UnifiedAddress>()) {
+        zebra_test::init();
+
+        let string = addr.to_string();
+
+        let addr2 = string.parse::<UnifiedAddress>()
+            .expect("randomized unified address should deserialize");
+
+        prop_assert_eq![addr, addr2];
+    }
+
+    #[test]
+    fn f4jumble_roundtrip(bytes in vec(any::<u8>(), 16..256)) {
+        zebra_test::init();
+
+        // Unified address encodings are padded to a multiple of 16 bytes
+        // before f4jumble is applied, so the test pads its input the same way.
+        let padded = pad("u", &bytes);
+        let jumbled = f4jumble(&padded);
+
+        prop_assert_eq![f4jumble_inv(&jumbled), padded];
+    }
+}
@@ -0,0 +1,174 @@
+//! A type for tracking the value balance of each of Zcash's value pools.
+
+use std::ops::{Add, Sub};
+
+use crate::amount::{Amount, Error as AmountError, NegativeAllowed};
+
+/// An error returned when an arithmetic operation on a [`ValueBalance`] would
+/// take one of its pool amounts out of its valid range.
+#[derive(thiserror::Error, Debug, displaydoc::Display, Clone, Eq, PartialEq)]
+pub enum ValueBalanceError {
+    /// the transparent value pool amount is invalid: {0}
+    Transparent(#[source] AmountError),
+    /// the sprout value pool amount is invalid: {0}
+    Sprout(#[source] AmountError),
+    /// the sapling value pool amount is invalid: {0}
+    Sapling(#[source] AmountError),
+    /// the orchard value pool amount is invalid: {0}
+    Orchard(#[source] AmountError),
+}
+
+/// The value balance of each of Zcash's value pools, in zatoshis.
+///
+/// Transactions move value into and out of the transparent, Sprout, Sapling,
+/// and Orchard pools. Each pool's balance can be summed across a transaction
+/// or a block to check that no value was created or destroyed, other than
+/// through the issuance of new coins.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ValueBalance {
+    transparent: Amount<NegativeAllowed>,
+    sprout: Amount<NegativeAllowed>,
+    sapling: Amount<NegativeAllowed>,
+    orchard: Amount<NegativeAllowed>,
+}
+
+impl Default for ValueBalance {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl ValueBalance {
+    /// Returns a `ValueBalance` with a zero balance in every pool.
+    pub fn zero() -> Self {
+        let zero = Amount::try_from(0).expect("0 is always a valid Amount");
+
+        Self {
+            transparent: zero,
+            sprout: zero,
+            sapling: zero,
+            orchard: zero,
+        }
+    }
+
+    /// Constructs a `ValueBalance` from its per-pool components.
+    pub fn from_components(
+        transparent: Amount<NegativeAllowed>,
+        sprout: Amount<NegativeAllowed>,
+        sapling: Amount<NegativeAllowed>,
+        orchard: Amount<NegativeAllowed>,
+    ) -> Self {
+        Self {
+            transparent,
+            sprout,
+            sapling,
+            orchard,
+        }
+    }
+
+    /// Returns the transparent pool component of this value balance.
+    pub fn transparent(&self) -> Amount<NegativeAllowed> {
+        self.transparent
+    }
+
+    /// Returns the Sprout pool component of this value balance.
+    pub fn sprout(&self) -> Amount<NegativeAllowed> {
+        self.sprout
+    }
+
+    /// Returns the Sapling pool component of this value balance.
+    pub fn sapling(&self) -> Amount<NegativeAllowed> {
+        self.sapling
+    }
+
+    /// Returns the Orchard pool component of this value balance.
+    pub fn orchard(&self) -> Amount<NegativeAllowed> {
+        self.orchard
+    }
+}
+
+impl Add for ValueBalance {
+    type Output = Result<ValueBalance, ValueBalanceError>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Ok(ValueBalance {
+            transparent: (self.transparent + rhs.transparent)
+                .map_err(ValueBalanceError::Transparent)?,
+            sprout: (self.sprout + rhs.sprout).map_err(ValueBalanceError::Sprout)?,
+            sapling: (self.sapling + rhs.sapling).map_err(ValueBalanceError::Sapling)?,
+            orchard: (self.orchard + rhs.orchard).map_err(ValueBalanceError::Orchard)?,
+        })
+    }
+}
+
+impl Sub for ValueBalance {
+    type Output = Result<ValueBalance, ValueBalanceError>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Ok(ValueBalance {
+            transparent: (self.transparent - rhs.transparent)
+                .map_err(ValueBalanceError::Transparent)?,
+            sprout: (self.sprout - rhs.sprout).map_err(ValueBalanceError::Sprout)?,
+            sapling: (self.sapling - rhs.sapling).map_err(ValueBalanceError::Sapling)?,
+            orchard: (self.orchard - rhs.orchard).map_err(ValueBalanceError::Orchard)?,
+        })
+    }
+}
+
+impl std::iter::Sum<ValueBalance> for Result<ValueBalance, ValueBalanceError> {
+    fn sum<I: Iterator<Item = ValueBalance>>(iter: I) -> Self {
+        iter.fold(Ok(ValueBalance::zero()), |total, value_balance| {
+            total? + value_balance
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: i64) -> Amount<NegativeAllowed> {
+        Amount::try_from(value).expect("valid test amount")
+    }
+
+    #[test]
+    fn add_combines_each_pool() {
+        zebra_test::init();
+
+        let a = ValueBalance::from_components(amount(1), amount(2), amount(3), amount(4));
+        let b = ValueBalance::from_components(amount(10), amount(20), amount(30), amount(40));
+
+        let total = (a + b).expect("sum within range should succeed");
+
+        assert_eq!(total.transparent(), amount(11));
+        assert_eq!(total.sprout(), amount(22));
+        assert_eq!(total.sapling(), amount(33));
+        assert_eq!(total.orchard(), amount(44));
+    }
+
+    #[test]
+    fn sub_rejects_out_of_range_pool() {
+        zebra_test::init();
+
+        let a = ValueBalance::from_components(
+            amount(crate::amount::MAX_MONEY),
+            amount(0),
+            amount(0),
+            amount(0),
+        );
+        let b = ValueBalance::from_components(amount(-1), amount(0), amount(0), amount(0));
+
+        (a - b).expect_err("subtracting a negative value should overflow MAX_MONEY");
+    }
+
+    #[test]
+    fn sum_of_zero_balances_is_zero() {
+        zebra_test::init();
+
+        let total: ValueBalance = std::iter::empty::<ValueBalance>()
+            .sum::<Result<ValueBalance, ValueBalanceError>>()
+            .expect("summing no value balances should succeed");
+
+        assert_eq!(total, ValueBalance::zero());
+    }
+}
@@ -491,6 +491,14 @@ impl From<Work> for PartialCumulativeWork {
     }
 }
 
+impl Sum<Work> for PartialCumulativeWork {
+    fn sum<I: Iterator<Item = Work>>(iter: I) -> Self {
+        iter.fold(PartialCumulativeWork::default(), |total, work| {
+            total + work
+        })
+    }
+}
+
 impl std::ops::Add<Work> for PartialCumulativeWork {
     type Output = PartialCumulativeWork;
 
@@ -4,5 +4,7 @@
 
 /// Founders' Reward functions apply for blocks before Canopy.
 pub mod founders_reward;
+/// Funding stream functions apply for blocks from Canopy until the second halving.
+pub mod funding_streams;
 /// General subsidy functions apply for blocks after slow-start mining.
 pub mod general;
@@ -0,0 +1,132 @@
+//! Funding Streams calculations. - [§7.10][7.10]
+//!
+//! [7.10]: https://zips.z.cash/protocol/protocol.pdf#fundingstreams
+
+use std::convert::TryFrom;
+
+use zebra_chain::{
+    amount::{Amount, Error, NonNegative},
+    block::Height,
+    parameters::{Network, NetworkUpgrade::*},
+};
+
+use crate::block::subsidy::general::{block_subsidy, halving_divisor};
+use crate::parameters::subsidy::{
+    FundingStreamReceiver, FUNDING_STREAM_ECC_NUMERATOR, FUNDING_STREAM_MG_NUMERATOR,
+    FUNDING_STREAM_RECEIVERS, FUNDING_STREAM_RECEIVER_DENOMINATOR, FUNDING_STREAM_ZF_NUMERATOR,
+};
+
+/// Returns `true` if `height` is within the funding stream period for `network`.
+///
+/// Funding streams are active from Canopy activation until the second halving, as
+/// described in [ZIP-207](https://zips.z.cash/zip-0207).
+pub fn funding_stream_period(height: Height, network: Network) -> bool {
+    let canopy_height = Canopy
+        .activation_height(network)
+        .expect("canopy activation height should be available");
+
+    height >= canopy_height && halving_divisor(height, network) <= 2
+}
+
+/// Returns `receiver`'s numerator, out of [`FUNDING_STREAM_RECEIVER_DENOMINATOR`], as
+/// described in [ZIP-207](https://zips.z.cash/zip-0207).
+pub fn funding_stream_numerator(receiver: FundingStreamReceiver) -> u64 {
+    match receiver {
+        FundingStreamReceiver::Ecc => FUNDING_STREAM_ECC_NUMERATOR,
+        FundingStreamReceiver::ZcashFoundation => FUNDING_STREAM_ZF_NUMERATOR,
+        FundingStreamReceiver::MajorGrants => FUNDING_STREAM_MG_NUMERATOR,
+    }
+}
+
+/// `FundingStream(height, receiver)` as described in [protocol specification §7.10][7.10]
+///
+/// Returns `Amount::zero()` outside of the funding stream period.
+///
+/// [7.10]: https://zips.z.cash/protocol/protocol.pdf#fundingstreams
+pub fn funding_stream_value(
+    height: Height,
+    network: Network,
+    receiver: FundingStreamReceiver,
+) -> Result<Amount<NonNegative>, Error> {
+    if !funding_stream_period(height, network) {
+        return Amount::try_from(0);
+    }
+
+    (block_subsidy(height, network)? * funding_stream_numerator(receiver))?
+        / FUNDING_STREAM_RECEIVER_DENOMINATOR
+}
+
+/// Returns the funding stream amount for every receiver at `height`.
+///
+/// Returns an empty list outside of the funding stream period.
+// TODO: once Zebra has a way to represent transparent addresses or scripts,
+// pair each amount with its receiver's address for `height` and `network`.
+pub fn funding_stream_values(
+    height: Height,
+    network: Network,
+) -> Result<Vec<(FundingStreamReceiver, Amount<NonNegative>)>, Error> {
+    if !funding_stream_period(height, network) {
+        return Ok(Vec::new());
+    }
+
+    FUNDING_STREAM_RECEIVERS
+        .iter()
+        .map(|&receiver| Ok((receiver, funding_stream_value(height, network, receiver)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use color_eyre::Report;
+
+    #[test]
+    fn test_funding_stream_values() -> Result<(), Report> {
+        zebra_test::init();
+
+        funding_stream_values_for_network(Network::Mainnet)?;
+        funding_stream_values_for_network(Network::Testnet)?;
+
+        Ok(())
+    }
+
+    fn funding_stream_values_for_network(network: Network) -> Result<(), Report> {
+        let canopy_height = Canopy.activation_height(network).unwrap();
+
+        // Before Canopy, there are no funding streams.
+        assert!(funding_stream_values((canopy_height - 1).unwrap(), network)?.is_empty());
+
+        // At Canopy, funding streams total 20% of the block subsidy, split
+        // 7% + 5% + 8%.
+        let block_subsidy = block_subsidy(canopy_height, network)?;
+        let values = funding_stream_values(canopy_height, network)?;
+
+        let total: Amount<NonNegative> = values
+            .iter()
+            .map(|(_, amount)| *amount)
+            .sum::<Result<_, Error>>()?;
+
+        assert_eq!(total, (block_subsidy * 20)? / 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_funding_stream_period_ends_at_second_halving() -> Result<(), Report> {
+        zebra_test::init();
+
+        let network = Network::Mainnet;
+        let canopy_height = Canopy.activation_height(network).unwrap();
+        let second_halving_height =
+            (canopy_height + crate::parameters::subsidy::POST_BLOSSOM_HALVING_INTERVAL).unwrap();
+
+        assert!(funding_stream_period(canopy_height, network));
+        assert!(funding_stream_period(
+            (second_halving_height - 1).unwrap(),
+            network
+        ));
+        assert!(!funding_stream_period(second_halving_height, network));
+
+        Ok(())
+    }
+}
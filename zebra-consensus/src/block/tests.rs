@@ -261,6 +261,23 @@ fn equihash_is_valid_for_historical_blocks() -> Result<(), Report> {
     Ok(())
 }
 
+#[test]
+fn equihash_is_invalid_for_corrupted_solution() -> Result<(), Report> {
+    zebra_test::init();
+
+    let mut block: Block =
+        Block::zcash_deserialize(&zebra_test::vectors::BLOCK_MAINNET_GENESIS_BYTES[..])
+            .expect("block is structurally valid");
+
+    // Corrupt the solution so it no longer satisfies the equihash parameters.
+    block.header.solution.0[0] ^= 0xff;
+
+    check::equihash_solution_is_valid(&block.header)
+        .expect_err("a corrupted equihash solution should fail validation");
+
+    Ok(())
+}
+
 #[test]
 fn subsidy_is_valid_for_historical_blocks() -> Result<(), Report> {
     zebra_test::init();
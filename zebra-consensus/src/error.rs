@@ -7,6 +7,8 @@
 
 use thiserror::Error;
 
+use zebra_chain::block;
+
 use crate::BoxError;
 
 #[derive(Error, Debug, PartialEq)]
@@ -47,6 +49,12 @@ pub enum TransactionError {
     #[error("must have at least one output: transparent, shielded output, or joinsplit")]
     NoOutputs,
 
+    #[error("transaction with expiry height {expiry_height:?} is expired: the block it would be mined in has height {block_height:?}")]
+    ExpiredTransaction {
+        expiry_height: block::Height,
+        block_height: block::Height,
+    },
+
     #[error("if there are no Spends or Outputs, the value balance MUST be 0.")]
     BadBalance,
 
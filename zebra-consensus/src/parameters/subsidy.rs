@@ -41,3 +41,37 @@ pub const POST_BLOSSOM_HALVING_INTERVAL: Height =
 ///
 /// Usage: founders_reward = block_subsidy / FOUNDERS_FRACTION_DIVISOR
 pub const FOUNDERS_FRACTION_DIVISOR: u64 = 5;
+
+/// A funding stream receiver, as described in [ZIP-207](https://zips.z.cash/zip-0207).
+///
+/// Funding streams replace the Founders' Reward from Canopy activation until the
+/// second halving.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FundingStreamReceiver {
+    /// The Electric Coin Company.
+    Ecc,
+    /// The Zcash Foundation.
+    ZcashFoundation,
+    /// The Major Grants fund.
+    MajorGrants,
+}
+
+/// The denominator used to calculate each funding stream receiver's share of the
+/// block subsidy, as described in [ZIP-207](https://zips.z.cash/zip-0207).
+pub const FUNDING_STREAM_RECEIVER_DENOMINATOR: u64 = 100;
+
+/// The Electric Coin Company's numerator, out of [`FUNDING_STREAM_RECEIVER_DENOMINATOR`].
+pub const FUNDING_STREAM_ECC_NUMERATOR: u64 = 7;
+
+/// The Zcash Foundation's numerator, out of [`FUNDING_STREAM_RECEIVER_DENOMINATOR`].
+pub const FUNDING_STREAM_ZF_NUMERATOR: u64 = 5;
+
+/// The Major Grants fund's numerator, out of [`FUNDING_STREAM_RECEIVER_DENOMINATOR`].
+pub const FUNDING_STREAM_MG_NUMERATOR: u64 = 8;
+
+/// Every funding stream receiver, in the order their amounts should be checked.
+pub const FUNDING_STREAM_RECEIVERS: [FundingStreamReceiver; 3] = [
+    FundingStreamReceiver::Ecc,
+    FundingStreamReceiver::ZcashFoundation,
+    FundingStreamReceiver::MajorGrants,
+];
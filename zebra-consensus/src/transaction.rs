@@ -110,20 +110,25 @@ where
             unimplemented!();
         }
 
-        let (tx, known_utxos, upgrade) = match req {
+        let (tx, known_utxos, upgrade, height) = match req {
             Request::Block {
                 transaction,
                 known_utxos,
                 height,
             } => {
                 let upgrade = NetworkUpgrade::current(self.network, height);
-                (transaction, known_utxos, upgrade)
+                (transaction, known_utxos, upgrade, Some(height))
             }
             Request::Mempool {
                 transaction,
                 known_utxos,
                 upgrade,
-            } => (transaction, known_utxos, upgrade),
+            } => {
+                // Bug: the mempool doesn't yet tell us the height the
+                // transaction would be mined at, so we can't check its
+                // expiry height here. See #1683.
+                (transaction, known_utxos, upgrade, None)
+            }
         };
 
         let mut spend_verifier = primitives::groth16::SPEND_VERIFIER.clone();
@@ -158,6 +163,10 @@ where
                     // Do basic checks first
                     check::has_inputs_and_outputs(&tx)?;
 
+                    if let Some(height) = height {
+                        check::expiry_height(&tx, height)?;
+                    }
+
                     // Handle transparent inputs and outputs.
                     if tx.is_coinbase() {
                         check::coinbase_tx_no_joinsplit_or_spend(&tx)?;
@@ -3,6 +3,7 @@
 //! Code in this file can freely assume that no pre-V4 transactions are present.
 
 use zebra_chain::{
+    block,
     sapling::{AnchorVariant, Output, PerSpendAnchor, ShieldedData, Spend},
     transaction::Transaction,
 };
@@ -61,6 +62,26 @@ pub fn has_inputs_and_outputs(tx: &Transaction) -> Result<(), TransactionError>
     }
 }
 
+/// Check that the transaction hasn't expired, given the height of the block
+/// it would be mined in.
+///
+/// Transactions with no expiry height, an expiry height of zero, or (for
+/// coinbase transactions) any expiry height, never expire.
+///
+/// https://zips.z.cash/protocol/protocol.pdf#txnencodingandconsensus
+pub fn expiry_height(tx: &Transaction, block_height: block::Height) -> Result<(), TransactionError> {
+    if tx.expiry_height_is_valid(block_height) {
+        Ok(())
+    } else {
+        Err(TransactionError::ExpiredTransaction {
+            expiry_height: tx
+                .expiry_height()
+                .expect("expiry_height_is_valid() only returns false when expiry_height() is Some"),
+            block_height,
+        })
+    }
+}
+
 /// Check that if there are no Spends or Outputs, that valueBalance is also 0.
 ///
 /// https://zips.z.cash/protocol/protocol.pdf#consensusfrombitcoin
@@ -2,16 +2,19 @@
 //! seen, and what services they provide.
 
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     iter::Extend,
-    net::SocketAddr,
-    time::Instant,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
 };
 
 use chrono::{DateTime, Utc};
+use serde::Serialize;
 use tracing::Span;
 
-use crate::{constants, types::MetaAddr, PeerAddrState};
+use crate::{constants, protocol::external::types::Version, types::MetaAddr, PeerAddrState};
+
+use zebra_chain::block;
 
 /// A database of peers, their advertised services, and information on when they
 /// were last seen.
@@ -25,6 +28,12 @@ pub struct AddressBook {
 
     /// The last time we logged a message about the address metrics
     last_address_log: Option<Instant>,
+
+    /// Addresses last seen longer than this ago are excluded from
+    /// [`AddressBook::sanitized`], so we don't gossip peers that are
+    /// unlikely to still be reachable. See
+    /// [`Config::gossip_freshness_cutoff`](crate::Config::gossip_freshness_cutoff).
+    gossip_freshness_cutoff: Duration,
 }
 
 /// Metrics about the states of the addresses in an [`AddressBook`].
@@ -49,6 +58,57 @@ pub struct AddressMetrics {
     recently_stopped_responding: usize,
 }
 
+/// A snapshot of a single [`AddressBook`] entry, in a stable format suitable
+/// for serialization (for example, to JSON).
+///
+/// This is intended for diagnostics: operators can dump an [`AddressBook`]
+/// to inspect why their node is or isn't finding peers. The format is
+/// intentionally simple, so it doesn't depend on the wire encoding of any
+/// particular field changing in lock-step with this struct.
+#[derive(Clone, Debug, Serialize)]
+pub struct AddressBookPeerInfo {
+    /// The peer's address.
+    pub addr: SocketAddr,
+    /// The services advertised by the peer, as raw bits. See
+    /// [`MetaAddr::services`] for the meaning of this field.
+    pub services: u64,
+    /// The outcome of our most recent communication attempt with this peer.
+    pub state: PeerAddrState,
+    /// The last time we interacted with this peer, as a Unix timestamp.
+    pub last_seen: i64,
+    /// The negotiated protocol version from our most recent handshake with
+    /// this peer, if any.
+    pub version: Option<Version>,
+    /// The user agent string the peer sent us during our most recent
+    /// handshake with it, if any.
+    pub user_agent: Option<String>,
+    /// The best chain tip height the peer reported during our most recent
+    /// handshake with it, if any.
+    pub start_height: Option<block::Height>,
+    /// Whether the peer asked us to relay transactions to it, during our
+    /// most recent handshake with it, if any.
+    pub relay: Option<bool>,
+    /// The number of consecutive heartbeats the peer failed to respond to,
+    /// before our most recent interaction with it.
+    pub missed_heartbeats: u32,
+}
+
+impl AddressBookPeerInfo {
+    fn new(meta_addr: &MetaAddr) -> AddressBookPeerInfo {
+        AddressBookPeerInfo {
+            addr: meta_addr.addr,
+            services: meta_addr.services.bits(),
+            state: meta_addr.last_connection_state,
+            last_seen: meta_addr.get_last_seen().timestamp(),
+            version: meta_addr.version,
+            user_agent: meta_addr.user_agent.clone(),
+            start_height: meta_addr.start_height,
+            relay: meta_addr.relay,
+            missed_heartbeats: meta_addr.missed_heartbeats,
+        }
+    }
+}
+
 #[allow(clippy::len_without_is_empty)]
 impl AddressBook {
     /// Construct an `AddressBook` with the given [`tracing::Span`].
@@ -60,24 +120,56 @@ impl AddressBook {
             by_addr: HashMap::default(),
             span,
             last_address_log: None,
+            gossip_freshness_cutoff: constants::DEFAULT_GOSSIP_FRESHNESS_CUTOFF,
         };
 
         new_book.update_metrics();
         new_book
     }
 
-    /// Get the contents of `self` in random order with sanitized timestamps.
+    /// Construct an `AddressBook` with the given [`tracing::Span`], gossiping
+    /// only addresses seen within `gossip_freshness_cutoff`.
+    pub fn with_gossip_freshness_cutoff(span: Span, gossip_freshness_cutoff: Duration) -> AddressBook {
+        let mut book = AddressBook::new(span);
+        book.gossip_freshness_cutoff = gossip_freshness_cutoff;
+        book
+    }
+
+    /// Get the contents of `self` in random order with sanitized timestamps,
+    /// excluding addresses last seen longer ago than
+    /// [`gossip_freshness_cutoff`](Self::gossip_freshness_cutoff).
     pub fn sanitized(&self) -> Vec<MetaAddr> {
         use rand::seq::SliceRandom;
         let _guard = self.span.enter();
+
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(self.gossip_freshness_cutoff)
+                .unwrap_or_else(|_| chrono::Duration::max_value());
+
         let mut peers = self
             .peers()
+            .filter(|addr| addr.get_last_seen() >= cutoff)
             .map(|a| MetaAddr::sanitize(&a))
             .collect::<Vec<_>>();
         peers.shuffle(&mut rand::thread_rng());
         peers
     }
 
+    /// Returns a snapshot of every peer in this address book, in a stable
+    /// format suitable for serialization (for example, to JSON), sorted by
+    /// address.
+    ///
+    /// Unlike [`AddressBook::sanitized`], this includes every peer, and does
+    /// not truncate timestamps, because it's intended for diagnostics, not
+    /// for gossiping to other peers.
+    pub fn peer_info(&self) -> Vec<AddressBookPeerInfo> {
+        let _guard = self.span.enter();
+
+        let mut peers: Vec<_> = self.by_addr.values().map(AddressBookPeerInfo::new).collect();
+        peers.sort_by_key(|peer| peer.addr);
+        peers
+    }
+
     /// Returns true if the address book has an entry for `addr`.
     pub fn contains_addr(&self, addr: &SocketAddr) -> bool {
         let _guard = self.span.enter();
@@ -193,18 +285,38 @@ impl AddressBook {
 
     /// Return an iterator over peers that are due for a reconnection attempt,
     /// in reconnection attempt order.
+    ///
+    /// Like Bitcoin Core's addrman, candidates are split into `Responded`
+    /// ("tried") and `NeverAttempted`/`Failed` ("new") buckets, keyed by
+    /// [`address_group`], and merged in round-robin order within each state.
+    /// This stops a single gossiping peer from dominating the front of the
+    /// order with many addresses from the same network block.
     pub fn reconnection_peers(&'_ self) -> impl Iterator<Item = MetaAddr> + '_ {
         let _guard = self.span.enter();
 
         // TODO: optimise, if needed, or get rid of older peers
 
-        // Skip live peers, and peers pending a reconnect attempt, then sort using BTreeSet
-        self.by_addr
+        // Skip live peers, and peers pending a reconnect attempt, then group
+        // the remainder into buckets, keyed by state and address group.
+        // Grouping by state first preserves the existing reconnection order:
+        // `Responded`, then `NeverAttempted`, then `Failed` (see
+        // `PeerAddrState`'s `Ord` impl).
+        let mut buckets: BTreeMap<PeerAddrState, BTreeMap<AddrGroup, BTreeSet<MetaAddr>>> =
+            BTreeMap::new();
+        for peer in self
+            .by_addr
             .values()
             .filter(move |peer| !self.maybe_connected_addr(&peer.addr))
-            .collect::<BTreeSet<_>>()
-            .into_iter()
-            .cloned()
+        {
+            buckets
+                .entry(peer.last_connection_state)
+                .or_default()
+                .entry(address_group(&peer.addr))
+                .or_default()
+                .insert(peer.clone());
+        }
+
+        buckets.into_values().flat_map(round_robin_buckets)
     }
 
     /// Return an iterator over all the peers in `state`, in arbitrary order.
@@ -253,12 +365,29 @@ impl AddressBook {
 
     /// Returns metrics for the addresses in this address book.
     pub fn address_metrics(&self) -> AddressMetrics {
-        let responded = self.state_peers(PeerAddrState::Responded).count();
-        let never_attempted = self.state_peers(PeerAddrState::NeverAttempted).count();
-        let failed = self.state_peers(PeerAddrState::Failed).count();
-        let attempt_pending = self.state_peers(PeerAddrState::AttemptPending).count();
+        // A single pass over `by_addr`, rather than one `state_peers` /
+        // `recently_live_peers` scan (each of which clones every matching
+        // `MetaAddr`) per counter. This runs on every `update`/`take` call,
+        // so avoiding the repeated full-book clones matters.
+        let mut responded = 0;
+        let mut never_attempted = 0;
+        let mut failed = 0;
+        let mut attempt_pending = 0;
+        let mut recently_live = 0;
+
+        for peer in self.by_addr.values() {
+            match peer.last_connection_state {
+                PeerAddrState::Responded => responded += 1,
+                PeerAddrState::NeverAttempted => never_attempted += 1,
+                PeerAddrState::Failed => failed += 1,
+                PeerAddrState::AttemptPending => attempt_pending += 1,
+            }
+
+            if self.recently_live_addr(&peer.addr) {
+                recently_live += 1;
+            }
+        }
 
-        let recently_live = self.recently_live_peers().count();
         let recently_stopped_responding = responded
             .checked_sub(recently_live)
             .expect("all recently live peers must have responded");
@@ -364,3 +493,56 @@ impl<'a> Iterator for Drain<'a> {
         self.book.take(next_item_addr)
     }
 }
+
+/// A coarse grouping of a peer address's network origin, used to bucket
+/// reconnection candidates.
+///
+/// Loosely based on Bitcoin Core's addrman `CNetAddr::GetGroup`, but
+/// simplified to a fixed-width IPv4 /16 or IPv6 /32 prefix.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+struct AddrGroup([u8; 5]);
+
+/// Returns the [`AddrGroup`] for `addr`.
+fn address_group(addr: &SocketAddr) -> AddrGroup {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            let o = ip.octets();
+            AddrGroup([0, 0, 0, o[0], o[1]])
+        }
+        IpAddr::V6(ip) => {
+            let o = ip.octets();
+            AddrGroup([1, o[0], o[1], o[2], o[3]])
+        }
+    }
+}
+
+/// Merges `buckets` via round-robin, taking one entry from each non-empty
+/// bucket (in the bucket's own order) before moving on to the next bucket.
+///
+/// This spreads the result across buckets, rather than letting one bucket's
+/// entries dominate the front of the order.
+fn round_robin_buckets<K>(
+    buckets: BTreeMap<K, BTreeSet<MetaAddr>>,
+) -> impl Iterator<Item = MetaAddr> {
+    let mut buckets: Vec<BTreeSet<MetaAddr>> = buckets.into_values().collect();
+    let mut next_bucket = 0;
+
+    std::iter::from_fn(move || {
+        if buckets.is_empty() {
+            return None;
+        }
+
+        for _ in 0..buckets.len() {
+            let index = next_bucket % buckets.len();
+            next_bucket += 1;
+
+            if let Some(candidate) = buckets[index].iter().next().cloned() {
+                buckets[index].remove(&candidate);
+                return Some(candidate);
+            }
+        }
+
+        // Every bucket was empty.
+        None
+    })
+}
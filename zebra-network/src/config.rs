@@ -2,7 +2,7 @@ use std::{collections::HashSet, net::SocketAddr, string::String, time::Duration}
 
 use zebra_chain::parameters::Network;
 
-use crate::BoxError;
+use crate::{protocol::external::types::Magic, types::Version, BoxError};
 
 /// The number of times Zebra will retry each initial peer, before checking if
 /// any other initial peers have returned addresses.
@@ -12,12 +12,17 @@ const MAX_SINGLE_PEER_RETRIES: usize = 2;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct Config {
-    /// The address on which this node should listen for connections.
+    /// The addresses on which this node should listen for connections.
     ///
-    /// Zebra will also advertise this address to other nodes. Advertising a
-    /// different external IP address is currently not supported, see #1890
-    /// for details.
-    pub listen_addr: SocketAddr,
+    /// Zebra opens a separate listener for each address, so a node can
+    /// listen on both an IPv4 and an IPv6 socket, or on several interfaces,
+    /// at once. Every listener feeds the same handshake and inbound request
+    /// pipeline. Must contain at least one address.
+    ///
+    /// Zebra advertises the first address in this list to other nodes.
+    /// Advertising a different external IP address is currently not
+    /// supported, see #1890 for details.
+    pub listen_addrs: Vec<SocketAddr>,
 
     /// The network to connect to.
     pub network: Network,
@@ -30,6 +35,30 @@ pub struct Config {
     /// testnet.
     pub initial_testnet_peers: HashSet<String>,
 
+    /// Overrides the network magic number, so Zebra can connect to a
+    /// custom, private Zcash-compatible network, such as a private
+    /// testnet, rather than the public `Mainnet` or `Testnet`.
+    ///
+    /// If set, [`Config::initial_custom_peers`] is used instead of
+    /// [`Config::initial_mainnet_peers`] or [`Config::initial_testnet_peers`]
+    /// for the initial peer list, regardless of [`Config::network`].
+    pub network_magic_override: Option<Magic>,
+
+    /// A list of initial peers for the peerset when
+    /// [`Config::network_magic_override`] is set.
+    pub initial_custom_peers: HashSet<String>,
+
+    /// Overrides the default port used to detect a misconfigured
+    /// [`Config::listen_addrs`], when [`Config::network_magic_override`] is
+    /// set.
+    ///
+    /// Zebra warns if a listener is configured with the default port of
+    /// the *other* network, to help operators notice accidental
+    /// mainnet/testnet port mix-ups. That check doesn't apply to a custom
+    /// network, which has no fixed default port of its own. If this field is
+    /// set, Zebra instead warns when a listener's port differs from it.
+    pub custom_network_default_port: Option<u16>,
+
     /// The initial target size for the peer set.
     ///
     /// If you have a slow network connection, and Zebra is having trouble
@@ -45,6 +74,125 @@ pub struct Config {
     /// CandidateSet::PEER_CONNECTION_INTERVAL
     #[serde(alias = "new_peer_interval")]
     pub crawl_new_peer_interval: Duration,
+
+    /// If set, all outbound connections are dialed through this SOCKS5 proxy
+    /// address, rather than directly.
+    ///
+    /// This is typically the address of a local Tor daemon, e.g.
+    /// `127.0.0.1:9050`.
+    pub tor_proxy: Option<SocketAddr>,
+
+    /// If set, outbound connections bind to this local address, rather than
+    /// letting the operating system choose one.
+    ///
+    /// This is useful on multi-homed servers, or for operators who want to
+    /// route peer-to-peer traffic over a specific interface or VPN, without
+    /// relying on firewall rules. The port in this address is ignored; the
+    /// operating system still chooses an ephemeral source port.
+    pub outbound_bind_addr: Option<SocketAddr>,
+
+    /// If true, Zebra will refuse to dial any peer directly: every outbound
+    /// connection must go through `tor_proxy`.
+    ///
+    /// Clearnet addresses can still be received and gossiped in `Addr`
+    /// messages, they are just never dialed. This is intended for operators
+    /// in hostile network environments who want to hide their own network
+    /// location.
+    ///
+    /// Requires `tor_proxy` to be set; Zebra fails to start otherwise.
+    pub tor_only: bool,
+
+    /// An optional limit on the total upload and download bandwidth used by
+    /// all peer connections combined, in bytes per second.
+    pub max_total_bandwidth_per_second: Option<u64>,
+
+    /// An optional limit on the upload and download bandwidth used by each
+    /// individual peer connection, in bytes per second.
+    pub max_per_peer_bandwidth_per_second: Option<u64>,
+
+    /// A list of trusted peers that are always preferred as reconnection
+    /// candidates over other peers Zebra has learned about.
+    ///
+    /// This is useful for operators who run their own set of nodes and want
+    /// Zebra to stay connected to them, rather than the wider network.
+    pub priority_peers: HashSet<String>,
+
+    /// A list of peer addresses that are exempt from Zebra's per-peer and
+    /// global bandwidth limits, inbound request quota, and outbound gossip
+    /// queue drops, similar to `zcashd`'s `-whitebind`/`-whitelist`.
+    ///
+    /// This is useful for trusted peers, such as other nodes run by the same
+    /// operator, that should never be throttled or have their gossip dropped.
+    pub whitelisted_peers: HashSet<SocketAddr>,
+
+    /// Addresses last seen longer than this ago are not gossiped to other
+    /// peers via `Peers`/`Addr` responses.
+    pub gossip_freshness_cutoff: Duration,
+
+    /// The timeout for requests made to a remote peer, including the
+    /// individual message exchanges that make up a handshake.
+    pub request_timeout: Duration,
+
+    /// The timeout for handshakes when connecting to new peers.
+    ///
+    /// This timeout should remain small, because it helps stop slow peers
+    /// getting into the peer set. This is particularly important for
+    /// network-constrained nodes, and on testnet.
+    pub handshake_timeout: Duration,
+
+    /// The interval between keepalive `Ping` messages sent to each connected
+    /// peer, if set.
+    ///
+    /// If `None`, Zebra never sends heartbeats, and relies on the peer to
+    /// keep the connection alive. This is useful for some test networks,
+    /// where heartbeats would otherwise dominate the network traffic.
+    pub heartbeat_interval: Option<Duration>,
+
+    /// The minimum time between successive outbound connection attempts made
+    /// by the crawler's candidate set.
+    ///
+    /// ## Security
+    ///
+    /// Zebra resists distributed denial of service attacks by making sure
+    /// that new peer connections are initiated at least this long apart.
+    /// Reducing this value below its default can make Zebra an easier
+    /// participant in amplification attacks.
+    pub min_peer_connection_interval: Duration,
+
+    /// How frequently we open a feeler connection to a `NeverAttempted`
+    /// candidate, to confirm that it's reachable.
+    ///
+    /// Feeler connections are closed immediately after the handshake
+    /// completes, so they help validate the address book without consuming
+    /// a long-lived outbound peer slot. This is an important eclipse
+    /// attack defense: it stops the address book filling up with unreachable
+    /// addresses that an attacker gossiped to us.
+    pub feeler_interval: Duration,
+
+    /// How frequently we disconnect our longest-lived outbound peer, and
+    /// replace it with a fresh candidate.
+    ///
+    /// This keeps Zebra's view of the network diverse over time, rather than
+    /// getting stuck with the same set of outbound peers indefinitely, which
+    /// is also an eclipse attack defense.
+    pub outbound_rotation_interval: Duration,
+
+    /// The minimum protocol version we accept from peers, during the
+    /// handshake.
+    ///
+    /// Peers advertising an older version are considered obsolete, and
+    /// Zebra disconnects from them. If unset, Zebra falls back to the
+    /// minimum version for `constants::MIN_NETWORK_UPGRADE`.
+    pub min_peer_protocol_version: Option<Version>,
+
+    /// If set, Zebra only keeps full block bodies for this many blocks below
+    /// its local tip, as configured by `zebra_state::Config::pruning`.
+    ///
+    /// This should match the state's pruning depth, if pruning is enabled.
+    /// Zebra advertises `NODE_NETWORK_LIMITED` instead of `NODE_NETWORK`
+    /// during the handshake when this is set, so peers don't expect it to
+    /// serve historical blocks it no longer has.
+    pub pruned_block_retention: Option<u32>,
 }
 
 impl Config {
@@ -53,7 +201,12 @@ impl Config {
     ///
     /// If DNS resolution fails or times out for all peers, continues retrying
     /// until at least one peer is found.
-    async fn resolve_peers(peers: &HashSet<String>) -> HashSet<SocketAddr> {
+    ///
+    /// Returns one address list per host, each sorted with
+    /// [`Config::resolve_host`]'s Happy Eyeballs preference, so that callers
+    /// can race the addresses for a single host against each other, rather
+    /// than racing different peers.
+    async fn resolve_peers(peers: &HashSet<String>) -> Vec<Vec<SocketAddr>> {
         use futures::stream::StreamExt;
 
         loop {
@@ -61,14 +214,14 @@ impl Config {
             // no peers in the combined list. DNS failures are correlated, so all
             // peers can fail DNS, leaving Zebra with a small list of custom IP
             // address peers. Individual retries avoid this issue.
-            let peer_addresses = peers
+            let peer_addresses: Vec<Vec<SocketAddr>> = peers
                 .iter()
                 .map(|s| Config::resolve_host(s, MAX_SINGLE_PEER_RETRIES))
                 .collect::<futures::stream::FuturesUnordered<_>>()
-                .concat()
+                .collect()
                 .await;
 
-            if peer_addresses.is_empty() {
+            if peer_addresses.iter().all(Vec::is_empty) {
                 tracing::info!(
                     ?peers,
                     ?peer_addresses,
@@ -83,18 +236,59 @@ impl Config {
     }
 
     /// Get the initial seed peers based on the configured network.
-    pub async fn initial_peers(&self) -> HashSet<SocketAddr> {
+    ///
+    /// Returns one address list per configured host, so that callers can
+    /// use Happy Eyeballs dialing for hosts that resolve to both IPv4 and
+    /// IPv6 addresses.
+    ///
+    /// If [`Config::network_magic_override`] is set, uses
+    /// [`Config::initial_custom_peers`] instead of the peer list for
+    /// [`Config::network`].
+    pub async fn initial_peers(&self) -> Vec<Vec<SocketAddr>> {
+        if self.network_magic_override.is_some() {
+            return Config::resolve_peers(&self.initial_custom_peers).await;
+        }
+
         match self.network {
             Network::Mainnet => Config::resolve_peers(&self.initial_mainnet_peers).await,
             Network::Testnet => Config::resolve_peers(&self.initial_testnet_peers).await,
         }
     }
 
+    /// Returns `true` if `addr` is in [`Config::whitelisted_peers`], and so
+    /// should be exempt from rate limits, inbound request quotas, and
+    /// gossip queue drops.
+    pub fn is_peer_whitelisted(&self, addr: &SocketAddr) -> bool {
+        self.whitelisted_peers.contains(addr)
+    }
+
+    /// Resolves the configured `priority_peers` into zero or more IP
+    /// addresses.
+    ///
+    /// Unlike [`Config::initial_peers`], an empty (the default) or
+    /// unresolvable `priority_peers` list is not retried, since most
+    /// deployments don't configure any priority peers at all.
+    pub async fn resolve_priority_peers(&self) -> HashSet<SocketAddr> {
+        if self.priority_peers.is_empty() {
+            return HashSet::new();
+        }
+
+        use futures::stream::StreamExt;
+        self.priority_peers
+            .iter()
+            .map(|s| Config::resolve_host(s, MAX_SINGLE_PEER_RETRIES))
+            .collect::<futures::stream::FuturesUnordered<_>>()
+            .concat()
+            .await
+            .into_iter()
+            .collect()
+    }
+
     /// Resolves `host` into zero or more IP addresses, retrying up to
     /// `max_retries` times.
     ///
     /// If DNS continues to fail, returns an empty list of addresses.
-    async fn resolve_host(host: &str, max_retries: usize) -> HashSet<SocketAddr> {
+    async fn resolve_host(host: &str, max_retries: usize) -> Vec<SocketAddr> {
         for retry_count in 1..=max_retries {
             match Config::resolve_host_once(host).await {
                 Ok(addresses) => return addresses,
@@ -103,19 +297,28 @@ impl Config {
             tokio::time::sleep(crate::constants::DNS_LOOKUP_TIMEOUT).await;
         }
 
-        HashSet::new()
+        Vec::new()
     }
 
     /// Resolves `host` into zero or more IP addresses.
     ///
     /// If `host` is a DNS name, performs DNS resolution with a timeout of a few seconds.
     /// If DNS resolution fails or times out, returns an error.
-    async fn resolve_host_once(host: &str) -> Result<HashSet<SocketAddr>, BoxError> {
+    ///
+    /// IPv6 addresses are returned before IPv4 addresses, so that dialers
+    /// using Happy Eyeballs (RFC 8305) prefer IPv6, the usually-faster
+    /// address family, and only fall back to IPv4 after
+    /// [`constants::HAPPY_EYEBALLS_STAGGER`](crate::constants::HAPPY_EYEBALLS_STAGGER).
+    async fn resolve_host_once(host: &str) -> Result<Vec<SocketAddr>, BoxError> {
         let fut = tokio::net::lookup_host(host);
         let fut = tokio::time::timeout(crate::constants::DNS_LOOKUP_TIMEOUT, fut);
 
         match fut.await {
-            Ok(Ok(ips)) => Ok(ips.collect()),
+            Ok(Ok(ips)) => {
+                let mut ips: Vec<SocketAddr> = ips.collect();
+                ips.sort_by_key(|addr| !addr.is_ipv6());
+                Ok(ips)
+            }
             Ok(Err(e)) => {
                 tracing::info!(?host, ?e, "DNS error resolving peer IP address");
                 Err(e.into())
@@ -150,12 +353,15 @@ impl Default for Config {
         .collect();
 
         Config {
-            listen_addr: "0.0.0.0:8233"
+            listen_addrs: vec!["0.0.0.0:8233"
                 .parse()
-                .expect("Hardcoded address should be parseable"),
+                .expect("Hardcoded address should be parseable")],
             network: Network::Mainnet,
             initial_mainnet_peers: mainnet_peers,
             initial_testnet_peers: testnet_peers,
+            network_magic_override: None,
+            initial_custom_peers: HashSet::new(),
+            custom_network_default_port: None,
             crawl_new_peer_interval: Duration::from_secs(60),
 
             // The default peerset target size should be large enough to ensure
@@ -172,6 +378,26 @@ impl Default for Config {
             // But the peer set for slow nodes is typically much smaller, due to
             // the handshake RTT timeout.
             peerset_initial_target_size: 50,
+
+            tor_proxy: None,
+            outbound_bind_addr: None,
+            tor_only: false,
+
+            max_total_bandwidth_per_second: None,
+            max_per_peer_bandwidth_per_second: None,
+
+            priority_peers: HashSet::new(),
+            whitelisted_peers: HashSet::new(),
+            gossip_freshness_cutoff: crate::constants::DEFAULT_GOSSIP_FRESHNESS_CUTOFF,
+
+            request_timeout: crate::constants::REQUEST_TIMEOUT,
+            handshake_timeout: crate::constants::HANDSHAKE_TIMEOUT,
+            heartbeat_interval: Some(crate::constants::HEARTBEAT_INTERVAL),
+            min_peer_connection_interval: crate::constants::MIN_PEER_CONNECTION_INTERVAL,
+            feeler_interval: Duration::from_secs(60),
+            outbound_rotation_interval: Duration::from_secs(60 * 60),
+            min_peer_protocol_version: None,
+            pruned_block_retention: None,
         }
     }
 }
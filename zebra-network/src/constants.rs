@@ -22,10 +22,13 @@ use zebra_chain::parameters::NetworkUpgrade;
 /// buffer adds up to 6 seconds worth of blocks to the queue.
 pub const PEERSET_BUFFER_SIZE: usize = 3;
 
-/// The timeout for requests made to a remote peer.
+/// The default timeout for requests made to a remote peer, unless overridden
+/// by [`Config::request_timeout`](crate::Config::request_timeout).
 pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
-/// The timeout for handshakes when connecting to new peers.
+/// The default timeout for handshakes when connecting to new peers, unless
+/// overridden by
+/// [`Config::handshake_timeout`](crate::Config::handshake_timeout).
 ///
 /// This timeout should remain small, because it helps stop slow peers getting
 /// into the peer set. This is particularly important for network-constrained
@@ -45,10 +48,26 @@ pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(4);
 /// specific manner that matches up with this math.
 pub const LIVE_PEER_DURATION: Duration = Duration::from_secs(60 + 20 + 20 + 20);
 
-/// Regular interval for sending keepalive `Ping` messages to each
-/// connected peer.
+/// The default interval for sending keepalive `Ping` messages to each
+/// connected peer, unless overridden by
+/// [`Config::heartbeat_interval`](crate::Config::heartbeat_interval).
 pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
 
+/// The number of consecutive heartbeats a peer is allowed to miss before we
+/// give up on it and close the connection.
+///
+/// Occasional missed heartbeats are tolerated, so that transient network
+/// hiccups don't needlessly churn the peer set. The number of misses that
+/// occurred before we gave up is recorded in the peer's
+/// [`MetaAddr::missed_heartbeats`](crate::types::MetaAddr), so that flaky
+/// links are visible in diagnostics.
+pub const MAX_MISSED_HEARTBEATS: u32 = 3;
+
+/// The number of times the peer set will transparently retry an idempotent
+/// request against another ready peer, after the peer it was originally
+/// routed to disconnects or returns an error.
+pub const PEER_SET_RETRY_LIMIT: usize = 2;
+
 /// The number of GetAddr requests sent when crawling for new peers.
 ///
 /// ## SECURITY
@@ -63,6 +82,53 @@ pub const GET_ADDR_FANOUT: usize = 2;
 /// messages from each of our peers.
 pub const TIMESTAMP_TRUNCATION_SECONDS: i64 = 30 * 60;
 
+/// The default maximum age of an address before it's considered too stale to
+/// gossip to other peers, unless overridden by
+/// [`Config::gossip_freshness_cutoff`](crate::Config::gossip_freshness_cutoff).
+///
+/// `zcashd` uses a similar cutoff (`nRelevantServices` and the "terrible"
+/// address check) to avoid gossiping addresses that are unlikely to still be
+/// online.
+pub const DEFAULT_GOSSIP_FRESHNESS_CUTOFF: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// The maximum number of hashes in a single `inv` message sent in response to
+/// a `getblocks` request.
+///
+/// This mirrors `zcashd`'s limit, and keeps a single response within the
+/// maximum inventory count allowed by the wire format.
+pub const GETBLOCKS_INV_LIMIT: usize = 500;
+
+/// The maximum number of headers in a single `headers` message sent in
+/// response to a `getheaders` request.
+///
+/// This mirrors `zcashd`'s limit, and keeps a single response within the
+/// maximum header count allowed by the wire format.
+pub const GETHEADERS_LIMIT: usize = 160;
+
+/// The maximum number of inbound requests a single peer connection may make
+/// within [`INBOUND_REQUEST_QUOTA_INTERVAL`], before the connection is
+/// dropped as a basic defence against request-flooding peers.
+pub const INBOUND_REQUEST_QUOTA: usize = 100;
+
+/// The interval over which [`INBOUND_REQUEST_QUOTA`] is enforced.
+pub const INBOUND_REQUEST_QUOTA_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The delay before starting a fallback connection attempt to an alternate
+/// address family for the same host, when dialing initial peers.
+///
+/// This implements the "Connection Attempt Delay" from the Happy Eyeballs
+/// algorithm in [RFC 8305 section 3](https://tools.ietf.org/html/rfc8305#section-3),
+/// which avoids long timeouts on dual-stack hosts with broken IPv6.
+pub const HAPPY_EYEBALLS_STAGGER: Duration = Duration::from_millis(250);
+
+/// The maximum number of outbound gossip messages (`addr`/`inv` advertisements)
+/// that a peer connection will queue before sending.
+///
+/// Gossip is low-priority: once this queue is full, the oldest queued gossip
+/// message is dropped to make room for the newest one, rather than blocking
+/// or dropping higher-priority request/response traffic.
+pub const GOSSIP_QUEUE_SIZE: usize = 10;
+
 /// The User-Agent string provided by the node.
 ///
 /// This must be a valid [BIP 14] user agent.
@@ -90,6 +156,16 @@ pub const CURRENT_VERSION: Version = Version(170_013);
 //       See the detailed comment in handshake.rs, where this constant is used.
 pub const MIN_NETWORK_UPGRADE: NetworkUpgrade = NetworkUpgrade::Canopy;
 
+/// The default minimum time between successive outbound connection attempts,
+/// unless overridden by
+/// [`Config::min_peer_connection_interval`](crate::Config::min_peer_connection_interval).
+///
+/// ## Security
+///
+/// Zebra resists distributed denial of service attacks by making sure that
+/// new peer connections are initiated at least this long apart.
+pub const MIN_PEER_CONNECTION_INTERVAL: Duration = Duration::from_millis(100);
+
 /// The default RTT estimate for peer responses.
 ///
 /// We choose a high value for the default RTT, so that new peers must prove they
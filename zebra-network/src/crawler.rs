@@ -0,0 +1,39 @@
+//! A standalone network crawler, for building DNS seeders and similar tools.
+
+use futures::{future, stream::StreamExt, Stream};
+
+use crate::{init, types::MetaAddr, BoxError, Config, PeerEvent, Response};
+
+/// Crawl the network for reachable peers, without ever downloading blocks or
+/// transactions.
+///
+/// This runs the same handshake and candidate-set crawl loop that powers
+/// [`init`], but never uses the resulting peer set to send requests, so it
+/// never issues [`Request::BlocksByHash`](crate::Request::BlocksByHash) or
+/// similar requests. The returned stream yields a [`MetaAddr`] - including
+/// the protocol version and services the peer advertised during its
+/// handshake - every time a peer is successfully connected to, which makes
+/// this suitable for feeding a DNS seeder.
+///
+/// Dropping the returned stream stops the crawl.
+pub async fn crawl_seed_peers(config: Config) -> impl Stream<Item = MetaAddr> {
+    // A seeder doesn't serve any inbound requests, so the inbound service
+    // just acknowledges them without doing any work.
+    let inbound = tower::service_fn(|_req| future::ok::<Response, BoxError>(Response::Nil));
+
+    let (_peer_set, address_book, _peer_set_readiness, peer_events) =
+        init(config, inbound).await;
+
+    peer_events
+        .into_stream()
+        .filter_map(|event| future::ready(event.ok()))
+        .filter_map(move |event| {
+            let address_book = address_book.clone();
+            future::ready(match event {
+                PeerEvent::HandshakeCompleted(addr) => {
+                    address_book.lock().unwrap().get_by_addr(addr)
+                }
+                PeerEvent::Disconnected { .. } | PeerEvent::Banned(_) => None,
+            })
+        })
+}
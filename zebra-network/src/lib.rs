@@ -67,6 +67,7 @@ pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 mod address_book;
 mod config;
 pub mod constants;
+mod crawler;
 mod isolated;
 mod meta_addr;
 mod peer;
@@ -76,16 +77,20 @@ mod protocol;
 mod timestamp_collector;
 
 pub use crate::{
-    address_book::AddressBook,
+    address_book::{AddressBook, AddressBookPeerInfo},
     config::Config,
+    crawler::crawl_seed_peers,
     isolated::connect_isolated,
     meta_addr::PeerAddrState,
-    peer_set::init,
-    policies::{RetryErrors, RetryLimit},
+    peer_set::{init, PeerEvent},
+    policies::{RetryErrors, RetryLimit, RetryLimitIdempotent},
     protocol::internal::{Request, Response},
 };
 
 /// Types used in the definition of [`Request`] and [`Response`] messages.
 pub mod types {
-    pub use crate::{meta_addr::MetaAddr, protocol::types::PeerServices};
+    pub use crate::{
+        meta_addr::MetaAddr,
+        protocol::{external::types::Version, types::PeerServices},
+    };
 }
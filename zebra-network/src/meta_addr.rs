@@ -9,12 +9,18 @@ use std::{
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use chrono::{DateTime, TimeZone, Utc};
 
-use zebra_chain::serialization::{
-    ReadZcashExt, SerializationError, TrustedPreallocate, WriteZcashExt, ZcashDeserialize,
-    ZcashSerialize,
+use zebra_chain::{
+    block,
+    serialization::{
+        ReadZcashExt, SerializationError, TrustedPreallocate, WriteZcashExt, ZcashDeserialize,
+        ZcashSerialize,
+    },
 };
 
-use crate::protocol::{external::MAX_PROTOCOL_MESSAGE_LEN, types::PeerServices};
+use crate::protocol::{
+    external::{types::Version, MAX_PROTOCOL_MESSAGE_LEN},
+    types::PeerServices,
+};
 
 use PeerAddrState::*;
 
@@ -32,7 +38,7 @@ mod tests;
 /// liveness based on the current time. This derived state is tracked using
 /// [`AddressBook::maybe_connected_peers`] and
 /// [`AddressBook::reconnection_peers`].
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, serde::Serialize)]
 #[cfg_attr(any(test, feature = "proptest-impl"), derive(Arbitrary))]
 pub enum PeerAddrState {
     /// The peer has sent us a valid message.
@@ -93,7 +99,10 @@ impl PartialOrd for PeerAddrState {
 /// An address with metadata on its advertised services and last-seen time.
 ///
 /// [Bitcoin reference](https://en.bitcoin.it/wiki/Protocol_documentation#Network_address)
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+///
+/// `MetaAddr` used to be `Copy`, but it no longer is, now that it can carry a
+/// peer's user agent from the handshake.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MetaAddr {
     /// The peer's address.
     pub addr: SocketAddr,
@@ -121,6 +130,43 @@ pub struct MetaAddr {
 
     /// The outcome of our most recent communication attempt with this peer.
     pub last_connection_state: PeerAddrState,
+
+    /// The negotiated protocol version from our most recent handshake with
+    /// this peer, if any.
+    ///
+    /// This is Zebra-specific peer metadata: it is not part of the Zcash
+    /// network protocol, and it is never sent to other peers.
+    pub version: Option<Version>,
+
+    /// The user agent string the peer sent us during our most recent
+    /// handshake with it, if any.
+    ///
+    /// This is Zebra-specific peer metadata: it is not part of the Zcash
+    /// network protocol, and it is never sent to other peers.
+    pub user_agent: Option<String>,
+
+    /// The best chain tip height the peer reported during our most recent
+    /// handshake with it, if any.
+    ///
+    /// This is Zebra-specific peer metadata: it is not part of the Zcash
+    /// network protocol, and it is never sent to other peers.
+    pub start_height: Option<block::Height>,
+
+    /// Whether the peer asked us to relay transactions to it, during our
+    /// most recent handshake with it, if any.
+    ///
+    /// This is Zebra-specific peer metadata: it is not part of the Zcash
+    /// network protocol, and it is never sent to other peers.
+    pub relay: Option<bool>,
+
+    /// The number of consecutive heartbeats the peer failed to respond to,
+    /// before our most recent interaction with it.
+    ///
+    /// This is Zebra-specific peer metadata: it is not part of the Zcash
+    /// network protocol, and it is never sent to other peers. It's intended
+    /// to make flaky links visible in diagnostics, rather than to drive any
+    /// behaviour.
+    pub missed_heartbeats: u32,
 }
 
 impl MetaAddr {
@@ -137,6 +183,11 @@ impl MetaAddr {
             last_seen: *last_seen,
             // the state is Zebra-specific, it isn't part of the Zcash network protocol
             last_connection_state: NeverAttempted,
+            version: None,
+            user_agent: None,
+            start_height: None,
+            relay: None,
+            missed_heartbeats: 0,
         }
     }
 
@@ -147,6 +198,11 @@ impl MetaAddr {
             services: *services,
             last_seen: Utc::now(),
             last_connection_state: Responded,
+            version: None,
+            user_agent: None,
+            start_height: None,
+            relay: None,
+            missed_heartbeats: 0,
         }
     }
 
@@ -157,6 +213,11 @@ impl MetaAddr {
             services: *services,
             last_seen: Utc::now(),
             last_connection_state: AttemptPending,
+            version: None,
+            user_agent: None,
+            start_height: None,
+            relay: None,
+            missed_heartbeats: 0,
         }
     }
 
@@ -167,6 +228,11 @@ impl MetaAddr {
             services: *services,
             last_seen: Utc::now(),
             last_connection_state: Failed,
+            version: None,
+            user_agent: None,
+            start_height: None,
+            relay: None,
+            missed_heartbeats: 0,
         }
     }
 
@@ -177,6 +243,34 @@ impl MetaAddr {
         MetaAddr::new_errored(addr, services)
     }
 
+    /// Attach the connection metadata we learned during our handshake with
+    /// this peer.
+    ///
+    /// This lets [`AddressBook`](crate::AddressBook) retain handshake
+    /// metadata for `Responded` and `Failed` peers, without threading it
+    /// through every constructor above, most of which are also used outside
+    /// the handshake.
+    pub fn with_connection_info(
+        mut self,
+        version: Version,
+        user_agent: String,
+        start_height: block::Height,
+        relay: bool,
+    ) -> MetaAddr {
+        self.version = Some(version);
+        self.user_agent = Some(user_agent);
+        self.start_height = Some(start_height);
+        self.relay = Some(relay);
+        self
+    }
+
+    /// Attach the number of consecutive heartbeats the peer failed to
+    /// respond to, before our most recent interaction with it.
+    pub fn with_missed_heartbeats(mut self, missed_heartbeats: u32) -> MetaAddr {
+        self.missed_heartbeats = missed_heartbeats;
+        self
+    }
+
     /// The last time we interacted with this peer.
     ///
     /// The exact meaning depends on `last_connection_state`:
@@ -208,6 +302,12 @@ impl MetaAddr {
             last_seen,
             // the state isn't sent to the remote peer, but sanitize it anyway
             last_connection_state: Default::default(),
+            // handshake metadata is Zebra-specific, and is never sent to remote peers
+            version: None,
+            user_agent: None,
+            start_height: None,
+            relay: None,
+            missed_heartbeats: 0,
         }
     }
 }
@@ -248,6 +348,11 @@ impl Ord for MetaAddr {
             .then(ip_numeric)
             .then(self.addr.port().cmp(&other.addr.port()))
             .then(self.services.bits().cmp(&other.services.bits()))
+            .then(self.version.cmp(&other.version))
+            .then(self.user_agent.cmp(&other.user_agent))
+            .then(self.start_height.cmp(&other.start_height))
+            .then(self.relay.cmp(&other.relay))
+            .then(self.missed_heartbeats.cmp(&other.missed_heartbeats))
     }
 }
 
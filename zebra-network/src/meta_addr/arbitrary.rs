@@ -22,6 +22,13 @@ impl Arbitrary for MetaAddr {
                     // last_connection_state refactor in #1849
                     last_seen: last_seen.into(),
                     last_connection_state,
+                    // handshake metadata isn't covered by the wire-format
+                    // round-trip tests, so we don't need to generate it here
+                    version: None,
+                    user_agent: None,
+                    start_height: None,
+                    relay: None,
+                    missed_heartbeats: 0,
                 },
             )
             .boxed()
@@ -14,6 +14,11 @@ fn sanitize_extremes() {
         services: Default::default(),
         last_seen: MIN_DATETIME,
         last_connection_state: Default::default(),
+        version: None,
+        user_agent: None,
+        start_height: None,
+        relay: None,
+        missed_heartbeats: 0,
     };
 
     let max_time_entry = MetaAddr {
@@ -21,6 +26,11 @@ fn sanitize_extremes() {
         services: Default::default(),
         last_seen: MAX_DATETIME,
         last_connection_state: Default::default(),
+        version: None,
+        user_agent: None,
+        start_height: None,
+        relay: None,
+        missed_heartbeats: 0,
     };
 
     check::sanitize_avoids_leaks(&min_time_entry);
@@ -10,6 +10,10 @@ mod connector;
 mod error;
 /// Performs peer handshakes.
 mod handshake;
+/// A minimal SOCKS5 client, used to dial outbound connections through Tor.
+pub(crate) mod socks;
+/// Global and per-peer bandwidth throttling.
+pub mod throttle;
 
 use client::ClientRequest;
 use client::ClientRequestReceiver;
@@ -22,3 +26,4 @@ pub use connection::Connection;
 pub use connector::Connector;
 pub use error::{HandshakeError, PeerError, SharedPeerError};
 pub use handshake::Handshake;
+pub use throttle::BandwidthLimit;
@@ -7,7 +7,11 @@
 //! And it's unclear if these assumptions match the `zcashd` implementation.
 //! It should be refactored into a cleaner set of request/response pairs (#1515).
 
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::{
     future::{self, Either},
@@ -336,6 +340,57 @@ pub struct Connection<S, Tx> {
     pub(super) error_slot: ErrorSlot,
     //pub(super) peer_rx: Rx,
     pub(super) peer_tx: Tx,
+    /// The number of inbound requests received from this peer in the current
+    /// quota interval, and when that interval started. Used to enforce
+    /// [`constants::INBOUND_REQUEST_QUOTA`].
+    pub(super) inbound_request_quota: InboundRequestQuota,
+    /// The timeout for requests made to or by this peer, taken from
+    /// [`Config::request_timeout`](crate::Config::request_timeout).
+    pub(super) request_timeout: Duration,
+    /// A bounded queue of outbound gossip (`addr`/`inv` advertisement)
+    /// messages, which are lower-priority than request/response traffic.
+    ///
+    /// Gossip is queued here instead of being written to `peer_tx`
+    /// immediately, so that a slow peer can't make an unsolicited
+    /// advertisement block the handling of the next client request. If the
+    /// queue is full, the oldest queued gossip is dropped in favour of the
+    /// newest, following [`constants::GOSSIP_QUEUE_SIZE`].
+    pub(super) gossip_queue: VecDeque<Message>,
+    /// True if this peer is in [`Config::whitelisted_peers`](crate::Config::whitelisted_peers).
+    ///
+    /// Whitelisted peers are exempt from [`constants::INBOUND_REQUEST_QUOTA`]
+    /// and from gossip queue drops.
+    pub(super) is_whitelisted: bool,
+}
+
+/// Tracks how many inbound requests a peer has made in the current interval,
+/// as a basic defence against request-flooding peers.
+pub(super) struct InboundRequestQuota {
+    count: usize,
+    window_start: Instant,
+}
+
+impl Default for InboundRequestQuota {
+    fn default() -> Self {
+        InboundRequestQuota {
+            count: 0,
+            window_start: Instant::now(),
+        }
+    }
+}
+
+impl InboundRequestQuota {
+    /// Records one more inbound request, returning `true` if the peer is
+    /// still within its quota, or `false` if it has exceeded it.
+    fn record_request(&mut self) -> bool {
+        if self.window_start.elapsed() >= constants::INBOUND_REQUEST_QUOTA_INTERVAL {
+            self.count = 0;
+            self.window_start = Instant::now();
+        }
+
+        self.count += 1;
+        self.count <= constants::INBOUND_REQUEST_QUOTA
+    }
 }
 
 impl<S, Tx> Connection<S, Tx>
@@ -372,6 +427,14 @@ where
             match self.state {
                 State::AwaitingRequest => {
                     trace!("awaiting client request or peer message");
+
+                    // We're idle, so this is a good time to flush any gossip
+                    // that built up while we were handling the last request.
+                    self.drain_gossip_queue().await;
+                    if let State::Failed = self.state {
+                        continue;
+                    }
+
                     // CORRECTNESS
                     //
                     // Currently, select prefers the first future if multiple
@@ -531,6 +594,33 @@ where
         }
     }
 
+    /// Queues `gossip` for sending to the peer, dropping the oldest queued
+    /// gossip message if [`constants::GOSSIP_QUEUE_SIZE`] is exceeded.
+    ///
+    /// Gossip is sent on a best-effort basis, so it's better to drop stale
+    /// gossip than to let it build up and delay the next client request.
+    fn queue_gossip(&mut self, gossip: Message) {
+        if !self.is_whitelisted && self.gossip_queue.len() >= constants::GOSSIP_QUEUE_SIZE {
+            let dropped = self.gossip_queue.pop_front();
+            trace!(?dropped, "dropping stale gossip, queue is full");
+        }
+        self.gossip_queue.push_back(gossip);
+    }
+
+    /// Sends any queued gossip to the peer.
+    ///
+    /// Called whenever the connection becomes idle, so that gossip never
+    /// delays the handling of a client request or peer message, but is still
+    /// sent promptly once there's nothing higher-priority to do.
+    async fn drain_gossip_queue(&mut self) {
+        while let Some(gossip) = self.gossip_queue.pop_front() {
+            if let Err(e) = self.peer_tx.send(gossip).await {
+                self.fail_with(e);
+                return;
+            }
+        }
+    }
+
     /// Marks the peer as having failed with error `e`.
     fn fail_with<E>(&mut self, e: E)
     where
@@ -731,20 +821,12 @@ where
                 }
             }
             (AwaitingRequest, AdvertiseTransactions(hashes)) => {
-                match self
-                    .peer_tx
-                    .send(Message::Inv(hashes.iter().map(|h| (*h).into()).collect()))
-                    .await
-                {
-                    Ok(()) => Ok((AwaitingRequest, Some(tx))),
-                    Err(e) => Err((e, tx)),
-                }
+                self.queue_gossip(Message::Inv(hashes.iter().map(|h| (*h).into()).collect()));
+                Ok((AwaitingRequest, Some(tx)))
             }
             (AwaitingRequest, AdvertiseBlock(hash)) => {
-                match self.peer_tx.send(Message::Inv(vec![hash.into()])).await {
-                    Ok(()) => Ok((AwaitingRequest, Some(tx))),
-                    Err(e) => Err((e, tx)),
-                }
+                self.queue_gossip(Message::Inv(vec![hash.into()]));
+                Ok((AwaitingRequest, Some(tx)))
             }
         };
         // Updates state or fails. Sends the error on the Sender if it is Some.
@@ -754,11 +836,11 @@ where
                 // send a response before dropping tx.
                 let _ = tx.send(Ok(Response::Nil));
                 self.state = AwaitingRequest;
-                self.request_timer = Some(sleep(constants::REQUEST_TIMEOUT));
+                self.request_timer = Some(sleep(self.request_timeout));
             }
             Ok((new_state @ AwaitingResponse { .. }, None)) => {
                 self.state = new_state;
-                self.request_timer = Some(sleep(constants::REQUEST_TIMEOUT));
+                self.request_timer = Some(sleep(self.request_timeout));
             }
             Err((e, tx)) => {
                 let e = SharedPeerError::from(e);
@@ -894,6 +976,11 @@ where
         trace!(?req);
         use tower::{load_shed::error::Overloaded, ServiceExt};
 
+        if !self.is_whitelisted && !self.inbound_request_quota.record_request() {
+            self.fail_with(PeerError::RequestQuotaExceeded);
+            return;
+        }
+
         if self.svc.ready_and().await.is_err() {
             // Treat all service readiness errors as Overloaded
             // TODO: treat `TryRecvError::Closed` in `Inbound::poll_ready` as a fatal error (#1655)
@@ -947,7 +1034,12 @@ where
                     }
                 }
             }
-            Response::BlockHashes(hashes) => {
+            Response::BlockHashes(mut hashes) => {
+                // Defense in depth: the state already caps `FindBlockHashes`
+                // responses, but make sure a `getblocks` reply can never
+                // exceed the wire limit, even if that changes.
+                hashes.truncate(crate::constants::GETBLOCKS_INV_LIMIT);
+
                 if let Err(e) = self
                     .peer_tx
                     .send(Message::Inv(hashes.into_iter().map(Into::into).collect()))
@@ -956,7 +1048,12 @@ where
                     self.fail_with(e)
                 }
             }
-            Response::BlockHeaders(headers) => {
+            Response::BlockHeaders(mut headers) => {
+                // Defense in depth: never send more headers than a
+                // `getheaders` response is allowed to contain, even if the
+                // state's limit changes.
+                headers.truncate(crate::constants::GETHEADERS_LIMIT);
+
                 if let Err(e) = self.peer_tx.send(Message::Headers(headers)).await {
                     self.fail_with(e)
                 }
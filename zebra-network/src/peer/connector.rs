@@ -6,31 +6,66 @@ use std::{
 };
 
 use futures::prelude::*;
+use socket2::{Domain, Socket, Type};
 use tokio::net::TcpStream;
 use tower::{discover::Change, Service, ServiceExt};
 
 use crate::{BoxError, Request, Response};
 
-use super::{Client, Handshake};
+use super::{socks, Client, Handshake};
 
 /// A wrapper around [`peer::Handshake`] that opens a TCP connection before
 /// forwarding to the inner handshake service. Writing this as its own
 /// [`tower::Service`] lets us apply unified timeout policies, etc.
 pub struct Connector<S> {
     handshaker: Handshake<S>,
+
+    /// If set, every connection is dialed through this SOCKS5 proxy address,
+    /// rather than directly. See [`Config::tor_proxy`](crate::Config::tor_proxy).
+    tor_proxy: Option<SocketAddr>,
+
+    /// If set, outbound connections bind to this local address.
+    /// See [`Config::outbound_bind_addr`](crate::Config::outbound_bind_addr).
+    outbound_bind_addr: Option<SocketAddr>,
 }
 
 impl<S: Clone> Clone for Connector<S> {
     fn clone(&self) -> Self {
         Connector {
             handshaker: self.handshaker.clone(),
+            tor_proxy: self.tor_proxy,
+            outbound_bind_addr: self.outbound_bind_addr,
         }
     }
 }
 
 impl<S> Connector<S> {
     pub fn new(handshaker: Handshake<S>) -> Self {
-        Connector { handshaker }
+        Connector {
+            handshaker,
+            tor_proxy: None,
+            outbound_bind_addr: None,
+        }
+    }
+
+    /// Returns a [`Connector`] that dials every outbound connection through
+    /// `tor_proxy`, instead of connecting to peers directly.
+    pub fn with_tor_proxy(handshaker: Handshake<S>, tor_proxy: SocketAddr) -> Self {
+        Connector {
+            handshaker,
+            tor_proxy: Some(tor_proxy),
+            outbound_bind_addr: None,
+        }
+    }
+
+    /// Binds every outbound connection's local socket to `outbound_bind_addr`,
+    /// instead of letting the operating system choose one.
+    ///
+    /// Has no effect on connections dialed through [`Connector::with_tor_proxy`],
+    /// since those connections are bound by the proxy, not by Zebra.
+    pub fn with_outbound_bind_addr(mut self, outbound_bind_addr: SocketAddr) -> Self {
+        self.outbound_bind_addr = Some(outbound_bind_addr);
+        self
     }
 }
 
@@ -50,8 +85,13 @@ where
 
     fn call(&mut self, addr: SocketAddr) -> Self::Future {
         let mut hs = self.handshaker.clone();
+        let tor_proxy = self.tor_proxy;
+        let outbound_bind_addr = self.outbound_bind_addr;
         async move {
-            let stream = TcpStream::connect(addr).await?;
+            let stream = match tor_proxy {
+                Some(proxy) => socks::connect(proxy, addr).await?,
+                None => connect(addr, outbound_bind_addr).await?,
+            };
             hs.ready_and().await?;
             let client = hs.call((stream, addr)).await?;
             Ok(Change::Insert(addr, client))
@@ -59,3 +99,39 @@ where
         .boxed()
     }
 }
+
+/// Opens a TCP connection to `addr`, optionally binding the local socket to
+/// `bind_addr` first.
+///
+/// Binding lets multi-homed servers, or operators routing peer-to-peer
+/// traffic over a specific interface or VPN, control which local address is
+/// used for outbound connections.
+async fn connect(addr: SocketAddr, bind_addr: Option<SocketAddr>) -> Result<TcpStream, BoxError> {
+    let bind_addr = match bind_addr {
+        Some(bind_addr) => bind_addr,
+        None => return Ok(TcpStream::connect(addr).await?),
+    };
+
+    let domain = if addr.is_ipv6() {
+        Domain::ipv6()
+    } else {
+        Domain::ipv4()
+    };
+    let socket = Socket::new(domain, Type::stream(), None)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&bind_addr.into())?;
+
+    // A non-blocking connect typically returns `EINPROGRESS`, which `socket2`
+    // surfaces as `WouldBlock`. We still need to wait for the connection to
+    // become writable before we can use it.
+    match socket.connect(&addr.into()) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let stream = TcpStream::from_std(socket.into_tcp_stream())?;
+    stream.writable().await?;
+
+    Ok(stream)
+}
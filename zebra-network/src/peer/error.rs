@@ -62,6 +62,10 @@ pub enum PeerError {
     /// We requested data that the peer couldn't find.
     #[error("Remote peer could not find items: {0:?}")]
     NotFound(Vec<InventoryHash>),
+    /// A remote peer sent us more requests than our per-peer quota allows,
+    /// within a single quota interval.
+    #[error("Remote peer exceeded its inbound request quota")]
+    RequestQuotaExceeded,
 }
 
 #[derive(Default, Clone)]
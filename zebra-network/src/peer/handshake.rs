@@ -30,7 +30,7 @@ use crate::{
     BoxError, Config,
 };
 
-use super::{Client, Connection, ErrorSlot, HandshakeError, PeerError};
+use super::{throttle::BandwidthLimit, Client, Connection, ErrorSlot, HandshakeError, PeerError};
 
 /// A [`Service`] that handshakes with a remote peer and constructs a
 /// client/server pair.
@@ -51,6 +51,9 @@ pub struct Handshake<S> {
     our_services: PeerServices,
     relay: bool,
     parent_span: Span,
+    /// Shared across every connection spawned by this `Handshake`, so it
+    /// enforces `Config::max_total_bandwidth_per_second`.
+    global_bandwidth_limit: Option<BandwidthLimit>,
 }
 
 pub struct Builder<S> {
@@ -143,6 +146,7 @@ where
         let user_agent = self.user_agent.unwrap_or_else(|| "".to_string());
         let our_services = self.our_services.unwrap_or_else(PeerServices::empty);
         let relay = self.relay.unwrap_or(false);
+        let global_bandwidth_limit = config.max_total_bandwidth_per_second.map(BandwidthLimit::new);
 
         Ok(Handshake {
             config,
@@ -154,6 +158,7 @@ where
             our_services,
             relay,
             parent_span: Span::current(),
+            global_bandwidth_limit,
         })
     }
 }
@@ -209,10 +214,20 @@ where
         let timestamp_collector = self.timestamp_collector.clone();
         let inv_collector = self.inv_collector.clone();
         let network = self.config.network;
-        let our_addr = self.config.listen_addr;
+        let network_magic_override = self.config.network_magic_override;
+        // We advertise the first listener address; see `Config::listen_addrs`.
+        let our_addr = self.config.listen_addrs[0];
         let user_agent = self.user_agent.clone();
         let our_services = self.our_services;
         let relay = self.relay;
+        let global_bandwidth_limit = self.global_bandwidth_limit.clone();
+        let max_per_peer_bandwidth_per_second = self.config.max_per_peer_bandwidth_per_second;
+        let request_timeout = self.config.request_timeout;
+        let heartbeat_interval = self.config.heartbeat_interval;
+        let min_peer_protocol_version = self.config.min_peer_protocol_version;
+        // Whitelisted peers are exempt from bandwidth limits, inbound request
+        // quotas, and gossip queue drops. See `Config::whitelisted_peers`.
+        let is_whitelisted = self.config.is_peer_whitelisted(&addr);
 
         let fut = async move {
             debug!("connecting to remote peer");
@@ -221,10 +236,22 @@ where
             //
             // As a defence-in-depth against hangs, every send or next on stream
             // should be wrapped in a timeout.
+            let (global_bandwidth_limit, max_per_peer_bandwidth_per_second) = if is_whitelisted {
+                (None, None)
+            } else {
+                (global_bandwidth_limit, max_per_peer_bandwidth_per_second)
+            };
+            let per_peer_bandwidth_limit = max_per_peer_bandwidth_per_second.map(BandwidthLimit::new);
+            let throttled_stream =
+                super::throttle::Throttle::new(tcp_stream, global_bandwidth_limit, per_peer_bandwidth_limit);
+
+            let mut codec_builder = Codec::builder().for_network(network);
+            if let Some(magic) = network_magic_override {
+                codec_builder = codec_builder.for_magic(magic);
+            }
             let mut stream = Framed::new(
-                tcp_stream,
-                Codec::builder()
-                    .for_network(network)
+                throttled_stream,
+                codec_builder
                     .with_metrics_label(addr.ip().to_string())
                     .finish(),
             );
@@ -270,25 +297,29 @@ where
             };
 
             debug!(?version, "sending initial version message");
-            timeout(constants::REQUEST_TIMEOUT, stream.send(version)).await??;
+            timeout(request_timeout, stream.send(version)).await??;
 
-            let remote_msg = timeout(constants::REQUEST_TIMEOUT, stream.next())
+            let remote_msg = timeout(request_timeout, stream.next())
                 .await?
                 .ok_or(HandshakeError::ConnectionClosed)??;
 
             // Check that we got a Version and destructure its fields into the local scope.
             debug!(?remote_msg, "got message from remote peer");
-            let (remote_nonce, remote_services, remote_version) = if let Message::Version {
-                nonce,
-                services,
-                version,
-                ..
-            } = remote_msg
-            {
-                (nonce, services, version)
-            } else {
-                return Err(HandshakeError::UnexpectedMessage(Box::new(remote_msg)));
-            };
+            let (remote_nonce, remote_services, remote_version, remote_user_agent, remote_start_height, remote_relay) =
+                if let Message::Version {
+                    nonce,
+                    services,
+                    version,
+                    user_agent,
+                    start_height,
+                    relay,
+                    ..
+                } = remote_msg
+                {
+                    (nonce, services, version, user_agent, start_height, relay)
+                } else {
+                    return Err(HandshakeError::UnexpectedMessage(Box::new(remote_msg)));
+                };
 
             // Check for nonce reuse, indicating self-connection.
             let nonce_reuse = {
@@ -302,9 +333,9 @@ where
                 return Err(HandshakeError::NonceReuse);
             }
 
-            timeout(constants::REQUEST_TIMEOUT, stream.send(Message::Verack)).await??;
+            timeout(request_timeout, stream.send(Message::Verack)).await??;
 
-            let remote_msg = timeout(constants::REQUEST_TIMEOUT, stream.next())
+            let remote_msg = timeout(request_timeout, stream.next())
                 .await?
                 .ok_or(HandshakeError::ConnectionClosed)??;
             if let Message::Verack = remote_msg {
@@ -340,6 +371,14 @@ where
                 return Err(HandshakeError::ObsoleteVersion(remote_version));
             }
 
+            // Disconnect if the peer is older than our configured minimum, even if
+            // it's new enough for the current network upgrade.
+            let min_peer_protocol_version = min_peer_protocol_version
+                .unwrap_or_else(|| Version::min_for_upgrade(network, constants::MIN_NETWORK_UPGRADE));
+            if remote_version < min_peer_protocol_version {
+                return Err(HandshakeError::ObsoleteVersion(remote_version));
+            }
+
             // Set the connection's version to the minimum of the received version or our own.
             let negotiated_version = std::cmp::min(remote_version, constants::CURRENT_VERSION);
 
@@ -389,11 +428,13 @@ where
             // Every message and error must update the peer address state via
             // the inbound_ts_collector.
             let inbound_ts_collector = timestamp_collector.clone();
+            let rx_user_agent = remote_user_agent.clone();
             let peer_rx = peer_rx
                 .then(move |msg| {
                     // Add a metric for inbound messages and errors.
                     // Fire a timestamp or failure event.
                     let mut inbound_ts_collector = inbound_ts_collector.clone();
+                    let rx_user_agent = rx_user_agent.clone();
                     async move {
                         match &msg {
                             Ok(msg) => {
@@ -406,7 +447,15 @@ where
                                 // the collector doesn't depend on network activity,
                                 // so this await should not hang
                                 let _ = inbound_ts_collector
-                                    .send(MetaAddr::new_responded(&addr, &remote_services))
+                                    .send(
+                                        MetaAddr::new_responded(&addr, &remote_services)
+                                            .with_connection_info(
+                                                negotiated_version,
+                                                rx_user_agent.clone(),
+                                                remote_start_height,
+                                                remote_relay,
+                                            ),
+                                    )
                                     .await;
                             }
                             Err(err) => {
@@ -417,7 +466,15 @@ where
                                     "addr" => addr.to_string(),
                                 );
                                 let _ = inbound_ts_collector
-                                    .send(MetaAddr::new_errored(&addr, &remote_services))
+                                    .send(
+                                        MetaAddr::new_errored(&addr, &remote_services)
+                                            .with_connection_info(
+                                                negotiated_version,
+                                                rx_user_agent.clone(),
+                                                remote_start_height,
+                                                remote_relay,
+                                            ),
+                                    )
                                     .await;
                             }
                         }
@@ -471,6 +528,12 @@ where
                 error_slot: slot,
                 peer_tx,
                 request_timer: None,
+                inbound_request_quota: connection::InboundRequestQuota::default(),
+                request_timeout,
+                gossip_queue: std::collections::VecDeque::with_capacity(
+                    constants::GOSSIP_QUEUE_SIZE,
+                ),
+                is_whitelisted,
             };
 
             tokio::spawn(
@@ -499,11 +562,21 @@ where
                     let mut shutdown_rx = shutdown_rx;
                     let mut server_tx = server_tx;
                     let mut timestamp_collector = timestamp_collector.clone();
-                    let mut interval_stream = tokio::time::interval(constants::HEARTBEAT_INTERVAL);
+                    let heartbeat_user_agent = remote_user_agent.clone();
+                    // `None` disables heartbeats: the task only waits for shutdown.
+                    let mut interval_stream = heartbeat_interval.map(tokio::time::interval);
+                    let mut missed_heartbeats: u32 = 0;
                     loop {
                         let shutdown_rx_ref = Pin::new(&mut shutdown_rx);
                         let mut send_addr_err = false;
 
+                        let next_heartbeat: Pin<
+                            Box<dyn Future<Output = Option<tokio::time::Instant>> + Send>,
+                        > = match interval_stream.as_mut() {
+                            Some(interval_stream) => Box::pin(interval_stream.next()),
+                            None => Box::pin(future::pending()),
+                        };
+
                         // CORRECTNESS
                         //
                         // Currently, select prefers the first future if multiple
@@ -513,8 +586,10 @@ where
                         // slow rate, and shutdown is a oneshot. If both futures
                         // are ready, we want the shutdown to take priority over
                         // sending a useless heartbeat.
-                        match future::select(shutdown_rx_ref, interval_stream.next()).await {
+                        match future::select(shutdown_rx_ref, next_heartbeat).await {
                             Either::Right(_) => {
+                                let heartbeat_interval = heartbeat_interval
+                                    .expect("heartbeat_interval must be set when interval_stream fires");
                                 let (tx, rx) = oneshot::channel();
                                 let request = Request::Ping(Nonce::default());
                                 tracing::trace!(?request, "queueing heartbeat request");
@@ -525,25 +600,21 @@ where
                                 }) {
                                     Ok(()) => {
                                         // TODO: also wait on the shutdown_rx here
-                                        match timeout(
-                                            constants::HEARTBEAT_INTERVAL,
-                                            server_tx.flush(),
-                                        )
-                                        .await
+                                        match timeout(heartbeat_interval, server_tx.flush()).await
                                         {
                                             Ok(Ok(())) => {
                                             }
                                             Ok(Err(e)) => {
                                                 tracing::warn!(
                                                     ?e,
-                                                    "flushing client request failed, shutting down"
+                                                    "flushing client request failed"
                                                 );
                                                 send_addr_err = true;
                                             }
                                             Err(e) => {
                                                 tracing::warn!(
                                                     ?e,
-                                                    "flushing client request timed out, shutting down"
+                                                    "flushing client request timed out"
                                                 );
                                                 send_addr_err = true;
                                             }
@@ -570,34 +641,56 @@ where
                                         return;
                                     }
                                 }
-                                // Heartbeats are checked internally to the
-                                // connection logic, but we need to wait on the
-                                // response to avoid canceling the request.
-                                //
-                                // TODO: also wait on the shutdown_rx here
-                                match timeout(constants::HEARTBEAT_INTERVAL, rx).await {
-                                    Ok(Ok(_)) => tracing::trace!("got heartbeat response"),
-                                    Ok(Err(e)) => {
-                                        tracing::warn!(
-                                            ?e,
-                                            "error awaiting heartbeat response, shutting down"
-                                        );
-                                        send_addr_err = true;
-                                    }
-                                    Err(e) => {
-                                        tracing::warn!(
-                                            ?e,
-                                            "heartbeat response timed out, shutting down"
-                                        );
-                                        send_addr_err = true;
+                                if !send_addr_err {
+                                    // Heartbeats are checked internally to the
+                                    // connection logic, but we need to wait on the
+                                    // response to avoid canceling the request.
+                                    //
+                                    // TODO: also wait on the shutdown_rx here
+                                    match timeout(heartbeat_interval, rx).await {
+                                        Ok(Ok(_)) => tracing::trace!("got heartbeat response"),
+                                        Ok(Err(e)) => {
+                                            tracing::warn!(
+                                                ?e,
+                                                "error awaiting heartbeat response"
+                                            );
+                                            send_addr_err = true;
+                                        }
+                                        Err(e) => {
+                                            tracing::warn!(?e, "heartbeat response timed out");
+                                            send_addr_err = true;
+                                        }
                                     }
                                 }
+
+                                if send_addr_err {
+                                    missed_heartbeats += 1;
+                                } else {
+                                    missed_heartbeats = 0;
+                                }
+                                if missed_heartbeats < constants::MAX_MISSED_HEARTBEATS {
+                                    send_addr_err = false;
+                                } else if send_addr_err {
+                                    tracing::warn!(
+                                        missed_heartbeats,
+                                        "peer missed too many heartbeats, shutting down"
+                                    );
+                                }
                             }
                             Either::Left(_) => {
                                 tracing::trace!("shutting down due to Client shut down");
                                 // awaiting a local task won't hang
                                 let _ = timestamp_collector
-                                    .send(MetaAddr::new_shutdown(&addr, &remote_services))
+                                    .send(
+                                        MetaAddr::new_shutdown(&addr, &remote_services)
+                                            .with_connection_info(
+                                                negotiated_version,
+                                                heartbeat_user_agent.clone(),
+                                                remote_start_height,
+                                                remote_relay,
+                                            )
+                                            .with_missed_heartbeats(missed_heartbeats),
+                                    )
                                     .await;
                                 return;
                             }
@@ -607,10 +700,16 @@ where
                             // so we can't send an error back on `tx`. So
                             // we just update the address book with a failure.
                             let _ = timestamp_collector
-                                .send(MetaAddr::new_errored(
-                                    &addr,
-                                    &remote_services,
-                                ))
+                                .send(
+                                    MetaAddr::new_errored(&addr, &remote_services)
+                                        .with_connection_info(
+                                            negotiated_version,
+                                            heartbeat_user_agent.clone(),
+                                            remote_start_height,
+                                            remote_relay,
+                                        )
+                                        .with_missed_heartbeats(missed_heartbeats),
+                                )
                                 .await;
                             return;
                         }
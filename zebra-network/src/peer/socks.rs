@@ -0,0 +1,102 @@
+//! A minimal SOCKS5 client, just enough to `CONNECT` through a local Tor
+//! daemon (or any other unauthenticated SOCKS5 proxy).
+//!
+//! This intentionally doesn't support authentication or the `BIND`/`UDP
+//! ASSOCIATE` commands: Zebra only ever needs an outbound TCP stream to a
+//! known [`SocketAddr`].
+
+use std::{io, net::SocketAddr};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// Opens a TCP connection to `target`, tunnelled through the SOCKS5 proxy
+/// listening at `proxy`.
+///
+/// Returns an error if the TCP connection to the proxy fails, the proxy
+/// doesn't speak SOCKS5, or the proxy refuses to relay the connection.
+pub async fn connect(proxy: SocketAddr, target: SocketAddr) -> io::Result<TcpStream> {
+    let mut stream = TcpStream::connect(proxy).await?;
+
+    // Greeting: version 5, one auth method, "no authentication required".
+    stream.write_all(&[0x05, 0x01, 0x00]).await?;
+
+    let mut greeting_reply = [0u8; 2];
+    stream.read_exact(&mut greeting_reply).await?;
+    if greeting_reply[0] != 0x05 || greeting_reply[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "SOCKS5 proxy did not accept a no-authentication handshake",
+        ));
+    }
+
+    // CONNECT request, addressed by raw IP (address type 0x01 for IPv4, 0x04
+    // for IPv6), since Zebra only deals in already-resolved `SocketAddr`s.
+    let mut request = vec![0x05, 0x01, 0x00];
+    match target {
+        SocketAddr::V4(addr) => {
+            request.push(0x01);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+        SocketAddr::V6(addr) => {
+            request.push(0x04);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+    }
+    request.extend_from_slice(&target.port().to_be_bytes());
+    stream.write_all(&request).await?;
+
+    // Reply header: version, reply code, reserved, address type.
+    let mut reply_header = [0u8; 4];
+    stream.read_exact(&mut reply_header).await?;
+    if reply_header[0] != 0x05 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "SOCKS5 proxy sent an unexpected reply version",
+        ));
+    }
+    if reply_header[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("SOCKS5 proxy refused the connection, reply code {}", reply_header[1]),
+        ));
+    }
+
+    // Consume the bound address the proxy reports back, we don't need it.
+    match reply_header[3] {
+        0x01 => {
+            let mut buf = [0u8; 4 + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        0x04 => {
+            let mut buf = [0u8; 16 + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            let mut buf = vec![0u8; len[0] as usize + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("SOCKS5 proxy returned an unknown address type {}", other),
+            ))
+        }
+    }
+
+    Ok(stream)
+}
+
+/// Checks that a SOCKS5 proxy is listening and reachable at `proxy`, without
+/// tunnelling a connection through it.
+///
+/// Used to fail fast at startup when [`Config::tor_only`](crate::Config::tor_only)
+/// is set but the configured proxy isn't actually running.
+pub async fn check_proxy_reachable(proxy: SocketAddr) -> io::Result<()> {
+    TcpStream::connect(proxy).await?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Bandwidth throttling for peer connections.
+//!
+//! [`Throttle`] wraps a connection's [`AsyncRead`]/[`AsyncWrite`] halves with
+//! a simple token-bucket limiter, so operators on constrained links can cap
+//! how much bandwidth Zebra spends on any one peer, or on the network as a
+//! whole.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A shared, refilling pool of bytes that one or more [`Throttle`]s can draw
+/// from.
+///
+/// A single [`BandwidthLimit`] can be shared between every peer connection
+/// (a global limit), or created per-connection (a per-peer limit); Zebra uses
+/// both at once, so a connection is throttled by whichever bucket runs out
+/// first.
+#[derive(Clone)]
+pub struct BandwidthLimit {
+    inner: Arc<Mutex<Bucket>>,
+}
+
+struct Bucket {
+    /// The maximum number of bytes the bucket can hold, and the number it
+    /// refills to every second.
+    bytes_per_second: u64,
+    /// The number of bytes currently available to spend.
+    available: u64,
+    /// The last time the bucket was refilled.
+    refilled_at: Instant,
+}
+
+impl BandwidthLimit {
+    /// Creates a new limit that permits `bytes_per_second` bytes to be spent
+    /// each second, starting full.
+    pub fn new(bytes_per_second: u64) -> Self {
+        BandwidthLimit {
+            inner: Arc::new(Mutex::new(Bucket {
+                bytes_per_second,
+                available: bytes_per_second,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Refills the bucket based on elapsed time, then takes up to
+    /// `requested` bytes from it, returning how many bytes were granted.
+    ///
+    /// Returns 0 if the bucket is currently empty; the caller should treat
+    /// that as "not ready yet" and retry later.
+    fn take(&self, requested: usize) -> usize {
+        let mut bucket = self.inner.lock().expect("mutex should be unpoisoned");
+
+        let elapsed = bucket.refilled_at.elapsed();
+        if elapsed >= Duration::from_millis(1) {
+            let refill = (bucket.bytes_per_second as f64 * elapsed.as_secs_f64()) as u64;
+            bucket.available = (bucket.available + refill).min(bucket.bytes_per_second);
+            bucket.refilled_at = Instant::now();
+        }
+
+        let granted = (requested as u64).min(bucket.available);
+        bucket.available -= granted;
+        granted as usize
+    }
+}
+
+/// Wraps an inner connection with global and per-peer [`BandwidthLimit`]s,
+/// and accounts the bytes it moves via the `peer.bytes.sent` and
+/// `peer.bytes.received` metrics.
+pub struct Throttle<T> {
+    inner: T,
+    global: Option<BandwidthLimit>,
+    per_peer: Option<BandwidthLimit>,
+}
+
+impl<T> Throttle<T> {
+    /// Wraps `inner`, throttled by whichever of `global` and `per_peer` are
+    /// configured. Either or both may be omitted to disable that limit.
+    pub fn new(inner: T, global: Option<BandwidthLimit>, per_peer: Option<BandwidthLimit>) -> Self {
+        Throttle {
+            inner,
+            global,
+            per_peer,
+        }
+    }
+
+    /// Returns the maximum number of bytes that can be moved right now,
+    /// respecting both the global and per-peer limits, or `None` if neither
+    /// limit is configured.
+    fn allowance(&self, requested: usize) -> Option<usize> {
+        if self.global.is_none() && self.per_peer.is_none() {
+            return None;
+        }
+
+        let mut allowed = requested;
+        if let Some(limit) = &self.global {
+            allowed = allowed.min(limit.take(allowed));
+        }
+        if let Some(limit) = &self.per_peer {
+            allowed = allowed.min(limit.take(allowed));
+        }
+        Some(allowed)
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Throttle<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        let allowance = match this.allowance(buf.remaining()) {
+            None => buf.remaining(),
+            Some(0) => {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Some(allowed) => allowed,
+        };
+
+        let mut limited = buf.take(allowance);
+        let before = limited.filled().len();
+        futures::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut limited))?;
+        let read = limited.filled().len() - before;
+
+        metrics::counter!("peer.bytes.received", read as u64);
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Throttle<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        let allowance = match this.allowance(data.len()) {
+            None => data.len(),
+            Some(0) => {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Some(allowed) => allowed,
+        };
+
+        let written = futures::ready!(Pin::new(&mut this.inner).poll_write(cx, &data[..allowance]))?;
+        metrics::counter!("peer.bytes.sent", written as u64);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
@@ -1,6 +1,7 @@
 mod candidate_set;
 mod initialize;
 mod inventory_registry;
+mod peer_event;
 mod set;
 mod unready_service;
 
@@ -9,3 +10,4 @@ use inventory_registry::InventoryRegistry;
 use set::PeerSet;
 
 pub use initialize::init;
+pub use peer_event::PeerEvent;
@@ -1,5 +1,7 @@
 use std::{
+    collections::HashSet,
     mem,
+    net::SocketAddr,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -8,7 +10,7 @@ use futures::stream::{FuturesUnordered, StreamExt};
 use tokio::time::{sleep, sleep_until, timeout, Sleep};
 use tower::{Service, ServiceExt};
 
-use crate::{constants, types::MetaAddr, AddressBook, BoxError, Request, Response};
+use crate::{constants, types::MetaAddr, AddressBook, BoxError, PeerAddrState, Request, Response};
 
 /// The `CandidateSet` manages the `PeerSet`'s peer reconnection attempts.
 ///
@@ -108,6 +110,13 @@ pub(super) struct CandidateSet<S> {
     pub(super) peer_set: Arc<Mutex<AddressBook>>,
     pub(super) peer_service: S,
     next_peer_min_wait: Sleep,
+    /// Peers configured as trusted or priority peers, which are always
+    /// preferred over other reconnection candidates. See
+    /// [`Config::priority_peers`](crate::Config::priority_peers).
+    priority_peers: HashSet<SocketAddr>,
+    /// The minimum time between successive calls to [`CandidateSet::next`].
+    /// See [`Config::min_peer_connection_interval`](crate::Config::min_peer_connection_interval).
+    min_peer_connection_interval: Duration,
 }
 
 impl<S> CandidateSet<S>
@@ -115,20 +124,32 @@ where
     S: Service<Request, Response = Response, Error = BoxError>,
     S::Future: Send + 'static,
 {
-    /// The minimum time between successive calls to `CandidateSet::next()`.
-    ///
-    /// ## Security
-    ///
-    /// Zebra resists distributed denial of service attacks by making sure that new peer connections
-    /// are initiated at least `MIN_PEER_CONNECTION_INTERVAL` apart.
-    const MIN_PEER_CONNECTION_INTERVAL: Duration = Duration::from_millis(100);
-
-    /// Uses `peer_set` and `peer_service` to manage a [`CandidateSet`] of peers.
+    /// Uses `peer_set` and `peer_service` to manage a [`CandidateSet`] of peers,
+    /// using the default [`Config::min_peer_connection_interval`](crate::Config::min_peer_connection_interval).
     pub fn new(peer_set: Arc<Mutex<AddressBook>>, peer_service: S) -> CandidateSet<S> {
+        CandidateSet::with_priority_peers(
+            peer_set,
+            peer_service,
+            HashSet::new(),
+            crate::constants::MIN_PEER_CONNECTION_INTERVAL,
+        )
+    }
+
+    /// As [`CandidateSet::new`], but always prefers `priority_peers` over
+    /// other reconnection candidates, and rate-limits new connections to
+    /// `min_peer_connection_interval`.
+    pub fn with_priority_peers(
+        peer_set: Arc<Mutex<AddressBook>>,
+        peer_service: S,
+        priority_peers: HashSet<SocketAddr>,
+        min_peer_connection_interval: Duration,
+    ) -> CandidateSet<S> {
         CandidateSet {
             peer_set,
             peer_service,
             next_peer_min_wait: sleep(Duration::from_secs(0)),
+            priority_peers,
+            min_peer_connection_interval,
         }
     }
 
@@ -239,7 +260,7 @@ where
     /// `MIN_PEER_CONNECTION_INTERVAL` apart.
     pub async fn next(&mut self) -> Option<MetaAddr> {
         let current_deadline = self.next_peer_min_wait.deadline();
-        let mut sleep = sleep_until(current_deadline + Self::MIN_PEER_CONNECTION_INTERVAL);
+        let mut sleep = sleep_until(current_deadline + self.min_peer_connection_interval);
         mem::swap(&mut self.next_peer_min_wait, &mut sleep);
 
         // CORRECTNESS
@@ -254,9 +275,15 @@ where
         // be kept to a minimum.
         let reconnect = {
             let mut peer_set_guard = self.peer_set.lock().unwrap();
+            // Prefer a configured priority peer over the usual reconnection
+            // order, if one is currently eligible for a connection attempt.
+            let reconnect = peer_set_guard
+                .reconnection_peers()
+                .find(|candidate| self.priority_peers.contains(&candidate.addr))
+                .or_else(|| peer_set_guard.reconnection_peers().next());
             // It's okay to early return here because we're returning None
             // instead of yielding the next connection.
-            let reconnect = peer_set_guard.reconnection_peers().next()?;
+            let reconnect = reconnect?;
 
             let reconnect = MetaAddr::new_reconnect(&reconnect.addr, &reconnect.services);
             peer_set_guard.update(reconnect);
@@ -269,6 +296,39 @@ where
         Some(reconnect)
     }
 
+    /// Returns a random `NeverAttempted` candidate for a feeler connection,
+    /// if any are available.
+    ///
+    /// Feeler connections briefly dial a candidate that we've never
+    /// connected to before, to confirm that it's actually reachable, without
+    /// adding it to the peer set. This lets us validate addresses from the
+    /// `NeverAttempted` pool.
+    ///
+    /// Unlike [`CandidateSet::next`], feeler candidates aren't rate-limited
+    /// by `min_peer_connection_interval`, and don't consume the usual
+    /// reconnection order: feeler connections don't occupy a long-lived
+    /// outbound slot, so they don't need the same DoS protection.
+    ///
+    /// ## Correctness
+    ///
+    /// As in [`CandidateSet::next`], this briefly holds the address book
+    /// lock, but does not await any futures, so it can't hang.
+    pub fn feeler_candidate(&mut self) -> Option<MetaAddr> {
+        use rand::seq::SliceRandom;
+
+        let mut peer_set_guard = self.peer_set.lock().unwrap();
+        let candidate = peer_set_guard
+            .state_peers(PeerAddrState::NeverAttempted)
+            .collect::<Vec<_>>()
+            .choose(&mut rand::thread_rng())
+            .cloned()?;
+
+        let pending = MetaAddr::new_reconnect(&candidate.addr, &candidate.services);
+        peer_set_guard.update(pending);
+
+        Some(candidate)
+    }
+
     /// Mark `addr` as a failed peer.
     pub fn report_failed(&mut self, addr: &MetaAddr) {
         let addr = MetaAddr::new_errored(&addr.addr, &addr.services);
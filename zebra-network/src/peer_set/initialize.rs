@@ -4,6 +4,7 @@
 // which is (c) 2019 Tower Contributors (MIT licensed).
 
 use std::{
+    collections::VecDeque,
     net::SocketAddr,
     sync::{Arc, Mutex},
 };
@@ -17,24 +18,25 @@ use futures::{
 };
 use tokio::{
     net::{TcpListener, TcpStream},
-    sync::broadcast,
+    sync::{broadcast, watch},
     time::Instant,
 };
 use tower::{
     buffer::Buffer, discover::Change, layer::Layer, load::peak_ewma::PeakEwmaDiscover,
-    util::BoxService, Service, ServiceExt,
+    retry::Retry, util::BoxService, Service, ServiceExt,
 };
 use tracing::Span;
 use tracing_futures::Instrument;
 
 use crate::{
-    constants, meta_addr::MetaAddr, peer, timestamp_collector::TimestampCollector, AddressBook,
-    BoxError, Config, Request, Response,
+    constants, meta_addr::MetaAddr, peer, policies::RetryLimitIdempotent,
+    timestamp_collector::TimestampCollector, AddressBook, BoxError, Config, Request, Response,
 };
 
 use zebra_chain::parameters::Network;
 
 use super::CandidateSet;
+use super::PeerEvent;
 use super::PeerSet;
 use peer::Client;
 
@@ -59,20 +61,46 @@ type PeerChange = Result<Change<SocketAddr, peer::Client>, BoxError>;
 ///
 /// In addition to returning a service for outbound requests, this method
 /// returns a shared [`AddressBook`] updated with last-seen timestamps for
-/// connected peers.
+/// connected peers, a [`watch::Receiver`] that reports whether the peer
+/// set currently has at least one ready peer, and a [`broadcast::Receiver`]
+/// of [`PeerEvent`]s that other tasks can subscribe to instead of polling
+/// the [`AddressBook`].
 pub async fn init<S>(
     config: Config,
     inbound_service: S,
 ) -> (
     Buffer<BoxService<Request, Response, BoxError>, Request>,
     Arc<Mutex<AddressBook>>,
+    watch::Receiver<bool>,
+    broadcast::Receiver<PeerEvent>,
 )
 where
     S: Service<Request, Response = Response, Error = BoxError> + Clone + Send + 'static,
     S::Future: Send + 'static,
 {
-    let (address_book, timestamp_collector) = TimestampCollector::spawn();
+    if config.tor_only && config.tor_proxy.is_none() {
+        panic!("`tor_only` requires `tor_proxy` to be configured");
+    }
+
+    if config.listen_addrs.is_empty() {
+        panic!("`listen_addrs` must contain at least one address");
+    }
+
+    if let Some(tor_proxy) = config.tor_proxy {
+        peer::socks::check_proxy_reachable(tor_proxy)
+            .await
+            .unwrap_or_else(|e| {
+                panic!(
+                    "configured Tor proxy at {} is not reachable: {}",
+                    tor_proxy, e
+                )
+            });
+    }
+
+    let (address_book, timestamp_collector) =
+        TimestampCollector::spawn_with_gossip_freshness_cutoff(config.gossip_freshness_cutoff);
     let (inv_sender, inv_receiver) = broadcast::channel(100);
+    let (peer_event_sender, peer_event_receiver) = broadcast::channel(100);
 
     // Construct services that handle inbound handshakes and perform outbound
     // handshakes. These use the same handshake service internally to detect
@@ -80,22 +108,33 @@ where
     // enforce timeouts as specified in the Config.
     let (listener, connector) = {
         use tower::timeout::TimeoutLayer;
-        let hs_timeout = TimeoutLayer::new(constants::HANDSHAKE_TIMEOUT);
+        let hs_timeout = TimeoutLayer::new(config.handshake_timeout);
         use crate::protocol::external::types::PeerServices;
+        let advertised_services = if config.pruned_block_retention.is_some() {
+            PeerServices::NODE_NETWORK_LIMITED
+        } else {
+            PeerServices::NODE_NETWORK
+        };
         let hs = peer::Handshake::builder()
             .with_config(config.clone())
             .with_inbound_service(inbound_service)
             .with_inventory_collector(inv_sender)
             .with_timestamp_collector(timestamp_collector)
-            .with_advertised_services(PeerServices::NODE_NETWORK)
+            .with_advertised_services(advertised_services)
             .with_user_agent(crate::constants::USER_AGENT.to_string())
             .want_transactions(true)
             .finish()
             .expect("configured all required parameters");
-        (
-            hs_timeout.layer(hs.clone()),
-            hs_timeout.layer(peer::Connector::new(hs)),
-        )
+        let connector = match config.tor_proxy {
+            Some(tor_proxy) => peer::Connector::with_tor_proxy(hs.clone(), tor_proxy),
+            None => peer::Connector::new(hs.clone()),
+        };
+        let connector = match config.outbound_bind_addr {
+            Some(outbound_bind_addr) => connector.with_outbound_bind_addr(outbound_bind_addr),
+            None => connector,
+        };
+
+        (hs_timeout.layer(hs), hs_timeout.layer(connector))
     };
 
     // Create an mpsc channel for peer changes, with a generous buffer.
@@ -105,7 +144,15 @@ where
     let (handle_tx, handle_rx) = tokio::sync::oneshot::channel();
 
     // Connect the rx end to a PeerSet, wrapping new peers in load instruments.
-    let peer_set = PeerSet::new(
+    //
+    // `PeakEwmaDiscover` tracks each peer's response latency as an
+    // exponentially-weighted moving average, inflated by the number of
+    // requests currently in flight to that peer. `PeerSet`'s p2c routing
+    // (see `PeerSet::preselect_p2c_index`) uses this load estimate to avoid
+    // sending requests to slow or already-overloaded peers, which matters
+    // most when the sync pipeline is saturating the peer set with block
+    // downloads.
+    let (peer_set, peer_set_readiness) = PeerSet::new(
         PeakEwmaDiscover::new(
             // Discover interprets an error as stream termination,
             // so discard any errored connections...
@@ -118,30 +165,64 @@ where
         handle_rx,
         inv_receiver,
         address_book.clone(),
+        peer_event_sender,
+    );
+    let peer_set = Buffer::new(BoxService::new(peer_set), constants::PEERSET_BUFFER_SIZE);
+    // Transparently retry idempotent requests against another ready peer,
+    // rather than making the caller wait for the full request timeout when
+    // the peer we originally routed a request to disconnects or errors out.
+    let peer_set = Retry::new(
+        RetryLimitIdempotent::new(constants::PEER_SET_RETRY_LIMIT),
+        peer_set,
     );
     let peer_set = Buffer::new(BoxService::new(peer_set), constants::PEERSET_BUFFER_SIZE);
 
-    // 1. Incoming peer connections, via a listener.
+    // 1. Incoming peer connections, via one listener per configured address.
 
     // Warn if we're configured using the wrong network port.
     // TODO: use the right port if the port is unspecified
     //       split the address and port configs?
-    let (wrong_net, wrong_net_port) = match config.network {
-        Network::Mainnet => (Network::Testnet, 18233),
-        Network::Testnet => (Network::Mainnet, 8233),
-    };
-    if config.listen_addr.port() == wrong_net_port {
-        warn!(
-            "We are configured with port {} for {:?}, but that port is the default port for {:?}",
-            config.listen_addr.port(),
-            config.network,
-            wrong_net
-        );
+    //
+    // This check assumes the default Mainnet/Testnet ports, so it doesn't
+    // apply to a custom network (see `Config::network_magic_override`),
+    // which has no fixed default port of its own.
+    if config.network_magic_override.is_none() {
+        let (wrong_net, wrong_net_port) = match config.network {
+            Network::Mainnet => (Network::Testnet, 18233),
+            Network::Testnet => (Network::Mainnet, 8233),
+        };
+        for listen_addr in &config.listen_addrs {
+            if listen_addr.port() == wrong_net_port {
+                warn!(
+                    "We are configured with port {} for {:?}, but that port is the default port for {:?}",
+                    listen_addr.port(),
+                    config.network,
+                    wrong_net
+                );
+            }
+        }
+    } else if let Some(expected_port) = config.custom_network_default_port {
+        for listen_addr in &config.listen_addrs {
+            if listen_addr.port() != expected_port {
+                warn!(
+                    "We are configured with port {} for our custom network, but that network's default port is {}",
+                    listen_addr.port(),
+                    expected_port,
+                );
+            }
+        }
     }
 
-    let listen_guard = tokio::spawn(
-        listen(config.listen_addr, listener, peerset_tx.clone()).instrument(Span::current()),
-    );
+    let listen_guards: Vec<_> = config
+        .listen_addrs
+        .iter()
+        .map(|&listen_addr| {
+            tokio::spawn(
+                listen(listen_addr, listener.clone(), peerset_tx.clone())
+                    .instrument(Span::current()),
+            )
+        })
+        .collect();
 
     // 2. Initial peers, specified in the config.
     let initial_peers_fut = {
@@ -159,7 +240,13 @@ where
     let add_guard = tokio::spawn(initial_peers_fut.instrument(Span::current()));
 
     // 3. Outgoing peers we connect to in response to load.
-    let mut candidates = CandidateSet::new(address_book.clone(), peer_set.clone());
+    let priority_peers = config.resolve_priority_peers().await;
+    let mut candidates = CandidateSet::with_priority_peers(
+        address_book.clone(),
+        peer_set.clone(),
+        priority_peers,
+        config.min_peer_connection_interval,
+    );
 
     // We need to await candidates.update() here, because zcashd only sends one
     // `addr` message per connection, and if we only have one initial peer we
@@ -176,6 +263,8 @@ where
     let crawl_guard = tokio::spawn(
         crawl_and_dial(
             config.crawl_new_peer_interval,
+            config.feeler_interval,
+            config.outbound_rotation_interval,
             demand_tx,
             demand_rx,
             candidates,
@@ -185,18 +274,28 @@ where
         .instrument(Span::current()),
     );
 
-    handle_tx
-        .send(vec![add_guard, listen_guard, crawl_guard])
-        .unwrap();
+    let mut guards = vec![add_guard, crawl_guard];
+    guards.extend(listen_guards);
+    handle_tx.send(guards).unwrap();
 
-    (peer_set, address_book)
+    (
+        peer_set,
+        address_book,
+        peer_set_readiness,
+        peer_event_receiver,
+    )
 }
 
 /// Use the provided `handshaker` to connect to `initial_peers`, then send
 /// the results over `tx`.
+///
+/// `initial_peers` contains one address group per configured host. Within a
+/// group, addresses are raced against each other using Happy Eyeballs
+/// (RFC 8305), so that a host with a broken IPv6 address doesn't delay
+/// startup while Zebra waits on its connection to time out.
 #[instrument(skip(initial_peers, connector, tx))]
 async fn add_initial_peers<S>(
-    initial_peers: std::collections::HashSet<SocketAddr>,
+    initial_peers: Vec<Vec<SocketAddr>>,
     connector: S,
     mut tx: mpsc::Sender<PeerChange>,
 ) -> Result<(), BoxError>
@@ -205,15 +304,13 @@ where
     S::Future: Send + 'static,
 {
     info!(?initial_peers, "connecting to initial peer set");
-    // ## Correctness:
-    //
-    // Each `CallAll` can hold one `Buffer` or `Batch` reservation for
-    // an indefinite period. We can use `CallAllUnordered` without filling
-    // the underlying `Inbound` buffer, because we immediately drive this
-    // single `CallAll` to completion, and handshakes have a short timeout.
-    use tower::util::CallAllUnordered;
-    let addr_stream = futures::stream::iter(initial_peers.into_iter());
-    let mut handshakes = CallAllUnordered::new(connector, addr_stream);
+
+    let mut handshakes: FuturesUnordered<_> = initial_peers
+        .into_iter()
+        // A host's DNS resolution can fail, leaving an empty group.
+        .filter(|group| !group.is_empty())
+        .map(|group| dial_happy_eyeballs(group, connector.clone()))
+        .collect();
 
     while let Some(handshake_result) = handshakes.next().await {
         // this is verbose, but it's better than just hanging with no output
@@ -226,6 +323,54 @@ where
     Ok(())
 }
 
+/// Dial every address in `group`, staggering attempts by
+/// [`constants::HAPPY_EYEBALLS_STAGGER`] and returning the first successful
+/// connection, per the Happy Eyeballs algorithm in RFC 8305.
+///
+/// `group` should have the preferred address family first, as returned by
+/// [`Config::resolve_host`](crate::Config::initial_peers). If every address
+/// in the group fails, returns the last error.
+async fn dial_happy_eyeballs<S>(group: Vec<SocketAddr>, connector: S) -> PeerChange
+where
+    S: Service<SocketAddr, Response = Change<SocketAddr, peer::Client>, Error = BoxError> + Clone,
+    S::Future: Send + 'static,
+{
+    // ## Correctness:
+    //
+    // To avoid hangs, each attempt must only await:
+    // - functions that return immediately, or
+    // - functions that have a reasonable timeout
+    //
+    // Handshakes have a short timeout, and the stagger delay is itself
+    // bounded, so this can't hang.
+    let mut attempts: FuturesUnordered<_> = group
+        .into_iter()
+        .enumerate()
+        .map(|(index, addr)| {
+            let mut connector = connector.clone();
+            async move {
+                if index > 0 {
+                    tokio::time::sleep(constants::HAPPY_EYEBALLS_STAGGER * index as u32).await;
+                }
+                let connector = connector.ready_and().await.expect("connector never errors");
+                connector.call(addr).await
+            }
+        })
+        .collect();
+
+    let mut last_result = None;
+    while let Some(result) = attempts.next().await {
+        if result.is_ok() {
+            // Drop `attempts`, cancelling every other in-flight connection
+            // attempt in this group.
+            return result;
+        }
+        last_result = Some(result);
+    }
+
+    last_result.expect("group must contain at least one address")
+}
+
 /// Bind to `addr`, listen for peers using `handshaker`, then send the
 /// results over `tx`.
 #[instrument(skip(tx, handshaker))]
@@ -271,7 +416,6 @@ where
 }
 
 /// An action that the peer crawler can take.
-#[allow(dead_code)]
 enum CrawlerAction {
     /// Drop the demand signal because there are too many pending handshakes.
     DemandDrop,
@@ -288,6 +432,24 @@ enum CrawlerAction {
     },
     /// Handle a handshake failure to `failed_addr`.
     HandshakeFailed { failed_addr: MetaAddr },
+    /// Attempt a feeler connection to a random `NeverAttempted` candidate, in
+    /// response to a timer `tick`.
+    ///
+    /// Feeler connections test that a candidate we've never attempted before
+    /// is actually reachable, without adding it to the peer set. This keeps
+    /// the address book from filling up with addresses that are no longer
+    /// online.
+    TimerFeeler { tick: Instant },
+    /// Handle a successful feeler connection to `candidate`, by immediately
+    /// closing it again.
+    FeelerConnected {
+        peer_set_change: Change<SocketAddr, Client>,
+    },
+    /// Handle a feeler connection failure to `failed_addr`.
+    FeelerFailed { failed_addr: MetaAddr },
+    /// Disconnect our longest-lived outbound peer, in response to a timer
+    /// `tick`, so that a fresh candidate can take its place.
+    TimerRotateOldest { tick: Instant },
 }
 
 /// Given a channel `demand_rx` that signals a need for new peers, try to find
@@ -298,6 +460,13 @@ enum CrawlerAction {
 /// demand, but no new peers in `candidates`. After crawling, try to connect to
 /// one new peer using `connector`.
 ///
+/// Every `feeler_interval`, open a feeler connection to a random
+/// `NeverAttempted` candidate, to confirm that it's reachable, then close the
+/// connection without adding it to the peer set.
+///
+/// Every `outbound_rotation_interval`, disconnect the longest-lived outbound
+/// peer, and ask the crawler to find a fresh candidate to replace it.
+///
 /// If a handshake fails, restore the unused demand signal by sending it to
 /// `demand_tx`.
 ///
@@ -307,6 +476,8 @@ enum CrawlerAction {
 #[instrument(skip(demand_tx, demand_rx, candidates, connector, success_tx))]
 async fn crawl_and_dial<C, S>(
     crawl_new_peer_interval: std::time::Duration,
+    feeler_interval: std::time::Duration,
+    outbound_rotation_interval: std::time::Duration,
     mut demand_tx: mpsc::Sender<()>,
     mut demand_rx: mpsc::Receiver<()>,
     mut candidates: CandidateSet<S>,
@@ -343,6 +514,19 @@ where
     let mut crawl_timer =
         tokio::time::interval(crawl_new_peer_interval).map(|tick| TimerCrawl { tick });
 
+    let mut feelers = FuturesUnordered::new();
+    feelers.push(future::pending().boxed());
+
+    let mut feeler_timer = tokio::time::interval(feeler_interval).map(|tick| TimerFeeler { tick });
+
+    // The addresses of outbound peers we've successfully handshaked with,
+    // oldest first. Used to find the longest-lived outbound peer to rotate
+    // out, without tracking exact connection ages.
+    let mut outbound_connections: VecDeque<SocketAddr> = VecDeque::new();
+
+    let mut rotation_timer = tokio::time::interval(outbound_rotation_interval)
+        .map(|tick| TimerRotateOldest { tick });
+
     loop {
         metrics::gauge!(
             "crawler.in_flight_handshakes",
@@ -356,7 +540,12 @@ where
             next_handshake_res = handshakes.next() => next_handshake_res.expect(
                 "handshakes never terminates, because it contains a future that never resolves"
             ),
+            next_feeler_res = feelers.next() => next_feeler_res.expect(
+                "feelers never terminates, because it contains a future that never resolves"
+            ),
             next_timer = crawl_timer.next() => next_timer.expect("timers never terminate"),
+            next_feeler_timer = feeler_timer.next() => next_feeler_timer.expect("timers never terminate"),
+            next_rotation_timer = rotation_timer.next() => next_rotation_timer.expect("timers never terminate"),
             // turn the demand into an action, based on the crawler's current state
             _ = demand_rx.next() => {
                 if handshakes.len() > 50 {
@@ -383,11 +572,12 @@ where
             DemandHandshake { candidate } => {
                 // spawn each handshake into an independent task, so it can make
                 // progress independently of the crawls
+                let candidate_addr = candidate.addr;
                 let hs_join =
                     tokio::spawn(dial(candidate, connector.clone())).map(move |res| match res {
                         Ok(crawler_action) => crawler_action,
                         Err(e) => {
-                            panic!("panic during handshaking with {:?}: {:?} ", candidate, e);
+                            panic!("panic during handshaking with {:?}: {:?} ", candidate_addr, e);
                         }
                     });
                 handshakes.push(Box::pin(hs_join));
@@ -416,6 +606,7 @@ where
             HandshakeConnected { peer_set_change } => {
                 if let Change::Insert(ref addr, _) = peer_set_change {
                     debug!(candidate.addr = ?addr, "successfully dialed new peer");
+                    outbound_connections.push_back(*addr);
                 } else {
                     unreachable!("unexpected handshake result: all changes should be Insert");
                 }
@@ -431,6 +622,59 @@ where
                 // turned into a connection, so add it back:
                 let _ = demand_tx.try_send(());
             }
+            TimerFeeler { tick } => {
+                if let Some(candidate) = candidates.feeler_candidate() {
+                    debug!(
+                        ?tick,
+                        candidate.addr = ?candidate.addr,
+                        "opening feeler connection in response to the feeler timer"
+                    );
+                    // spawn each feeler into an independent task, so it can make
+                    // progress independently of the crawls and handshakes
+                    let candidate_addr = candidate.addr;
+                    let feeler_join = tokio::spawn(dial_feeler(candidate, connector.clone()))
+                        .map(move |res| match res {
+                            Ok(crawler_action) => crawler_action,
+                            Err(e) => {
+                                panic!("panic during feeler connection to {:?}: {:?} ", candidate_addr, e);
+                            }
+                        });
+                    feelers.push(Box::pin(feeler_join));
+                } else {
+                    trace!(?tick, "no candidates available for a feeler connection");
+                }
+            }
+            FeelerConnected { peer_set_change } => {
+                if let Change::Insert(ref addr, mut client) = peer_set_change {
+                    debug!(candidate.addr = ?addr, "closing successful feeler connection");
+                    // We only wanted to confirm that the candidate is reachable.
+                    // Immediately close the connection, rather than adding it
+                    // to the peer set.
+                    client.close();
+                } else {
+                    unreachable!("unexpected feeler result: all changes should be Insert");
+                }
+            }
+            FeelerFailed { failed_addr } => {
+                debug!(?failed_addr.addr, "marking feeler candidate as failed");
+                candidates.report_failed(&failed_addr);
+            }
+            TimerRotateOldest { tick } => {
+                if let Some(oldest) = outbound_connections.pop_front() {
+                    debug!(
+                        ?tick,
+                        peer.addr = ?oldest,
+                        "rotating out the longest-lived outbound connection"
+                    );
+                    // Removing a peer that's already gone is harmless, so we
+                    // don't need to track whether `oldest` is still connected.
+                    success_tx.send(Ok(Change::Remove(oldest))).await?;
+                    // Ask the crawler to find a fresh candidate to take its place.
+                    let _ = demand_tx.try_send(());
+                } else {
+                    trace!(?tick, "no outbound connections to rotate");
+                }
+            }
         }
     }
 }
@@ -467,6 +711,46 @@ where
         .await
 }
 
+/// Try to open a feeler connection to `candidate` using `connector`.
+///
+/// Returns a `FeelerConnected` action on success, and a `FeelerFailed`
+/// action on error. The caller is responsible for closing successful
+/// feeler connections, since we only want to confirm that `candidate` is
+/// reachable, not add it to the peer set.
+#[instrument(skip(connector,))]
+async fn dial_feeler<C>(candidate: MetaAddr, mut connector: C) -> CrawlerAction
+where
+    C: Service<SocketAddr, Response = Change<SocketAddr, peer::Client>, Error = BoxError>
+        + Clone
+        + Send
+        + 'static,
+    C::Future: Send + 'static,
+{
+    use CrawlerAction::*;
+
+    // CORRECTNESS
+    //
+    // As in `dial`, to avoid hangs, the feeler must only await:
+    // - functions that return immediately, or
+    // - functions that have a reasonable timeout
+
+    debug!(?candidate.addr, "attempting feeler connection");
+
+    // the connector is always ready, so this can't hang
+    let connector = connector.ready_and().await.expect("connector never errors");
+
+    // the handshake has timeouts, so it shouldn't hang
+    match connector.call(candidate.addr).await {
+        Ok(peer_set_change) => FeelerConnected { peer_set_change },
+        Err(e) => {
+            debug!(?candidate.addr, ?e, "feeler connection failed");
+            FeelerFailed {
+                failed_addr: candidate,
+            }
+        }
+    }
+}
+
 impl From<Result<Change<SocketAddr, Client>, (MetaAddr, BoxError)>> for CrawlerAction {
     fn from(dial_result: Result<Change<SocketAddr, Client>, (MetaAddr, BoxError)>) -> Self {
         use CrawlerAction::*;
@@ -0,0 +1,26 @@
+//! Peer lifecycle events, broadcast so other components can react to peer
+//! churn without polling the [`AddressBook`](crate::AddressBook).
+
+use std::net::SocketAddr;
+
+/// A notable change in a peer connection's lifecycle, broadcast by
+/// [`init`](super::init) so that zebrad components like metrics, the
+/// mempool, and future RPCs can subscribe instead of polling.
+#[derive(Clone, Debug)]
+pub enum PeerEvent {
+    /// We completed a handshake with `addr`, and it was added to the peer set.
+    HandshakeCompleted(SocketAddr),
+    /// `addr` was disconnected from the peer set, for the given `reason`.
+    Disconnected {
+        /// The peer that disconnected.
+        addr: SocketAddr,
+        /// A human-readable description of why the connection ended.
+        reason: String,
+    },
+    /// `addr` was banned, and future connection attempts to it will be refused.
+    ///
+    /// Zebra doesn't ban peers yet, so this variant is never produced in this
+    /// version. It's defined now so that subscribers don't need to update
+    /// their `match` when banning is implemented.
+    Banned(SocketAddr),
+}
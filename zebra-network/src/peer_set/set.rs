@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     future::Future,
     marker::PhantomData,
@@ -17,7 +17,7 @@ use futures::{
     stream::FuturesUnordered,
 };
 use indexmap::IndexMap;
-use tokio::sync::{broadcast, oneshot::error::TryRecvError};
+use tokio::sync::{broadcast, oneshot::error::TryRecvError, watch};
 use tokio::task::JoinHandle;
 use tower::{
     discover::{Change, Discover},
@@ -35,7 +35,7 @@ use crate::{
 
 use super::{
     unready_service::{Error as UnreadyError, UnreadyService},
-    InventoryRegistry,
+    InventoryRegistry, PeerEvent,
 };
 
 /// A [`tower::Service`] that abstractly represents "the rest of the network".
@@ -107,6 +107,13 @@ where
     ///
     /// Used for logging diagnostics.
     address_book: Arc<Mutex<AddressBook>>,
+    /// Reports whether the peer set currently has at least one ready peer,
+    /// so that other tasks can wait for the peer set to become usable,
+    /// instead of polling it with requests.
+    readiness_reporter: watch::Sender<bool>,
+    /// Broadcasts peer lifecycle events, so other tasks can react to peer
+    /// churn without polling the [`AddressBook`].
+    peer_event_sender: broadcast::Sender<PeerEvent>,
 }
 
 impl<D> PeerSet<D>
@@ -119,14 +126,19 @@ where
     <D::Service as Load>::Metric: Debug,
 {
     /// Construct a peerset which uses `discover` internally.
+    ///
+    /// Returns the peer set, along with a [`watch::Receiver`] that reports
+    /// whether the peer set currently has at least one ready peer.
     pub fn new(
         discover: D,
         demand_signal: mpsc::Sender<()>,
         handle_rx: tokio::sync::oneshot::Receiver<Vec<JoinHandle<Result<(), BoxError>>>>,
         inv_stream: broadcast::Receiver<(InventoryHash, SocketAddr)>,
         address_book: Arc<Mutex<AddressBook>>,
-    ) -> Self {
-        Self {
+        peer_event_sender: broadcast::Sender<PeerEvent>,
+    ) -> (Self, watch::Receiver<bool>) {
+        let (readiness_reporter, readiness_receiver) = watch::channel(false);
+        let peer_set = Self {
             discover,
             preselected_p2c_index: None,
             ready_services: IndexMap::new(),
@@ -138,7 +150,11 @@ where
             inventory_registry: InventoryRegistry::new(inv_stream),
             last_peer_log: None,
             address_book,
-        }
+            readiness_reporter,
+            peer_event_sender,
+        };
+
+        (peer_set, readiness_receiver)
     }
 
     fn poll_background_errors(&mut self, cx: &mut Context) -> Result<(), BoxError> {
@@ -188,6 +204,10 @@ where
                     debug!(%error, "service failed while unready, dropped");
                     let _cancel = self.cancel_handles.remove(&key);
                     assert!(_cancel.is_some(), "missing cancel handle");
+                    let _ = self.peer_event_sender.send(PeerEvent::Disconnected {
+                        addr: key,
+                        reason: error.to_string(),
+                    });
                 }
             }
         }
@@ -203,11 +223,18 @@ where
                 Change::Remove(key) => {
                     trace!(?key, "got Change::Remove from Discover");
                     self.remove(&key);
+                    let _ = self.peer_event_sender.send(PeerEvent::Disconnected {
+                        addr: key,
+                        reason: "peer was removed from the peer set".into(),
+                    });
                 }
                 Change::Insert(key, svc) => {
                     trace!(?key, "got Change::Insert from Discover");
                     self.remove(&key);
                     self.push_unready(key, svc);
+                    let _ = self
+                        .peer_event_sender
+                        .send(PeerEvent::HandshakeCompleted(key));
                 }
             }
         }
@@ -257,6 +284,15 @@ where
     }
 
     /// Performs P2C on inner services to select a ready service.
+    ///
+    /// The `D::Service`s tracked by `ready_services` are wrapped in
+    /// [`tower::load::PeakEwmaDiscover`] by [`init`](super::init), so
+    /// [`query_load`](Self::query_load) returns an exponentially-weighted
+    /// moving average of each peer's response latency, inflated by the
+    /// number of requests currently in flight to it. Preferring the
+    /// lower-load peer of each sampled pair means slow or already-busy
+    /// peers are chosen less often, which matters most when the sync
+    /// pipeline is saturating the peer set with block downloads.
     fn preselect_p2c_index(&mut self) -> Option<usize> {
         match self.ready_services.len() {
             0 => None,
@@ -280,6 +316,11 @@ where
     }
 
     /// Accesses a ready endpoint by index and returns its current load.
+    ///
+    /// With the EWMA discovery wrapper used by [`init`](super::init), this is
+    /// the peer's decayed average response latency, so it falls as a peer
+    /// proves itself fast and reliable, and rises as it becomes slow or
+    /// overloaded with in-flight requests.
     fn query_load(&self, index: usize) -> <D::Service as Load>::Metric {
         let (_, svc) = self.ready_services.get_index(index).expect("invalid index");
         svc.load()
@@ -329,16 +370,31 @@ where
         }
     }
 
-    // Routes a request to all ready peers, ignoring return values.
-    fn route_all(&mut self, req: Request) -> <Self as tower::Service<Request>>::Future {
+    // Routes a request to a random subset of ready peers, sized to the
+    // square root of the ready set, ignoring return values.
+    //
+    // This is used to fan out gossip requests (`AdvertiseTransactions`,
+    // `AdvertiseBlock`) to enough peers that the gossiped item reliably
+    // propagates through the network, without the cost and redundancy of
+    // sending it to every ready peer on every advertisement.
+    fn route_fanout(&mut self, req: Request) -> <Self as tower::Service<Request>>::Future {
         // This is not needless: otherwise, we'd hold a &mut reference to self.ready_services,
         // blocking us from passing &mut self to push_unready.
         let ready_services = std::mem::take(&mut self.ready_services);
         self.preselected_p2c_index = None; // All services are now unready.
 
+        let fanout_size = (ready_services.len() as f64).sqrt().ceil() as usize;
+        let fanout_size = fanout_size.min(ready_services.len());
+        let fanout: HashSet<usize> =
+            rand::seq::index::sample(&mut rand::thread_rng(), ready_services.len(), fanout_size)
+                .into_iter()
+                .collect();
+
         let futs = FuturesUnordered::new();
-        for (key, mut svc) in ready_services {
-            futs.push(svc.call(req.clone()).map_err(|_| ()));
+        for (index, (key, mut svc)) in ready_services.into_iter().enumerate() {
+            if fanout.contains(&index) {
+                futs.push(svc.call(req.clone()).map_err(|_| ()));
+            }
             self.push_unready(key, svc);
         }
 
@@ -400,6 +456,9 @@ where
         metrics::gauge!("pool.num_ready", num_ready as f64);
         metrics::gauge!("pool.num_unready", num_unready as f64);
         metrics::gauge!("zcash.net.peers", num_peers as f64);
+
+        // `send` only errors if there are no receivers, which is fine here.
+        let _ = self.readiness_reporter.send(num_ready > 0);
     }
 }
 
@@ -450,9 +509,14 @@ where
                     Poll::Ready(Err(e)) => {
                         let error = e.into();
                         trace!(%error, "preselected service failed, dropping it");
-                        self.ready_services
+                        let (key, _) = self
+                            .ready_services
                             .swap_remove_index(index)
                             .expect("preselected index must be valid");
+                        let _ = self.peer_event_sender.send(PeerEvent::Disconnected {
+                            addr: key,
+                            reason: error.to_string(),
+                        });
                     }
                 }
             }
@@ -505,8 +569,8 @@ where
                 let hash = InventoryHash::from(*hashes.iter().next().unwrap());
                 self.route_inv(req, hash)
             }
-            Request::AdvertiseTransactions(_) => self.route_all(req),
-            Request::AdvertiseBlock(_) => self.route_all(req),
+            Request::AdvertiseTransactions(_) => self.route_fanout(req),
+            Request::AdvertiseBlock(_) => self.route_fanout(req),
             _ => self.route_p2c(req),
         };
         self.update_metrics();
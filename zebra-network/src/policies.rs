@@ -1,6 +1,8 @@
 use futures::future;
 use tower::retry::Policy;
 
+use crate::protocol::internal::Request;
+
 /// A very basic retry policy with a limited number of retry attempts.
 ///
 /// XXX Remove this when https://github.com/tower-rs/tower/pull/414 lands.
@@ -40,6 +42,50 @@ impl<Req: Clone + std::fmt::Debug, Res, E: std::fmt::Debug> Policy<Req, Res, E>
     }
 }
 
+/// A retry policy that transparently retries idempotent [`Request`]s against
+/// another ready peer, up to a limited number of attempts, instead of making
+/// the caller wait for the full request timeout.
+///
+/// Requests with network-visible side effects (see
+/// [`Request::is_retryable_with_another_peer`]) are never retried, because
+/// retrying them against a different peer could duplicate their effect.
+#[derive(Clone, Debug)]
+pub struct RetryLimitIdempotent {
+    remaining_tries: usize,
+}
+
+impl RetryLimitIdempotent {
+    /// Create a policy with the given number of retry attempts.
+    pub fn new(retry_attempts: usize) -> Self {
+        RetryLimitIdempotent {
+            remaining_tries: retry_attempts,
+        }
+    }
+}
+
+impl<Res, E: std::fmt::Debug> Policy<Request, Res, E> for RetryLimitIdempotent {
+    type Future = future::Ready<Self>;
+    fn retry(&self, req: &Request, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let e = result.err()?;
+        if self.remaining_tries == 0 || !req.is_retryable_with_another_peer() {
+            return None;
+        }
+
+        tracing::debug!(?req, ?e, remaining_tries = self.remaining_tries, "retrying idempotent request on another peer");
+        Some(future::ready(RetryLimitIdempotent {
+            remaining_tries: self.remaining_tries - 1,
+        }))
+    }
+
+    fn clone_request(&self, req: &Request) -> Option<Request> {
+        if req.is_retryable_with_another_peer() {
+            Some(req.clone())
+        } else {
+            None
+        }
+    }
+}
+
 /// A very basic retry policy that always retries failed requests.
 ///
 /// XXX remove this when https://github.com/tower-rs/tower/pull/414 lands.
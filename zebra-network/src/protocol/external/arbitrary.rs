@@ -1,8 +1,23 @@
-use proptest::{arbitrary::any, arbitrary::Arbitrary, prelude::*};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 
-use super::InventoryHash;
+use chrono::{TimeZone, Utc};
+use proptest::{arbitrary::any, arbitrary::Arbitrary, collection::vec, prelude::*};
 
-use zebra_chain::{block, transaction};
+use zebra_chain::{
+    block::{self, Block},
+    transaction::{self, Transaction},
+    LedgerState,
+};
+
+use super::{
+    message::RejectReason,
+    types::{Filter, Nonce, PeerServices, Tweak, Version},
+    InventoryHash, Message,
+};
+use crate::meta_addr::MetaAddr;
 
 impl InventoryHash {
     /// Generate a proptest strategy for Inv Errors
@@ -35,6 +50,14 @@ impl InventoryHash {
             .prop_map(InventoryHash::FilteredBlock)
             .boxed()
     }
+
+    /// Generate a proptest strategy for Inv Wtx hashes
+    pub fn wtx_strategy() -> BoxedStrategy<Self> {
+        (any::<[u8; 32]>())
+            .prop_map(transaction::Hash)
+            .prop_map(InventoryHash::Wtx)
+            .boxed()
+    }
 }
 
 impl Arbitrary for InventoryHash {
@@ -46,6 +69,240 @@ impl Arbitrary for InventoryHash {
             Self::tx_strategy(),
             Self::block_strategy(),
             Self::filtered_block_strategy(),
+            Self::wtx_strategy(),
+        ]
+        .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+impl Arbitrary for Version {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        any::<u32>().prop_map(Version).boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+impl Arbitrary for Nonce {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        any::<u64>().prop_map(Nonce).boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+impl Arbitrary for Tweak {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        any::<u32>().prop_map(Tweak).boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+impl Arbitrary for Filter {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        // Bound the generated filter well below the 36,000 byte wire limit,
+        // so that proptest cases run quickly.
+        vec(any::<u8>(), 0..256).prop_map(Filter).boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+impl Arbitrary for RejectReason {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(RejectReason::Malformed),
+            Just(RejectReason::Invalid),
+            Just(RejectReason::Obsolete),
+            Just(RejectReason::Duplicate),
+            Just(RejectReason::Nonstandard),
+            Just(RejectReason::Dust),
+            Just(RejectReason::InsufficientFee),
+            Just(RejectReason::Checkpoint),
+            Just(RejectReason::Other),
+        ]
+        .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
+/// Generate a proptest strategy for a socket address.
+///
+/// Only generates IPv4 addresses, because that's all `zcashd` peers
+/// advertise on the wire in practice, and it keeps the strategy simple.
+fn socket_addr_strategy() -> BoxedStrategy<SocketAddr> {
+    (any::<[u8; 4]>(), any::<u16>())
+        .prop_map(|(octets, port)| {
+            SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3])),
+                port,
+            )
+        })
+        .boxed()
+}
+
+impl Message {
+    /// Generate a proptest strategy for a `version` message.
+    fn version_strategy() -> BoxedStrategy<Self> {
+        (
+            any::<Version>(),
+            any::<u64>().prop_map(PeerServices::from_bits_truncate),
+            any::<block::Height>(),
+            any::<Nonce>(),
+            any::<u64>().prop_map(PeerServices::from_bits_truncate),
+            socket_addr_strategy(),
+            any::<u64>().prop_map(PeerServices::from_bits_truncate),
+            socket_addr_strategy(),
+            ".{0,10}",
+            any::<bool>(),
+        )
+            .prop_map(
+                |(
+                    version,
+                    services,
+                    start_height,
+                    nonce,
+                    recv_services,
+                    recv_addr,
+                    from_services,
+                    from_addr,
+                    user_agent,
+                    relay,
+                )| Message::Version {
+                    version,
+                    services,
+                    timestamp: Utc.timestamp(1_580_000_000, 0),
+                    address_recv: (recv_services, recv_addr),
+                    address_from: (from_services, from_addr),
+                    nonce,
+                    user_agent,
+                    start_height,
+                    relay,
+                },
+            )
+            .boxed()
+    }
+
+    /// Generate a proptest strategy for a `reject` message.
+    fn reject_strategy() -> BoxedStrategy<Self> {
+        (
+            ".{0,10}",
+            any::<RejectReason>(),
+            ".{0,10}",
+            proptest::option::of(any::<[u8; 32]>()),
+        )
+            .prop_map(|(message, ccode, reason, data)| Message::Reject {
+                message,
+                ccode,
+                reason,
+                data,
+            })
+            .boxed()
+    }
+
+    /// Generate a proptest strategy for a `headers` message.
+    fn headers_strategy() -> BoxedStrategy<Self> {
+        vec(
+            any::<block::Header>().prop_map(|header| block::CountedHeader {
+                header,
+                transaction_count: 0,
+            }),
+            0..10,
+        )
+        .prop_map(Message::Headers)
+        .boxed()
+    }
+
+    /// Generate a proptest strategy for a `block` message.
+    ///
+    /// Uses a fixed [`LedgerState`], because the wire format doesn't depend
+    /// on the ledger state used to generate a consensus-plausible block.
+    fn block_message_strategy() -> BoxedStrategy<Self> {
+        Block::arbitrary_with(LedgerState::default())
+            .prop_map(|block| Message::Block(Arc::new(block)))
+            .boxed()
+    }
+
+    /// Generate a proptest strategy for a `tx` message.
+    fn tx_strategy() -> BoxedStrategy<Self> {
+        Transaction::vec_strategy(LedgerState::default(), 1)
+            .prop_map(|transactions| Message::Tx(transactions[0].clone()))
+            .boxed()
+    }
+
+    /// Generate a proptest strategy for a `filterload` message.
+    fn filterload_strategy() -> BoxedStrategy<Self> {
+        (any::<Filter>(), any::<u32>(), any::<Tweak>(), any::<u8>())
+            .prop_map(
+                |(filter, hash_functions_count, tweak, flags)| Message::FilterLoad {
+                    filter,
+                    hash_functions_count,
+                    tweak,
+                    flags,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for Message {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Self::version_strategy(),
+            Just(Message::Verack),
+            any::<Nonce>().prop_map(Message::Ping),
+            any::<Nonce>().prop_map(Message::Pong),
+            Self::reject_strategy(),
+            Just(Message::GetAddr),
+            // Gossiped addresses carry only known service bits and a u32
+            // timestamp in whole seconds on the wire.
+            vec(
+                (socket_addr_strategy(), any::<u64>(), any::<u32>()).prop_map(
+                    |(addr, services, last_seen)| MetaAddr::new_gossiped(
+                        &addr,
+                        &PeerServices::from_bits_truncate(services),
+                        &Utc.timestamp(last_seen.into(), 0),
+                    )
+                ),
+                0..10
+            )
+            .prop_map(Message::Addr),
+            (
+                vec(any::<block::Hash>(), 0..10),
+                proptest::option::of(any::<block::Hash>())
+            )
+                .prop_map(|(known_blocks, stop)| Message::GetBlocks { known_blocks, stop }),
+            vec(any::<InventoryHash>(), 0..10).prop_map(Message::Inv),
+            (
+                vec(any::<block::Hash>(), 0..10),
+                proptest::option::of(any::<block::Hash>())
+            )
+                .prop_map(|(known_blocks, stop)| Message::GetHeaders { known_blocks, stop }),
+            Self::headers_strategy(),
+            vec(any::<InventoryHash>(), 0..10).prop_map(Message::GetData),
+            Self::block_message_strategy(),
+            Self::tx_strategy(),
+            vec(any::<InventoryHash>(), 0..10).prop_map(Message::NotFound),
+            Just(Message::Mempool),
+            Self::filterload_strategy(),
+            vec(any::<u8>(), 0..256).prop_map(|data| Message::FilterAdd { data }),
+            Just(Message::FilterClear),
         ]
         .boxed()
     }
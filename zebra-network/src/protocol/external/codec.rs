@@ -42,8 +42,8 @@ pub struct Codec {
 
 /// A builder for specifying [`Codec`] options.
 pub struct Builder {
-    /// The network magic to use in encoding.
-    network: Network,
+    /// The network magic to use in encoding and decoding.
+    magic: Magic,
     /// The protocol version to speak when encoding/decoding.
     version: Version,
     /// The maximum allowable message length.
@@ -56,7 +56,7 @@ impl Codec {
     /// Return a builder for constructing a [`Codec`].
     pub fn builder() -> Builder {
         Builder {
-            network: Network::Mainnet,
+            magic: Magic::from(Network::Mainnet),
             version: constants::CURRENT_VERSION,
             max_len: MAX_PROTOCOL_MESSAGE_LEN,
             metrics_label: None,
@@ -80,7 +80,19 @@ impl Builder {
 
     /// Configure the codec for the given [`Network`].
     pub fn for_network(mut self, network: Network) -> Self {
-        self.network = network;
+        self.magic = Magic::from(network);
+        self
+    }
+
+    /// Configure the codec to use `magic` instead of the magic associated
+    /// with a [`Network`].
+    ///
+    /// This is used to connect to custom networks, such as private
+    /// Zcash-compatible testnets, which use a network magic that doesn't
+    /// match `Network::Mainnet` or `Network::Testnet`. Overrides any magic
+    /// set by a previous call to [`Builder::for_network`].
+    pub fn for_magic(mut self, magic: Magic) -> Self {
+        self.magic = magic;
         self
     }
 
@@ -156,7 +168,7 @@ impl Encoder<Message> for Codec {
         let start_len = dst.len();
         {
             let dst = &mut dst.writer();
-            dst.write_all(&Magic::from(self.builder.network).0[..])?;
+            dst.write_all(&self.builder.magic.0[..])?;
             dst.write_all(command)?;
             dst.write_u32::<LittleEndian>(body_length as u32)?;
 
@@ -361,7 +373,7 @@ impl Decoder for Codec {
                     "read header from src buffer"
                 );
 
-                if magic != Magic::from(self.builder.network) {
+                if magic != self.builder.magic {
                     return Err(Parse("supplied magic did not meet expectations"));
                 }
                 if body_len > self.builder.max_len {
@@ -408,6 +420,9 @@ impl Decoder for Codec {
                 }
 
                 let mut body_reader = Cursor::new(&body);
+                let message_type = String::from_utf8_lossy(&command)
+                    .trim_end_matches('\u{0}')
+                    .to_string();
                 match &command {
                     b"version\0\0\0\0\0" => self.read_version(&mut body_reader),
                     b"verack\0\0\0\0\0\0" => self.read_verack(&mut body_reader),
@@ -430,6 +445,9 @@ impl Decoder for Codec {
                     b"filterclear\0" => self.read_filterclear(&mut body_reader),
                     _ => return Err(Parse("unknown command")),
                 }
+                // Record which message body failed to parse, and how far into it we
+                // got, so a malformed peer message doesn't require hexdump archaeology.
+                .map_err(|err| err.context("body", message_type, Some(body_reader.position())))
                 // We need Ok(Some(msg)) to signal that we're done decoding.
                 // This is also convenient for tracing the parse result.
                 .map(|msg| {
@@ -627,11 +645,14 @@ impl Codec {
     }
 }
 
-// XXX replace these interior unit tests with exterior integration tests + proptest
+// The `message_round_trip` proptest below covers arbitrary values of every
+// `Message` variant; the tests above cover specific edge cases (empty and
+// oversized fields) that are easier to express by hand.
 #[cfg(test)]
 mod tests {
     use super::*;
     use futures::prelude::*;
+    use proptest::prelude::*;
     use tokio::runtime::Runtime;
 
     #[test]
@@ -826,4 +847,37 @@ mod tests {
                 .expect("message should decode with the msg body size as max allowed value")
         });
     }
+
+    proptest! {
+        /// Check that an arbitrary [`Message`] of any variant round-trips
+        /// through the wire format unchanged.
+        #[test]
+        fn message_round_trip(msg in any::<Message>()) {
+            zebra_test::init();
+
+            let rt = Runtime::new().unwrap();
+
+            use tokio_util::codec::{FramedRead, FramedWrite};
+            let bytes = rt.block_on(async {
+                let mut bytes = Vec::new();
+                {
+                    let mut fw = FramedWrite::new(&mut bytes, Codec::builder().finish());
+                    fw.send(msg.clone())
+                        .await
+                        .expect("message should be serialized");
+                }
+                bytes
+            });
+
+            let parsed = rt.block_on(async {
+                let mut fr = FramedRead::new(Cursor::new(&bytes), Codec::builder().finish());
+                fr.next()
+                    .await
+                    .expect("a next message should be available")
+                    .expect("that message should deserialize")
+            });
+
+            prop_assert_eq!(msg, parsed);
+        }
+    }
 }
@@ -38,6 +38,17 @@ pub enum InventoryHash {
     /// rather than a block message; this only works if a bloom filter has been
     /// set.
     FilteredBlock(block::Hash),
+    /// The wtxid of a v5 transaction, as introduced by NU5 ([ZIP 239]).
+    ///
+    /// `zcashd` advertises and requests v5 transactions by their wtxid
+    /// (which commits to the witness data), rather than their legacy txid, so
+    /// that transactions can be relayed before their authorizing data is
+    /// verified. This variant carries a single 32-byte [`transaction::Hash`],
+    /// not a [`transaction::WtxId`], and is read and written as 32 bytes; it
+    /// holds no authorizing data digest.
+    ///
+    /// [ZIP 239]: https://zips.z.cash/zip-0239
+    Wtx(transaction::Hash),
 }
 
 impl From<transaction::Hash> for InventoryHash {
@@ -61,6 +72,7 @@ impl ZcashSerialize for InventoryHash {
             InventoryHash::Tx(hash) => (1, hash.0),
             InventoryHash::Block(hash) => (2, hash.0),
             InventoryHash::FilteredBlock(hash) => (3, hash.0),
+            InventoryHash::Wtx(hash) => (5, hash.0),
         };
         writer.write_u32::<LittleEndian>(code)?;
         writer.write_all(&bytes)?;
@@ -77,6 +89,7 @@ impl ZcashDeserialize for InventoryHash {
             1 => Ok(InventoryHash::Tx(transaction::Hash(bytes))),
             2 => Ok(InventoryHash::Block(block::Hash(bytes))),
             3 => Ok(InventoryHash::FilteredBlock(block::Hash(bytes))),
+            5 => Ok(InventoryHash::Wtx(transaction::Hash(bytes))),
             _ => Err(SerializationError::Parse("invalid inventory code")),
         }
     }
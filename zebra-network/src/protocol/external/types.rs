@@ -16,7 +16,7 @@ use zebra_chain::{
 use proptest_derive::Arbitrary;
 
 /// A magic number identifying the network.
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct Magic(pub [u8; 4]);
 
@@ -37,7 +37,7 @@ impl From<Network> for Magic {
 }
 
 /// A protocol version number.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub struct Version(pub u32);
 
 impl Version {
@@ -84,6 +84,12 @@ bitflags! {
         /// blocks, as opposed to a light client that makes network requests but
         /// does not provide network services.
         const NODE_NETWORK = 1;
+
+        /// NODE_NETWORK_LIMITED means that the node is capable of serving
+        /// blocks, but only within some recent range of its chain tip, because
+        /// it's configured to prune older block data (see
+        /// `zebra_state::Config::pruning`).
+        const NODE_NETWORK_LIMITED = 1 << 10;
     }
 }
 
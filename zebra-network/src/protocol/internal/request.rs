@@ -171,3 +171,30 @@ pub enum Request {
     /// Returns [`Response::TransactionHashes`](super::Response::TransactionHashes).
     MempoolTransactions,
 }
+
+impl Request {
+    /// Returns `true` if this request can be safely retried against a
+    /// different peer, if the peer it was originally routed to disconnects or
+    /// returns an error.
+    ///
+    /// Read-only requests are idempotent, so retrying them on another peer is
+    /// harmless. Requests that have a network-visible side effect are not
+    /// retried, because sending them to a second peer could have a different
+    /// effect than sending them to the first peer would have had (for
+    /// example, gossiping the same transaction to more peers than intended).
+    pub fn is_retryable_with_another_peer(&self) -> bool {
+        match self {
+            Request::Peers
+            | Request::Ping(_)
+            | Request::BlocksByHash(_)
+            | Request::TransactionsByHash(_)
+            | Request::FindBlocks { .. }
+            | Request::FindHeaders { .. }
+            | Request::MempoolTransactions => true,
+
+            Request::PushTransaction(_)
+            | Request::AdvertiseTransactions(_)
+            | Request::AdvertiseBlock(_) => false,
+        }
+    }
+}
@@ -1,10 +1,13 @@
 //! The timestamp collector collects liveness information from peers.
 
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use futures::{channel::mpsc, prelude::*};
 
-use crate::{types::MetaAddr, AddressBook};
+use crate::{constants, types::MetaAddr, AddressBook};
 
 /// The timestamp collector hooks into incoming message streams for each peer and
 /// records per-connection last-seen timestamps into an [`AddressBook`].
@@ -15,13 +18,23 @@ impl TimestampCollector {
     /// transmission channel for timestamp events and for the [`AddressBook`] it
     /// updates.
     pub fn spawn() -> (Arc<Mutex<AddressBook>>, mpsc::Sender<MetaAddr>) {
+        TimestampCollector::spawn_with_gossip_freshness_cutoff(
+            constants::DEFAULT_GOSSIP_FRESHNESS_CUTOFF,
+        )
+    }
+
+    /// As [`TimestampCollector::spawn`], but gossiping only addresses seen
+    /// within `gossip_freshness_cutoff`.
+    pub fn spawn_with_gossip_freshness_cutoff(
+        gossip_freshness_cutoff: Duration,
+    ) -> (Arc<Mutex<AddressBook>>, mpsc::Sender<MetaAddr>) {
         use tracing::Level;
         const TIMESTAMP_WORKER_BUFFER_SIZE: usize = 100;
         let (worker_tx, mut worker_rx) = mpsc::channel(TIMESTAMP_WORKER_BUFFER_SIZE);
-        let address_book = Arc::new(Mutex::new(AddressBook::new(span!(
-            Level::TRACE,
-            "timestamp collector"
-        ))));
+        let address_book = Arc::new(Mutex::new(AddressBook::with_gossip_freshness_cutoff(
+            span!(Level::TRACE, "timestamp collector"),
+            gossip_freshness_cutoff,
+        )));
         let worker_address_book = address_book.clone();
 
         let worker = async move {
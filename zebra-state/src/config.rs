@@ -39,10 +39,149 @@ pub struct Config {
     /// [`cache_dir`]: struct.Config.html#structfield.cache_dir
     pub ephemeral: bool,
 
+    /// Whether an ephemeral database should live purely in memory, rather
+    /// than in a temporary directory on disk.
+    ///
+    /// This is fast, and avoids leftover files if Zebra panics or crashes,
+    /// but the database must fit in RAM, so it's only suitable for tests and
+    /// regtest, not for syncing a full mainnet or testnet chain.
+    ///
+    /// Ignored unless `ephemeral` is also `true`. Set to `false` by default.
+    pub ephemeral_in_memory: bool,
+
     /// Commit blocks to the finalized state up to this height, then exit Zebra.
     ///
     /// Set to `None` by default: Zebra continues syncing indefinitely.
     pub debug_stop_at_height: Option<u32>,
+
+    /// Whether to maintain an index from transparent addresses to their
+    /// UTXOs, for `Request::AddressBalance` and `Request::AddressUtxos`.
+    ///
+    /// Set to `false` by default, because the index takes up extra disk
+    /// space and isn't needed for consensus. Enable it for explorer or
+    /// exchange use cases that need to look up an address's balance or
+    /// spendable outputs.
+    pub transparent_indexing: bool,
+
+    /// How many blocks below the finalized tip to keep full block bodies for.
+    ///
+    /// Once a block falls deeper than this below the tip, its transaction
+    /// data is dropped from the database, keeping only its header. The
+    /// header is enough to validate `previous_block_hash` continuity and to
+    /// serve headers to peers; the UTXO set and nullifier indexes aren't
+    /// affected, since they're stored separately from the block body.
+    ///
+    /// Set to `None` by default, so Zebra keeps every block body. Pruning
+    /// trades away the ability to serve full historical blocks (for
+    /// `Request::Block` and `getblock`-style RPCs) for a smaller database.
+    ///
+    /// Set to [`Config::HEADER_ONLY_PRUNING`] for Zebra's header-only mode:
+    /// every block body is dropped as soon as it's committed, leaving only
+    /// headers (and the UTXO set and nullifier indexes, which are unaffected
+    /// by pruning). This suits resource-constrained deployments that want to
+    /// act as a header relay or light-client backend, rather than a full
+    /// node. Pair it with `zebra_network::Config::pruned_block_retention`
+    /// set to the same depth, so Zebra also advertises `NODE_NETWORK_LIMITED`
+    /// to its peers instead of `NODE_NETWORK`.
+    pub pruning: Option<u32>,
+
+    /// The size of RocksDB's block cache, in megabytes.
+    ///
+    /// Set to `None` by default, which uses RocksDB's built-in default
+    /// (currently 8 MB). Increasing this can significantly speed up reads
+    /// on machines with spare RAM, at the cost of using more memory.
+    pub db_block_cache_size_mb: Option<usize>,
+
+    /// The size of RocksDB's in-memory write buffer for each column family,
+    /// in megabytes.
+    ///
+    /// Set to `None` by default, which uses RocksDB's built-in default.
+    /// Increasing this reduces the frequency of compactions, trading memory
+    /// usage for write throughput.
+    pub db_write_buffer_size_mb: Option<usize>,
+
+    /// RocksDB's compaction style.
+    ///
+    /// Set to [`CompactionStyle::Level`] by default, which is a good
+    /// general-purpose choice. [`CompactionStyle::Universal`] trades some
+    /// read and space amplification for lower write amplification, which
+    /// can help on write-constrained disks.
+    pub db_compaction_style: CompactionStyle,
+
+    /// Overrides the number of files RocksDB is allowed to keep open at
+    /// once.
+    ///
+    /// Set to `None` by default, so Zebra derives a limit from the
+    /// process's open file limit, reserving half of it for the database and
+    /// half for peer connections. Set this on small VPSes with a low open
+    /// file limit that can't be raised, or to let the database use more
+    /// file descriptors on a server dedicated to running Zebra.
+    pub db_max_open_files: Option<i32>,
+
+    /// Whether to compress block bodies with zstd before storing them in the
+    /// `block_by_height` column family.
+    ///
+    /// Set to `false` by default. Enabling this trades CPU time (on both
+    /// writes and reads) for roughly 30-40% less disk space used by block
+    /// bodies, since they compress well.
+    ///
+    /// This only affects `block_by_height`, which is the only column family
+    /// holding serialized block data; every other column family is
+    /// unaffected. Compression and decompression both happen inside
+    /// RocksDB's storage layer, so reads are decompressed transparently:
+    /// Zebra's block-reading code doesn't need to know whether a block it
+    /// reads was stored compressed.
+    ///
+    /// Toggling this doesn't require recreating the database: RocksDB keeps
+    /// reading existing SST files with whatever compression they were
+    /// written with, and starts writing (and, as background compactions
+    /// happen, rewriting) new ones with the configured setting. To compress
+    /// every existing block body immediately instead of waiting for
+    /// compaction to get to it, run [`crate::compact_blocks`] after enabling
+    /// this.
+    pub compress_blocks: bool,
+
+    /// The maximum number of queued finalized blocks to commit in a single
+    /// RocksDB write batch.
+    ///
+    /// When a run of queued blocks becomes ready to commit at once (for
+    /// example, when initial block download finally delivers the block that
+    /// connects a long run of already-downloaded blocks), committing them
+    /// one RocksDB write at a time is much slower than combining them into a
+    /// single batched write, especially on spinning disks and cloud block
+    /// storage. Set to `1` to write every block individually, which disables
+    /// batching. Set to `64` by default.
+    pub finalized_block_write_batch_limit: usize,
+}
+
+/// RocksDB's compaction style, as exposed in [`Config::db_compaction_style`].
+///
+/// This mirrors [`rocksdb::DBCompactionStyle`], which isn't `(De)Serialize`,
+/// so it can't be used in the config directly.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactionStyle {
+    /// Classic leveled compaction. Good general-purpose default: reasonable
+    /// write amplification, and effective space reclamation.
+    Level,
+    /// Universal (tiered) compaction. Lower write amplification than
+    /// levelled compaction, at the cost of higher space and read
+    /// amplification.
+    Universal,
+    /// FIFO compaction: old data is dropped once the database exceeds a
+    /// size limit, rather than being compacted. Not useful for Zebra, which
+    /// needs to keep its state, but included for completeness.
+    Fifo,
+}
+
+impl From<CompactionStyle> for rocksdb::DBCompactionStyle {
+    fn from(style: CompactionStyle) -> Self {
+        match style {
+            CompactionStyle::Level => rocksdb::DBCompactionStyle::Level,
+            CompactionStyle::Universal => rocksdb::DBCompactionStyle::Universal,
+            CompactionStyle::Fifo => rocksdb::DBCompactionStyle::Fifo,
+        }
+    }
 }
 
 fn gen_temp_path(prefix: &str) -> PathBuf {
@@ -52,6 +191,10 @@ fn gen_temp_path(prefix: &str) -> PathBuf {
 }
 
 impl Config {
+    /// A [`Config::pruning`] depth that keeps no block bodies at all, only
+    /// headers: Zebra's header-only storage mode.
+    pub const HEADER_ONLY_PRUNING: u32 = 0;
+
     /// The ideal open file limit for Zebra
     const IDEAL_OPEN_FILE_LIMIT: usize = 1024;
 
@@ -94,17 +237,40 @@ impl Config {
 
         let mut opts = rocksdb::Options::default();
 
+        if self.ephemeral && self.ephemeral_in_memory {
+            let mem_env =
+                rocksdb::Env::mem_env().expect("creating an in-memory rocksdb Env should not fail");
+            opts.set_env(&mem_env);
+        }
+
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
+        opts.set_compaction_style(self.db_compaction_style.into());
+
+        if let Some(write_buffer_size_mb) = self.db_write_buffer_size_mb {
+            opts.set_write_buffer_size(write_buffer_size_mb * 1024 * 1024);
+        }
 
-        let open_file_limit = Config::increase_open_file_limit();
-        let db_file_limit = Config::get_db_open_file_limit(open_file_limit);
-        // If the current limit is very large, set the DB limit using the ideal limit
-        let db_file_limit = db_file_limit.try_into().unwrap_or_else(|_| {
-            Config::get_db_open_file_limit(Config::IDEAL_OPEN_FILE_LIMIT)
-                .try_into()
-                .expect("ideal open file limit fits in a config int")
-        });
+        if let Some(block_cache_size_mb) = self.db_block_cache_size_mb {
+            let cache = rocksdb::Cache::new_lru_cache(block_cache_size_mb * 1024 * 1024);
+            let mut block_opts = rocksdb::BlockBasedOptions::default();
+            block_opts.set_block_cache(&cache);
+            opts.set_block_based_table_factory(&block_opts);
+        }
+
+        let db_file_limit = match self.db_max_open_files {
+            Some(max_open_files) => max_open_files,
+            None => {
+                let open_file_limit = Config::increase_open_file_limit();
+                let db_file_limit = Config::get_db_open_file_limit(open_file_limit);
+                // If the current limit is very large, set the DB limit using the ideal limit
+                db_file_limit.try_into().unwrap_or_else(|_| {
+                    Config::get_db_open_file_limit(Config::IDEAL_OPEN_FILE_LIMIT)
+                        .try_into()
+                        .expect("ideal open file limit fits in a config int")
+                })
+            }
+        };
         opts.set_max_open_files(db_file_limit);
 
         (path, opts)
@@ -118,6 +284,18 @@ impl Config {
         }
     }
 
+    /// Construct a config for an ephemeral, purely in-memory database.
+    ///
+    /// Suitable for tests and regtest, where the chain is short enough to fit
+    /// in RAM. See [`Config::ephemeral_in_memory`].
+    pub fn ephemeral_in_memory() -> Config {
+        Config {
+            ephemeral: true,
+            ephemeral_in_memory: true,
+            ..Config::default()
+        }
+    }
+
     /// Calculate the database's share of `open_file_limit`
     fn get_db_open_file_limit(open_file_limit: usize) -> usize {
         // Give the DB half the files, and reserve half the files for peers
@@ -266,7 +444,16 @@ impl Default for Config {
         Self {
             cache_dir,
             ephemeral: false,
+            ephemeral_in_memory: false,
             debug_stop_at_height: None,
+            transparent_indexing: false,
+            compress_blocks: false,
+            pruning: None,
+            db_block_cache_size_mb: None,
+            db_write_buffer_size_mb: None,
+            db_compaction_style: CompactionStyle::Level,
+            db_max_open_files: None,
+            finalized_block_write_batch_limit: 64,
         }
     }
 }
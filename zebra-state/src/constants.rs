@@ -13,8 +13,17 @@ pub const MIN_TRANSPARENT_COINBASE_MATURITY: u32 = 100;
 /// coinbase transactions.
 pub const MAX_BLOCK_REORG_HEIGHT: u32 = MIN_TRANSPARENT_COINBASE_MATURITY - 1;
 
-/// The database format version, incremented each time the database format changes.
-pub const DATABASE_FORMAT_VERSION: u32 = 4;
+/// The database format version, incremented each time the database format
+/// changes.
+///
+/// This is the single source of truth for the on-disk format version: it
+/// selects the `state/v{N}/{network}` cache directory in `Config::db_config`,
+/// and `import_snapshot` checks it against a snapshot's metadata file.
+/// Keeping these in one constant means a column family layout
+/// change always forces a resync into a fresh directory, instead of silently
+/// reusing an old directory and letting RocksDB fabricate the new column
+/// families empty.
+pub const DATABASE_FORMAT_VERSION: u32 = 6;
 
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -3,7 +3,12 @@ use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 
-use zebra_chain::{block, work::difficulty::CompactDifficulty};
+use zebra_chain::{
+    amount::{Amount, NegativeAllowed},
+    block,
+    value_balance::ValueBalanceError,
+    work::difficulty::CompactDifficulty,
+};
 
 /// A wrapper for type erased errors that is itself clonable and implements the
 /// Error trait
@@ -74,4 +79,15 @@ pub enum ValidateContextError {
         difficulty_threshold: CompactDifficulty,
         expected_difficulty: CompactDifficulty,
     },
+
+    #[error("block would take the {pool} chain value pool to a negative balance: {amount:?}")]
+    #[non_exhaustive]
+    NegativeValuePool {
+        pool: &'static str,
+        amount: Amount<NegativeAllowed>,
+    },
+
+    #[error("block contains an invalid value balance: {0}")]
+    #[non_exhaustive]
+    InvalidValueBalance(#[from] ValueBalanceError),
 }
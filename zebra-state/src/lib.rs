@@ -32,6 +32,9 @@ pub use config::Config;
 pub use constants::MAX_BLOCK_REORG_HEIGHT;
 pub use error::{BoxError, CloneError, CommitBlockError, ValidateContextError};
 pub use request::{FinalizedBlock, HashOrHeight, PreparedBlock, Request};
-pub use response::Response;
-pub use service::init;
+pub use response::{DatabaseColumnFamily, DatabaseLayout, Response, UtxoSetInfo};
+pub use service::{
+    check, compact_blocks, export_snapshot, import_snapshot, init, open_secondary, repair,
+    restore_backup, ConsistencyReport, ReadOnlyFinalizedState,
+};
 pub use utxo::Utxo;
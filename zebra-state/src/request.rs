@@ -1,8 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use zebra_chain::{
     block::{self, Block},
-    transaction, transparent,
+    orchard, sapling,
+    serialization::SerializationError,
+    sprout, transaction, transparent,
 };
 
 use crate::Utxo;
@@ -50,6 +52,22 @@ impl From<block::Height> for HashOrHeight {
     }
 }
 
+impl FromStr for HashOrHeight {
+    type Err = SerializationError;
+
+    /// Parses a decimal height, or a hex-encoded hash, as produced by their
+    /// respective `Display` impls.
+    ///
+    /// This lets callers such as RPC methods accept a single
+    /// "height or hash" string parameter, rather than forcing the caller to
+    /// decide up front which variant they have.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Height)
+            .or_else(|_| s.parse().map(Self::Hash))
+    }
+}
+
 /// A block which has undergone semantic validation and has been prepared for
 /// contextual validation.
 ///
@@ -230,7 +248,8 @@ pub enum Request {
     ///
     /// Returns
     ///
-    /// * [`Response::Transaction(Some(Arc<Transaction>))`](Response::Transaction) if the transaction is in the best chain;
+    /// * [`Response::Transaction(Some((transaction, height)))`](Response::Transaction)
+    ///   with its confirmation height, if the transaction is in the best chain;
     /// * [`Response::Transaction(None)`](Response::Transaction) otherwise.
     Transaction(transaction::Hash),
 
@@ -301,4 +320,114 @@ pub enum Request {
         /// Optionally, the hash of the last header to request.
         stop: Option<block::Hash>,
     },
+
+    /// Reports the on-disk column family layout of the finalized state
+    /// database: its format version, and the schema and approximate entry
+    /// count of each column family.
+    ///
+    /// Intended for debugging and introspection, not for consensus-critical
+    /// code.
+    ///
+    /// Returns [`Response::DatabaseLayout`](super::Response::DatabaseLayout).
+    DatabaseLayout,
+
+    /// Looks up the total balance of a transparent address's UTXOs in the
+    /// finalized state.
+    ///
+    /// Returns [`Response::AddressBalance`](super::Response::AddressBalance).
+    ///
+    /// Returns zero if the `transparent_indexing` option is disabled in the
+    /// [`Config`](crate::Config), rather than an error, because an explorer
+    /// querying an unindexed node should see "no balance", not a failure.
+    AddressBalance(transparent::Address),
+
+    /// Looks up the UTXOs currently indexed for a transparent address in the
+    /// finalized state.
+    ///
+    /// Returns [`Response::AddressUtxos`](super::Response::AddressUtxos).
+    ///
+    /// Returns an empty list if the `transparent_indexing` option is
+    /// disabled in the [`Config`](crate::Config).
+    AddressUtxos(transparent::Address),
+
+    /// Looks up the transaction that spent a transparent output, in the
+    /// finalized state.
+    ///
+    /// Returns [`Response::SpendingTransaction`](super::Response::SpendingTransaction)
+    /// with the spending transaction's hash and the index of the spending
+    /// input within it, if the output has been spent by a block in the
+    /// finalized state.
+    ///
+    /// Returns `None` if the output hasn't been spent, is unknown, or was
+    /// spent by a non-finalized block, and also if the `transparent_indexing`
+    /// option is disabled in the [`Config`](crate::Config).
+    SpendingTransaction(transparent::OutPoint),
+
+    /// Asks the state if the given Sprout note commitment tree anchor is known,
+    /// in the finalized or non-finalized state.
+    ///
+    /// Returns [`Response::AnchorExists`](super::Response::AnchorExists).
+    SproutAnchorExists(sprout::tree::Root),
+
+    /// Asks the state if the given Sapling note commitment tree anchor is known,
+    /// in the finalized or non-finalized state.
+    ///
+    /// Returns [`Response::AnchorExists`](super::Response::AnchorExists).
+    SaplingAnchorExists(sapling::tree::Root),
+
+    /// Asks the state if the given Orchard note commitment tree anchor is known,
+    /// in the finalized or non-finalized state.
+    ///
+    /// Returns [`Response::AnchorExists`](super::Response::AnchorExists).
+    OrchardAnchorExists(orchard::tree::Root),
+
+    /// Marks a non-finalized block hash as invalid, and reorgs away from it if
+    /// it's currently part of a known chain.
+    ///
+    /// Intended for consensus incident response, and for regtest testing of
+    /// reorg handling. Only affects the non-finalized, in-memory chains: a
+    /// block that has already been finalized to disk can't be invalidated.
+    ///
+    /// Returns [`Response::Invalidated`](super::Response::Invalidated).
+    InvalidateBlock(block::Hash),
+
+    /// Un-marks a block hash previously passed to
+    /// [`Request::InvalidateBlock`], so blocks with that hash can be
+    /// committed again.
+    ///
+    /// This doesn't revive any chain that was discarded when the block was
+    /// invalidated: the blocks that made it up need to be re-downloaded and
+    /// re-committed from scratch.
+    ///
+    /// Returns [`Response::Reconsidered`](super::Response::Reconsidered).
+    ReconsiderBlock(block::Hash),
+
+    /// Looks up the current chain value pool balances, for the best chain tip.
+    ///
+    /// The transparent pool component is always zero, because computing it
+    /// requires the values of spent transparent inputs, which aren't tracked
+    /// yet. The shielded pool components are the cumulative balance since
+    /// genesis, combining the finalized state's persisted balance with the
+    /// change caused by the non-finalized blocks in the best chain.
+    ///
+    /// Intended for a future `getblockchaininfo` `valuePools` field, not for
+    /// consensus-critical code.
+    ///
+    /// Returns [`Response::ChainValuePools`](super::Response::ChainValuePools).
+    ChainValuePools,
+
+    /// Computes summary statistics for the UTXO set in the finalized state:
+    /// the total number of UTXOs, their total transparent value, and a
+    /// digest of the set.
+    ///
+    /// This scans every entry in the `utxo_by_outpoint` column family, so it
+    /// is relatively expensive; it isn't updated incrementally as blocks are
+    /// committed. Intended for a future `gettxoutsetinfo` RPC, and for
+    /// auditing a node's UTXO set against another implementation's, not for
+    /// consensus-critical code.
+    ///
+    /// Doesn't include UTXOs created or spent by non-finalized blocks.
+    ///
+    /// Returns [`Response::UtxoSetInfo`](super::Response::UtxoSetInfo).
+    UtxoSetInfo,
 }
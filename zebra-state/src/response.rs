@@ -1,7 +1,10 @@
 use std::sync::Arc;
 use zebra_chain::{
+    amount::{Amount, NonNegative},
     block::{self, Block},
-    transaction::Transaction,
+    transaction::{Hash, Transaction},
+    transparent,
+    value_balance::ValueBalance,
 };
 
 use crate::Utxo;
@@ -27,8 +30,9 @@ pub enum Response {
     /// Response to [`Request::BlockLocator`] with a block locator object.
     BlockLocator(Vec<block::Hash>),
 
-    /// Response to [`Request::Transaction`] with the specified transaction.
-    Transaction(Option<Arc<Transaction>>),
+    /// Response to [`Request::Transaction`] with the specified transaction
+    /// and the height it was confirmed at.
+    Transaction(Option<(Arc<Transaction>, block::Height)>),
 
     /// Response to [`Request::Block`] with the specified block.
     Block(Option<Arc<Block>>),
@@ -41,4 +45,87 @@ pub enum Response {
 
     /// The response to a `FindBlockHeaders` request.
     BlockHeaders(Vec<block::CountedHeader>),
+
+    /// The response to a [`Request::DatabaseLayout`] request.
+    DatabaseLayout(DatabaseLayout),
+
+    /// The response to a [`Request::AddressBalance`] request.
+    AddressBalance(Amount<NonNegative>),
+
+    /// The response to a [`Request::AddressUtxos`] request.
+    AddressUtxos(Vec<(transparent::OutPoint, Utxo)>),
+
+    /// The response to a [`Request::SpendingTransaction`] request, with the
+    /// hash of the transaction that spent the output, and the index of the
+    /// spending input within it.
+    SpendingTransaction(Option<(Hash, u32)>),
+
+    /// The response to a [`Request::SproutAnchorExists`],
+    /// [`Request::SaplingAnchorExists`], or [`Request::OrchardAnchorExists`]
+    /// request.
+    AnchorExists(bool),
+
+    /// The response to a [`Request::ChainValuePools`] request.
+    ChainValuePools(ValueBalance),
+
+    /// The response to a [`Request::InvalidateBlock`] request, confirming
+    /// the block hash has been marked invalid.
+    Invalidated(block::Hash),
+
+    /// The response to a [`Request::ReconsiderBlock`] request, confirming
+    /// the block hash is no longer marked invalid.
+    Reconsidered(block::Hash),
+
+    /// The response to a [`Request::UtxoSetInfo`] request.
+    UtxoSetInfo(UtxoSetInfo),
+}
+
+/// Summary statistics for the finalized UTXO set, as reported by
+/// [`Response::UtxoSetInfo`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UtxoSetInfo {
+    /// The total number of UTXOs in the finalized state.
+    pub utxo_count: u64,
+
+    /// The total transparent value of every UTXO in the finalized state.
+    pub total_value: Amount<NonNegative>,
+
+    /// A SHA256d digest of the UTXO set, computed by hashing each
+    /// `(OutPoint, Utxo)` entry's on-disk encoding in the key order RocksDB
+    /// stores them in.
+    ///
+    /// This is stable across runs of the same UTXO set, but isn't a
+    /// consensus rule: it isn't comparable across different Zebra versions
+    /// if the on-disk encoding of `OutPoint` or `Utxo` ever changes.
+    pub digest: [u8; 32],
+}
+
+/// A description of the on-disk column family layout, generated from the
+/// same schema definition used to open the database.
+///
+/// Returned by [`Request::DatabaseLayout`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseLayout {
+    /// The current on-disk database format version.
+    pub format_version: u32,
+
+    /// The column families making up the database, in the order they are
+    /// opened in.
+    pub column_families: Vec<DatabaseColumnFamily>,
+}
+
+/// A description of a single column family, as reported by
+/// [`Response::DatabaseLayout`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseColumnFamily {
+    /// The column family's name, as passed to RocksDB.
+    pub name: String,
+
+    /// A human-readable description of the keys and values stored in this
+    /// column family.
+    pub schema: String,
+
+    /// An approximate count of the live entries in this column family, as
+    /// reported by RocksDB's `estimate-num-keys` property.
+    pub approximate_entries: u64,
 }
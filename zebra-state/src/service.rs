@@ -9,16 +9,18 @@ use std::{
 use check::difficulty::POW_MEDIAN_BLOCK_SPAN;
 use futures::future::FutureExt;
 use non_finalized_state::{NonFinalizedState, QueuedBlocks};
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, watch};
 use tower::{util::BoxService, Service};
 use tracing::instrument;
 use zebra_chain::{
     block::{self, Block},
+    orchard,
     parameters::Network,
     parameters::POW_AVERAGING_WINDOW,
-    transaction,
+    sapling, sprout, transaction,
     transaction::Transaction,
     transparent,
+    value_balance::ValueBalance,
 };
 
 use crate::{
@@ -33,7 +35,10 @@ mod pending_utxos;
 #[cfg(test)]
 mod tests;
 
-use self::{finalized_state::FinalizedState, pending_utxos::PendingUtxos};
+use self::{
+    finalized_state::{FinalizedState, ReadStateService},
+    pending_utxos::PendingUtxos,
+};
 
 pub type QueuedBlock = (
     PreparedBlock,
@@ -57,6 +62,9 @@ struct StateService {
     network: Network,
     /// Instant tracking the last time `pending_utxos` was pruned
     last_prune: Instant,
+    /// The sending half of the channel used to publish the current
+    /// non-finalized best chain to this service's [`ReadStateService`]s.
+    mem_watch_tx: watch::Sender<NonFinalizedState>,
 }
 
 impl StateService {
@@ -70,6 +78,7 @@ impl StateService {
         };
         let queued_blocks = QueuedBlocks::default();
         let pending_utxos = PendingUtxos::default();
+        let (mem_watch_tx, _) = watch::channel(mem.clone());
 
         Self {
             disk,
@@ -78,9 +87,20 @@ impl StateService {
             pending_utxos,
             network,
             last_prune: Instant::now(),
+            mem_watch_tx,
         }
     }
 
+    /// Returns a cloneable, read-only view of the chain state, for answering
+    /// queries without going through this service's request queue.
+    ///
+    /// The returned [`ReadStateService`] shares this service's database
+    /// handle, and tracks its non-finalized best chain, but reads are not
+    /// linearized with concurrent writes through this service.
+    pub fn read_state(&self) -> ReadStateService {
+        ReadStateService::new(self.disk.db_handle(), self.mem_watch_tx.subscribe())
+    }
+
     /// Queue a non finalized block for verification and check if any queued
     /// blocks are ready to be verified and committed to the state.
     ///
@@ -103,6 +123,12 @@ impl StateService {
             return rsp_rx;
         }
 
+        if self.mem.is_invalidated(&prepared.hash) {
+            let (rsp_tx, rsp_rx) = oneshot::channel();
+            let _ = rsp_tx.send(Err("block hash has been marked invalid".into()));
+            return rsp_rx;
+        }
+
         // Request::CommitBlock contract: a request to commit a block which has
         // been queued but not yet committed to the state fails the older
         // request and replaces it with the newer request.
@@ -123,8 +149,27 @@ impl StateService {
             return rsp_rx;
         }
 
+        let best_tip_before_commit = self.best_tip();
         self.process_queued(parent_hash);
 
+        if let (Some((_, old_best_tip_hash)), Some((new_best_tip_height, new_best_tip_hash))) =
+            (best_tip_before_commit, self.best_tip())
+        {
+            // If the previous best tip is no longer part of the best chain, the
+            // best chain switched to a different fork, rather than just being
+            // extended, so this is a reorganization.
+            if new_best_tip_hash != old_best_tip_hash
+                && self.best_height_by_hash(old_best_tip_hash).is_none()
+            {
+                tracing::info!(
+                    ?old_best_tip_hash,
+                    ?new_best_tip_hash,
+                    ?new_best_tip_height,
+                    "chain reorganization: the best chain tip switched to a different fork"
+                );
+            }
+        }
+
         while self.mem.best_chain_len() > crate::constants::MAX_BLOCK_REORG_HEIGHT {
             tracing::trace!("finalizing block past the reorg limit");
             let finalized = self.mem.finalize();
@@ -138,6 +183,11 @@ impl StateService {
             "Finalized state must have at least one block before committing non-finalized state",
         ));
 
+        // Notify any `ReadStateService`s of the (possibly unchanged)
+        // non-finalized best chain. Ignore errors: they just mean every
+        // receiver has been dropped.
+        let _ = self.mem_watch_tx.send(self.mem.clone());
+
         tracing::trace!("finished processing queued block");
         rsp_rx
     }
@@ -148,12 +198,23 @@ impl StateService {
         self.check_contextual_validity(&prepared)?;
         let parent_hash = prepared.block.header.previous_block_hash;
 
+        // The block is contextually valid and about to be committed, so any
+        // `Request::AwaitUtxo`s waiting on its outputs can now be resolved.
+        self.pending_utxos.check_against(&prepared.new_outputs);
+
+        let commit_start = std::time::Instant::now();
+
         if self.disk.finalized_tip_hash() == parent_hash {
             self.mem.commit_new_chain(prepared);
         } else {
             self.mem.commit_block(prepared);
         }
 
+        metrics::histogram!(
+            "state.memory.block.commit.latency.seconds",
+            commit_start.elapsed().as_secs_f64()
+        );
+
         Ok(())
     }
 
@@ -193,10 +254,14 @@ impl StateService {
         assert!(relevant_chain.len() >= POW_AVERAGING_WINDOW + POW_MEDIAN_BLOCK_SPAN,
                 "contextual validation requires at least 28 (POW_AVERAGING_WINDOW + POW_MEDIAN_BLOCK_SPAN) blocks");
 
+        let parent_hash = prepared.block.header.previous_block_hash;
+        let chain_value_pools = self.chain_value_pools_at(parent_hash);
+
         check::block_is_contextually_valid(
             prepared,
             self.network,
             self.disk.finalized_tip_height(),
+            chain_value_pools,
             relevant_chain,
         )?;
 
@@ -243,9 +308,12 @@ impl StateService {
             .or_else(|| self.disk.block(hash_or_height))
     }
 
-    /// Return the transaction identified by `hash` if it exists in the current
-    /// best chain.
-    pub fn best_transaction(&self, hash: transaction::Hash) -> Option<Arc<Transaction>> {
+    /// Return the transaction identified by `hash`, and the height it was
+    /// confirmed at, if it exists in the current best chain.
+    pub fn best_transaction(
+        &self,
+        hash: transaction::Hash,
+    ) -> Option<(Arc<Transaction>, block::Height)> {
         self.mem
             .best_transaction(hash)
             .or_else(|| self.disk.transaction(hash))
@@ -285,6 +353,49 @@ impl StateService {
             .or_else(|| self.disk.utxo(outpoint))
     }
 
+    /// Return `true` if `sprout_anchor` is a known Sprout note commitment tree
+    /// anchor, in the finalized or non-finalized state.
+    pub fn sprout_anchor_exists(&self, sprout_anchor: sprout::tree::Root) -> bool {
+        self.mem.any_sprout_anchor_contains(&sprout_anchor)
+            || self.disk.contains_sprout_anchor(&sprout_anchor)
+    }
+
+    /// Return `true` if `sapling_anchor` is a known Sapling note commitment
+    /// tree anchor, in the finalized or non-finalized state.
+    pub fn sapling_anchor_exists(&self, sapling_anchor: sapling::tree::Root) -> bool {
+        self.mem.any_sapling_anchor_contains(&sapling_anchor)
+            || self.disk.contains_sapling_anchor(&sapling_anchor)
+    }
+
+    /// Return `true` if `orchard_anchor` is a known Orchard note commitment
+    /// tree anchor, in the finalized or non-finalized state.
+    pub fn orchard_anchor_exists(&self, orchard_anchor: orchard::tree::Root) -> bool {
+        self.mem.any_orchard_anchor_contains(&orchard_anchor)
+            || self.disk.contains_orchard_anchor(&orchard_anchor)
+    }
+
+    /// Return the current chain value pool balances, for the best chain tip.
+    ///
+    /// See [`Request::ChainValuePools`] for the caveats that apply to this value.
+    pub fn chain_value_pools(&self) -> ValueBalance {
+        match self.best_tip() {
+            Some((_, hash)) => self.chain_value_pools_at(hash),
+            None => self.disk.finalized_value_pool(),
+        }
+    }
+
+    /// Returns the cumulative chain value pool balances since genesis, for
+    /// the chain with tip `parent_hash`: the finalized state's persisted
+    /// balance, plus the partial balance of any non-finalized blocks
+    /// building on top of it.
+    fn chain_value_pools_at(&self, parent_hash: block::Hash) -> ValueBalance {
+        let finalized_value_pools = self.disk.finalized_value_pool();
+        let non_finalized_value_pools = self.mem.chain_value_pools(parent_hash);
+
+        (finalized_value_pools + non_finalized_value_pools)
+            .expect("value pool changes have already been validated")
+    }
+
     /// Return an iterator over the relevant chain of the block identified by
     /// `hash`.
     ///
@@ -569,7 +680,9 @@ impl Service<Request> for StateService {
             Request::CommitBlock(prepared) => {
                 metrics::counter!("state.requests", 1, "type" => "commit_block");
 
-                self.pending_utxos.check_against(&prepared.new_outputs);
+                // `pending_utxos` is notified once the block is actually
+                // committed, in `validate_and_commit` below, not here: this
+                // block may still be queued behind a missing parent.
                 let rsp_rx = self.queue_and_commit_non_finalized(prepared);
 
                 async move {
@@ -586,8 +699,13 @@ impl Service<Request> for StateService {
 
                 let (rsp_tx, rsp_rx) = oneshot::channel();
 
-                self.pending_utxos.check_against(&finalized.new_outputs);
-                self.disk.queue_and_commit_finalized((finalized, rsp_tx));
+                // `pending_utxos` is only notified about outputs that are
+                // actually committed: `finalized` may still be queued behind
+                // a missing parent, so its own outputs might not be among
+                // them yet.
+                let newly_committed_outputs =
+                    self.disk.queue_and_commit_finalized((finalized, rsp_tx));
+                self.pending_utxos.check_against(&newly_committed_outputs);
 
                 async move {
                     rsp_rx
@@ -640,6 +758,85 @@ impl Service<Request> for StateService {
                     self.find_best_chain_hashes(known_blocks, stop, MAX_FIND_BLOCK_HASHES_RESULTS);
                 async move { Ok(Response::BlockHashes(res)) }.boxed()
             }
+            Request::DatabaseLayout => {
+                metrics::counter!("state.requests", 1, "type" => "database_layout");
+                let rsp = Ok(self.disk.database_layout()).map(Response::DatabaseLayout);
+                async move { rsp }.boxed()
+            }
+            Request::AddressBalance(address) => {
+                metrics::counter!("state.requests", 1, "type" => "address_balance");
+                // The transparent address index only covers the finalized state: a
+                // UTXO created or spent by a non-finalized block isn't reflected
+                // here until that block is finalized.
+                let rsp = Ok(self.disk.address_balance(&address)).map(Response::AddressBalance);
+                async move { rsp }.boxed()
+            }
+            Request::AddressUtxos(address) => {
+                metrics::counter!("state.requests", 1, "type" => "address_utxos");
+                let rsp = Ok(self.disk.address_utxos(&address)).map(Response::AddressUtxos);
+                async move { rsp }.boxed()
+            }
+            Request::SpendingTransaction(outpoint) => {
+                metrics::counter!("state.requests", 1, "type" => "spending_transaction");
+                // Like `AddressBalance`, this index only covers the finalized
+                // state: an output spent by a non-finalized block isn't
+                // reflected here until that block is finalized.
+                let rsp = Ok(self.disk.spending_transaction(&outpoint))
+                    .map(Response::SpendingTransaction);
+                async move { rsp }.boxed()
+            }
+            Request::SproutAnchorExists(sprout_anchor) => {
+                metrics::counter!("state.requests", 1, "type" => "sprout_anchor_exists");
+                let rsp = Ok(Response::AnchorExists(
+                    self.sprout_anchor_exists(sprout_anchor),
+                ));
+                async move { rsp }.boxed()
+            }
+            Request::SaplingAnchorExists(sapling_anchor) => {
+                metrics::counter!("state.requests", 1, "type" => "sapling_anchor_exists");
+                let rsp = Ok(Response::AnchorExists(
+                    self.sapling_anchor_exists(sapling_anchor),
+                ));
+                async move { rsp }.boxed()
+            }
+            Request::OrchardAnchorExists(orchard_anchor) => {
+                metrics::counter!("state.requests", 1, "type" => "orchard_anchor_exists");
+                let rsp = Ok(Response::AnchorExists(
+                    self.orchard_anchor_exists(orchard_anchor),
+                ));
+                async move { rsp }.boxed()
+            }
+            Request::ChainValuePools => {
+                metrics::counter!("state.requests", 1, "type" => "chain_value_pools");
+                let rsp = Ok(Response::ChainValuePools(self.chain_value_pools()));
+                async move { rsp }.boxed()
+            }
+            Request::InvalidateBlock(hash) => {
+                metrics::counter!("state.requests", 1, "type" => "invalidate_block");
+                self.mem.invalidate_block(hash);
+
+                // Notify any `ReadStateService`s of the (possibly changed)
+                // non-finalized best chain. Ignore errors: they just mean
+                // every receiver has been dropped.
+                let _ = self.mem_watch_tx.send(self.mem.clone());
+
+                let rsp = Ok(Response::Invalidated(hash));
+                async move { rsp }.boxed()
+            }
+            Request::ReconsiderBlock(hash) => {
+                metrics::counter!("state.requests", 1, "type" => "reconsider_block");
+                self.mem.reconsider_block(hash);
+                let rsp = Ok(Response::Reconsidered(hash));
+                async move { rsp }.boxed()
+            }
+            Request::UtxoSetInfo => {
+                metrics::counter!("state.requests", 1, "type" => "utxo_set_info");
+                // Like `AddressBalance`, this only covers the finalized state:
+                // UTXOs created or spent by non-finalized blocks aren't
+                // reflected here until those blocks are finalized.
+                let rsp = Ok(Response::UtxoSetInfo(self.disk.utxo_set_info()));
+                async move { rsp }.boxed()
+            }
             Request::FindBlockHeaders { known_blocks, stop } => {
                 const MAX_FIND_BLOCK_HEADERS_RESULTS: usize = 160;
                 // Zcashd will blindly request more block headers as long as it
@@ -679,3 +876,8 @@ impl Service<Request> for StateService {
 pub fn init(config: Config, network: Network) -> BoxService<Request, Response, BoxError> {
     BoxService::new(StateService::new(config, network))
 }
+
+pub use self::finalized_state::{
+    check, compact_blocks, export_snapshot, import_snapshot, open_secondary, repair,
+    restore_backup, ConsistencyReport, ReadOnlyFinalizedState,
+};
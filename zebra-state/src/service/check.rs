@@ -1,12 +1,14 @@
 //! Consensus critical contextual checks
 
-use std::borrow::Borrow;
+use std::{borrow::Borrow, convert::TryFrom};
 
 use chrono::Duration;
 use zebra_chain::{
+    amount::{Amount, NegativeAllowed},
     block::{self, Block},
     parameters::POW_AVERAGING_WINDOW,
     parameters::{Network, NetworkUpgrade},
+    value_balance::ValueBalance,
     work::difficulty::CompactDifficulty,
 };
 
@@ -37,6 +39,7 @@ pub(crate) fn block_is_contextually_valid<C>(
     prepared: &PreparedBlock,
     network: Network,
     finalized_tip_height: Option<block::Height>,
+    chain_value_pools: ValueBalance,
     relevant_chain: C,
 ) -> Result<(), ValidateContextError>
 where
@@ -82,6 +85,9 @@ where
         difficulty_adjustment,
     )?;
 
+    let block_value_pool_change = prepared.block.chain_value_pool_change()?;
+    check::chain_value_pools_are_valid(chain_value_pools, block_value_pool_change)?;
+
     // TODO: other contextual validation design and implementation
     Ok(())
 }
@@ -168,6 +174,42 @@ fn difficulty_threshold_is_valid(
     Ok(())
 }
 
+/// Returns `ValidateContextError::NegativeValuePool` if applying
+/// `block_value_pool_change` to `chain_value_pools` would take the Sprout,
+/// Sapling, or Orchard value pool negative.
+///
+/// The transparent pool isn't checked here, because
+/// [`Block::chain_value_pool_change`](block::Block::chain_value_pool_change)
+/// doesn't track it: see its documentation for details.
+fn chain_value_pools_are_valid(
+    chain_value_pools: ValueBalance,
+    block_value_pool_change: ValueBalance,
+) -> Result<(), ValidateContextError> {
+    let new_pools = (chain_value_pools + block_value_pool_change)?;
+    let zero = Amount::<NegativeAllowed>::try_from(0).expect("0 is always a valid Amount");
+
+    if new_pools.sprout() < zero {
+        Err(ValidateContextError::NegativeValuePool {
+            pool: "sprout",
+            amount: new_pools.sprout(),
+        })?
+    }
+    if new_pools.sapling() < zero {
+        Err(ValidateContextError::NegativeValuePool {
+            pool: "sapling",
+            amount: new_pools.sapling(),
+        })?
+    }
+    if new_pools.orchard() < zero {
+        Err(ValidateContextError::NegativeValuePool {
+            pool: "orchard",
+            amount: new_pools.orchard(),
+        })?
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -218,4 +260,38 @@ mod tests {
         height_one_more_than_parent_height(block::Height(500000), height)
             .expect_err("parent height is way more, should panic");
     }
+
+    #[test]
+    fn test_chain_value_pools_are_valid() {
+        zebra_test::init();
+
+        let zero = Amount::<NegativeAllowed>::try_from(0).expect("0 is always a valid Amount");
+        let sapling_balance =
+            Amount::<NegativeAllowed>::try_from(200).expect("valid test amount");
+
+        // The sapling pool has a positive balance from blocks that are
+        // already finalized: `chain_value_pools` is the cumulative balance
+        // since genesis, not just the change caused by this block.
+        let chain_value_pools =
+            ValueBalance::from_components(zero, zero, sapling_balance, zero);
+
+        // This block spends more sapling value than it was created with, but
+        // not more than the cumulative pool balance: a valid withdrawal of
+        // value that was shielded in an earlier, already-finalized block.
+        let spend_existing_balance = Amount::<NegativeAllowed>::try_from(-150)
+            .expect("valid test amount");
+        let block_value_pool_change =
+            ValueBalance::from_components(zero, zero, spend_existing_balance, zero);
+
+        chain_value_pools_are_valid(chain_value_pools, block_value_pool_change)
+            .expect("spending an already-finalized shielded balance should be valid");
+
+        // But a block that spends more than the cumulative balance allows
+        // must still be rejected.
+        let overspend = Amount::<NegativeAllowed>::try_from(-250).expect("valid test amount");
+        let block_value_pool_change = ValueBalance::from_components(zero, zero, overspend, zero);
+
+        chain_value_pools_are_valid(chain_value_pools, block_value_pool_change)
+            .expect_err("spending more than the cumulative balance should be invalid");
+    }
 }
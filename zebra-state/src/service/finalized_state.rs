@@ -1,19 +1,33 @@
 //! The primary implementation of the `zebra_state::Service` built upon rocksdb
 
 mod disk_format;
+pub(crate) mod read;
+pub(crate) mod schema;
 
-use std::{collections::HashMap, convert::TryInto, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, io::Write, path::Path, sync::Arc};
 
 use zebra_chain::transparent;
 use zebra_chain::{
+    amount::{Amount, NonNegative},
     block::{self, Block},
-    parameters::{Network, GENESIS_PREVIOUS_BLOCK_HASH},
+    orchard,
+    parameters::{genesis_hash, Network, GENESIS_PREVIOUS_BLOCK_HASH},
+    sapling, sprout,
     transaction::{self, Transaction},
+    value_balance::ValueBalance,
 };
 
-use crate::{BoxError, Config, FinalizedBlock, HashOrHeight, Utxo};
+use crate::{
+    BoxError, Config, DatabaseColumnFamily, DatabaseLayout, FinalizedBlock, HashOrHeight, Utxo,
+};
+
+use self::disk_format::{
+    AddressUtxoKey, DiskDeserialize, DiskSerialize, FromDisk, IntoDisk, SnapshotMetadata,
+    SpendingTransactionLocation, StoredBlock, TransactionLocation,
+};
+use self::schema::{COLUMN_FAMILIES, DATABASE_FORMAT_VERSION};
 
-use self::disk_format::{DiskDeserialize, DiskSerialize, FromDisk, IntoDisk, TransactionLocation};
+pub(crate) use self::read::ReadStateService;
 
 use super::QueuedFinalized;
 
@@ -27,24 +41,46 @@ pub struct FinalizedState {
     /// as a break in the graph.
     max_queued_height: f64,
 
-    db: rocksdb::DB,
+    /// The underlying database handle.
+    ///
+    /// Wrapped in an [`Arc`] so it can be shared with a [`ReadStateService`](
+    /// super::read::ReadStateService), which answers read-only queries directly
+    /// from the database without going through the [`StateService`](super::StateService).
+    db: Arc<rocksdb::DB>,
     ephemeral: bool,
+    /// Whether `db` was opened with an in-memory rocksdb `Env`, so there are
+    /// no on-disk files to delete. See [`Config::ephemeral_in_memory`].
+    ephemeral_in_memory: bool,
     /// Commit blocks to the finalized state up to this height, then exit Zebra.
     debug_stop_at_height: Option<block::Height>,
+    /// The network this state is for, used to derive transparent addresses
+    /// from output lock scripts when `transparent_indexing` is enabled.
+    network: Network,
+    /// Whether to maintain the `utxo_loc_by_transparent_addr_loc` index.
+    transparent_indexing: bool,
+    /// How many blocks below the tip to keep full block bodies for, or
+    /// `None` to keep every block body. See [`Config::pruning`].
+    pruning: Option<u32>,
+    /// The maximum number of queued blocks to commit in a single RocksDB
+    /// write batch. See [`Config::finalized_block_write_batch_limit`].
+    write_batch_limit: usize,
 }
 
 impl FinalizedState {
     pub fn new(config: &Config, network: Network) -> Self {
         let (path, db_options) = config.db_config(network);
-        let column_families = vec![
-            rocksdb::ColumnFamilyDescriptor::new("hash_by_height", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("height_by_hash", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("block_by_height", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("tx_by_hash", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("utxo_by_outpoint", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("sprout_nullifiers", db_options.clone()),
-            rocksdb::ColumnFamilyDescriptor::new("sapling_nullifiers", db_options.clone()),
-        ];
+        let column_families = schema::column_family_names()
+            .map(|name| {
+                let mut cf_options = db_options.clone();
+                // Block bodies are the only data compressible enough, and
+                // large enough, to be worth the CPU cost of zstd. See
+                // `Config::compress_blocks`.
+                if name == "block_by_height" && config.compress_blocks {
+                    cf_options.set_compression_type(rocksdb::DBCompressionType::Zstd);
+                }
+                rocksdb::ColumnFamilyDescriptor::new(name, cf_options)
+            })
+            .collect::<Vec<_>>();
         let db_result = rocksdb::DB::open_cf_descriptors(&db_options, &path, column_families);
 
         let db = match db_result {
@@ -64,11 +100,43 @@ impl FinalizedState {
         let new_state = Self {
             queued_by_prev_hash: HashMap::new(),
             max_queued_height: f64::NAN,
-            db,
+            db: Arc::new(db),
             ephemeral: config.ephemeral,
+            ephemeral_in_memory: config.ephemeral && config.ephemeral_in_memory,
             debug_stop_at_height: config.debug_stop_at_height.map(block::Height),
+            network,
+            transparent_indexing: config.transparent_indexing,
+            pruning: config.pruning,
+            write_batch_limit: config.finalized_block_write_batch_limit.max(1),
         };
 
+        // If the database already has a genesis block, make sure it's the
+        // genesis block for the configured `network`. This catches the case
+        // where a cache directory is reused across mainnet and testnet (or
+        // vice versa), which would otherwise silently build a chain on top
+        // of the wrong genesis block.
+        //
+        // We don't *write* the genesis block here: Zebra still downloads and
+        // verifies it like any other block, via `zebra-consensus`'s
+        // checkpoint list. Writing it here would mean embedding the full
+        // serialized genesis block bytes in this crate, and today those
+        // bytes only exist as test vectors in the `zebra-test` dev-dependency,
+        // not in a crate `zebra-state` can depend on at runtime.
+        if let Some(genesis) = new_state.hash(block::Height(0)) {
+            let expected_genesis = genesis_hash(network);
+
+            assert_eq!(
+                genesis, expected_genesis,
+                "Zebra's state cache is for the wrong network: \
+                 expected the {:?} genesis block {:?}, but the cache at {:?} has {:?} at height 0. \
+                 Hint: Delete the cache, or point `state.cache_dir` at a fresh directory.",
+                network,
+                expected_genesis,
+                path,
+                genesis,
+            );
+        }
+
         if let Some(tip_height) = new_state.finalized_tip_height() {
             if new_state.is_at_stop_height(tip_height) {
                 let debug_stop_at_height = new_state
@@ -103,6 +171,38 @@ impl FinalizedState {
         new_state
     }
 
+    /// Opens the on-disk finalized state at `config`'s `cache_dir` as a
+    /// read-only RocksDB "secondary" instance, rather than the read-write
+    /// "primary" instance a running node opens with [`FinalizedState::new`].
+    ///
+    /// This lets a separate process (an indexer, a debugging tool, or
+    /// `zebra-utils`) read a live node's database without stopping it, and
+    /// without contending with it for the lock RocksDB takes on the primary's
+    /// database directory.
+    ///
+    /// `secondary_path` is a private directory for the secondary instance's
+    /// own metadata. It can be a temporary directory: it doesn't need to be
+    /// backed up, and isn't read by the primary.
+    pub fn new_read_only(
+        config: &Config,
+        network: Network,
+        secondary_path: &Path,
+    ) -> Result<ReadOnlyFinalizedState, BoxError> {
+        let (primary_path, db_options) = config.db_config(network);
+        let column_families = schema::column_family_names()
+            .map(|name| rocksdb::ColumnFamilyDescriptor::new(name, db_options.clone()))
+            .collect::<Vec<_>>();
+
+        let db = rocksdb::DB::open_cf_descriptors_as_secondary(
+            &db_options,
+            &primary_path,
+            secondary_path,
+            column_families,
+        )?;
+
+        Ok(ReadOnlyFinalizedState { db: Arc::new(db) })
+    }
+
     /// Stop the process if `block_height` is greater than or equal to the
     /// configured stop height.
     fn is_at_stop_height(&self, block_height: block::Height) -> bool {
@@ -122,15 +222,47 @@ impl FinalizedState {
     ///
     /// After queueing a finalized block, this method checks whether the newly
     /// queued block (and any of its descendants) can be committed to the state.
-    pub fn queue_and_commit_finalized(&mut self, queued: QueuedFinalized) {
+    ///
+    /// Returns the transparent outputs that were newly committed to the state
+    /// by this call, so that callers can notify anything waiting on them
+    /// (for example, a [`Request::AwaitUtxo`](crate::Request::AwaitUtxo)).
+    /// This does *not* include the outputs of `queued`, unless `queued` (or
+    /// one of the blocks already in the queue) was actually committed.
+    pub fn queue_and_commit_finalized(
+        &mut self,
+        queued: QueuedFinalized,
+    ) -> HashMap<transparent::OutPoint, Utxo> {
         let prev_hash = queued.0.block.header.previous_block_hash;
         let height = queued.0.height;
         self.queued_by_prev_hash.insert(prev_hash, queued);
 
-        while let Some(queued_block) = self.queued_by_prev_hash.remove(&self.finalized_tip_hash()) {
-            self.commit_finalized(queued_block);
-            metrics::counter!("state.finalized.committed.block.count", 1);
-            metrics::gauge!("state.finalized.committed.block.height", height.0 as _);
+        // Collect the whole run of newly-ready blocks before committing any
+        // of them, so that a run unblocked by a single arriving ancestor
+        // (the common case during initial block download) can be written in
+        // batches, rather than one RocksDB write per block.
+        let mut ready = Vec::new();
+        let mut expected_prev_hash = self.finalized_tip_hash();
+        while let Some(queued_block) = self.queued_by_prev_hash.remove(&expected_prev_hash) {
+            expected_prev_hash = queued_block.0.hash;
+            ready.push(queued_block);
+        }
+
+        let mut newly_committed_outputs = HashMap::new();
+        let mut ready = ready.into_iter();
+        loop {
+            let batch: Vec<QueuedFinalized> =
+                ready.by_ref().take(self.write_batch_limit).collect();
+            if batch.is_empty() {
+                break;
+            }
+
+            let batch_len = batch.len();
+            let batch_height = batch.last().expect("batch is non-empty").0.height;
+
+            newly_committed_outputs.extend(self.commit_finalized_batch(batch));
+
+            metrics::counter!("state.finalized.committed.block.count", batch_len as u64);
+            metrics::gauge!("state.finalized.committed.block.height", batch_height.0 as _);
         }
 
         if self.queued_by_prev_hash.is_empty() {
@@ -149,6 +281,14 @@ impl FinalizedState {
             "state.finalized.queued.block.count",
             self.queued_by_prev_hash.len() as f64
         );
+
+        newly_committed_outputs
+    }
+
+    /// Returns a clone of the shared database handle, for use by a
+    /// [`ReadStateService`](super::read::ReadStateService).
+    pub(super) fn db_handle(&self) -> Arc<rocksdb::DB> {
+        self.db.clone()
     }
 
     /// Returns the hash of the current finalized tip block.
@@ -164,6 +304,62 @@ impl FinalizedState {
         self.tip().map(|(height, _)| height)
     }
 
+    /// Returns the cumulative Sprout, Sapling, and Orchard value pool
+    /// balances, from genesis up to and including the current finalized
+    /// tip, or [`ValueBalance::zero`] if the state is empty.
+    pub fn finalized_value_pool(&self) -> ValueBalance {
+        let value_pool_by_height = self.db.cf_handle("value_pool_by_height").unwrap();
+
+        self.finalized_tip_height()
+            .and_then(|tip_height| self.db.zs_get(value_pool_by_height, &tip_height))
+            .unwrap_or_else(ValueBalance::zero)
+    }
+
+    /// Records metrics tracking block interval and difficulty history, for
+    /// dashboards that plot how these change over time.
+    fn chain_history_metrics(&self, finalized: &FinalizedBlock) {
+        let header = &finalized.block.header;
+
+        if let Some(work) = header.difficulty_threshold.to_work() {
+            metrics::gauge!("state.finalized.block.work", work.as_u128() as f64);
+        }
+
+        if let Some(previous_height) = finalized.height.0.checked_sub(1).map(block::Height) {
+            if let Some(previous_block) = self.block(previous_height.into()) {
+                let interval_seconds =
+                    (header.time - previous_block.header.time).num_seconds() as f64;
+                metrics::gauge!("state.finalized.block.interval_seconds", interval_seconds);
+            }
+        }
+
+        let tip_age_seconds = (chrono::Utc::now() - header.time).num_seconds() as f64;
+        metrics::gauge!("state.finalized.tip.age_seconds", tip_age_seconds);
+    }
+
+    /// Records a gauge for the approximate on-disk size of each column family,
+    /// for dashboards that plot how the database grows over time.
+    fn disk_size_metrics(&self) {
+        for cf in COLUMN_FAMILIES {
+            let handle = self
+                .db
+                .cf_handle(cf.name)
+                .expect("column families are opened from the same schema list");
+
+            let size_bytes = self
+                .db
+                .property_int_value_cf(handle, "rocksdb.total-sst-files-size")
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+
+            metrics::gauge!(
+                "state.finalized.cf.size_bytes",
+                size_bytes as f64,
+                "cf" => cf.name
+            );
+        }
+    }
+
     fn is_empty(&self, cf: &rocksdb::ColumnFamily) -> bool {
         // use iterator to check if it's empty
         !self
@@ -172,13 +368,127 @@ impl FinalizedState {
             .valid()
     }
 
+    /// Returns a description of the column families backing this database,
+    /// generated from the same [`schema`] used to open them, along with an
+    /// approximate live entry count for each.
+    pub fn database_layout(&self) -> DatabaseLayout {
+        let column_families = COLUMN_FAMILIES
+            .iter()
+            .map(|cf| {
+                let handle = self
+                    .db
+                    .cf_handle(cf.name)
+                    .expect("column families are opened from the same schema list");
+
+                // This is an estimate: RocksDB doesn't track exact live key
+                // counts without a full scan.
+                let approximate_entries = self
+                    .db
+                    .property_int_value_cf(handle, "rocksdb.estimate-num-keys")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+
+                DatabaseColumnFamily {
+                    name: cf.name.to_string(),
+                    schema: cf.schema.to_string(),
+                    approximate_entries,
+                }
+            })
+            .collect();
+
+        DatabaseLayout {
+            format_version: DATABASE_FORMAT_VERSION,
+            column_families,
+        }
+    }
+
     /// Immediately commit `finalized` to the finalized state.
     pub fn commit_finalized_direct(
         &mut self,
         finalized: FinalizedBlock,
     ) -> Result<block::Hash, BoxError> {
+        let hash = finalized.hash;
+        let expected_height = match self.finalized_tip_height() {
+            Some(tip_height) => (tip_height + 1).expect("height fits in a u32"),
+            None => block::Height(0),
+        };
+        let expected_previous_hash = self.finalized_tip_hash();
+        let expected_chain_value_pool = self.finalized_value_pool();
+
         block_precommit_metrics(&finalized);
+        self.chain_history_metrics(&finalized);
+
+        let mut batch = rocksdb::WriteBatch::default();
+        self.prepare_commit(
+            &mut batch,
+            &finalized,
+            expected_height,
+            expected_previous_hash,
+            expected_chain_value_pool,
+            &HashMap::new(),
+        );
+
+        let commit_start = std::time::Instant::now();
+        let result = self.db.write(batch).map(|()| hash);
+        metrics::histogram!(
+            "state.finalized.block.commit.latency.seconds",
+            commit_start.elapsed().as_secs_f64()
+        );
 
+        if result.is_ok() {
+            self.disk_size_metrics();
+
+            if let Some(retention_depth) = self.pruning {
+                if let Some(prune_height) = expected_height.0.checked_sub(retention_depth) {
+                    self.prune_block_body(block::Height(prune_height));
+                }
+            }
+
+            if self.is_at_stop_height(expected_height) {
+                tracing::info!(height = ?expected_height, ?hash, "stopping at configured height");
+                // We'd like to drop the database here, because that closes the
+                // column families and the database. But Rust's ownership rules
+                // make that difficult, so we just flush instead.
+                self.db.flush().expect("flush is successful");
+                self.delete_ephemeral();
+                std::process::exit(0);
+            }
+        }
+
+        result.map_err(Into::into)
+    }
+
+    /// Builds the RocksDB operations needed to commit `finalized` into
+    /// `batch`, without writing `batch` to the database.
+    ///
+    /// `expected_height` and `expected_previous_hash` are the height and
+    /// parent hash `finalized` must have to extend the state. They're
+    /// threaded through explicitly, rather than read from
+    /// [`FinalizedState::finalized_tip_height`]/[`FinalizedState::finalized_tip_hash`],
+    /// so that [`FinalizedState::commit_finalized_batch`] can prepare several
+    /// blocks into the same `batch` before any of them are actually written.
+    ///
+    /// `pending_outputs` is an overlay of transparent outputs created by
+    /// blocks already prepared into `batch` but not yet on disk, so that a
+    /// block in the batch can spend an output created earlier in the same
+    /// batch.
+    ///
+    /// `expected_chain_value_pool` is the cumulative Sprout, Sapling, and
+    /// Orchard value pool balance up to (but not including) `finalized`,
+    /// threaded through the same way as `expected_height` and
+    /// `expected_previous_hash`, so that a multi-block batch doesn't need to
+    /// re-read the previous block's balance from disk. Returns the updated
+    /// cumulative balance, including `finalized`.
+    fn prepare_commit(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        finalized: &FinalizedBlock,
+        expected_height: block::Height,
+        expected_previous_hash: block::Hash,
+        expected_chain_value_pool: ValueBalance,
+        pending_outputs: &HashMap<transparent::OutPoint, Utxo>,
+    ) -> ValueBalance {
         let FinalizedBlock {
             block,
             hash,
@@ -186,6 +496,8 @@ impl FinalizedState {
             new_outputs,
             transaction_hashes,
         } = finalized;
+        let hash = *hash;
+        let height = *height;
 
         let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
         let height_by_hash = self.db.cf_handle("height_by_hash").unwrap();
@@ -194,9 +506,21 @@ impl FinalizedState {
         let utxo_by_outpoint = self.db.cf_handle("utxo_by_outpoint").unwrap();
         let sprout_nullifiers = self.db.cf_handle("sprout_nullifiers").unwrap();
         let sapling_nullifiers = self.db.cf_handle("sapling_nullifiers").unwrap();
+        let sprout_anchors = self.db.cf_handle("sprout_anchors").unwrap();
+        let sapling_anchors = self.db.cf_handle("sapling_anchors").unwrap();
+        let orchard_anchors = self.db.cf_handle("orchard_anchors").unwrap();
+        let value_pool_by_height = self.db.cf_handle("value_pool_by_height").unwrap();
+        let utxo_loc_by_transparent_addr_loc = self
+            .db
+            .cf_handle("utxo_loc_by_transparent_addr_loc")
+            .unwrap();
+        let spending_tx_loc_by_outpoint = self
+            .db
+            .cf_handle("spending_tx_loc_by_outpoint")
+            .unwrap();
 
         // Assert that callers (including unit tests) get the chain order correct
-        if self.is_empty(hash_by_height) {
+        if expected_height == block::Height(0) {
             assert_eq!(
                 block::Hash([0; 32]),
                 block.header.previous_block_hash,
@@ -209,110 +533,251 @@ impl FinalizedState {
             );
         } else {
             assert_eq!(
-                self.finalized_tip_height()
-                    .expect("state must have a genesis block committed")
-                    + 1,
-                Some(height),
+                expected_height, height,
                 "committed block height must be 1 more than the finalized tip height"
             );
 
             assert_eq!(
-                self.finalized_tip_hash(),
-                block.header.previous_block_hash,
+                expected_previous_hash, block.header.previous_block_hash,
                 "committed block must be a child of the finalized tip"
             );
         }
 
-        // We use a closure so we can use an early return for control flow in
-        // the genesis case
-        let prepare_commit = || -> rocksdb::WriteBatch {
-            let mut batch = rocksdb::WriteBatch::default();
+        // Index the block
+        batch.zs_insert(hash_by_height, height, hash);
+        batch.zs_insert(height_by_hash, hash, height);
+        batch.zs_insert(block_by_height, height, StoredBlock::Full(Arc::clone(block)));
+
+        // TODO: sprout and sapling anchors (per block)
+
+        // Consensus-critical bug in zcashd: transactions in the
+        // genesis block are ignored, so they don't contribute to the
+        // cumulative value pool balance either.
+        let chain_value_pool = if block.header.previous_block_hash == block::Hash([0; 32]) {
+            expected_chain_value_pool
+        } else {
+            let block_value_pool_change = block
+                .chain_value_pool_change()
+                .expect("value balances have already been validated");
+            (expected_chain_value_pool + block_value_pool_change)
+                .expect("value pool changes have already been validated")
+        };
+        batch.zs_insert(value_pool_by_height, height, chain_value_pool);
 
-            // Index the block
-            batch.zs_insert(hash_by_height, height, hash);
-            batch.zs_insert(height_by_hash, hash, height);
-            batch.zs_insert(block_by_height, height, &block);
+        if block.header.previous_block_hash == block::Hash([0; 32]) {
+            return chain_value_pool;
+        }
 
-            // TODO: sprout and sapling anchors (per block)
+        // Index all new transparent outputs
+        for (outpoint, utxo) in new_outputs.iter() {
+            batch.zs_insert(utxo_by_outpoint, *outpoint, utxo);
 
-            // Consensus-critical bug in zcashd: transactions in the
-            // genesis block are ignored.
-            if block.header.previous_block_hash == block::Hash([0; 32]) {
-                return batch;
+            if self.transparent_indexing {
+                if let Some(address) = utxo.output.lock_script.address(self.network) {
+                    batch.zs_insert(
+                        utxo_loc_by_transparent_addr_loc,
+                        AddressUtxoKey {
+                            address,
+                            outpoint: *outpoint,
+                        },
+                        (),
+                    );
+                }
             }
+        }
 
-            // Index all new transparent outputs
-            for (outpoint, utxo) in new_outputs.into_iter() {
-                batch.zs_insert(utxo_by_outpoint, outpoint, utxo);
-            }
+        // Index each transaction, spent inputs, nullifiers
+        // TODO: move computation into FinalizedBlock as with transparent outputs
+        for (transaction_index, (transaction, transaction_hash)) in block
+            .transactions
+            .iter()
+            .zip(transaction_hashes.iter())
+            .enumerate()
+        {
+            let transaction_location = TransactionLocation {
+                height,
+                index: transaction_index
+                    .try_into()
+                    .expect("no more than 4 billion transactions per block"),
+            };
+            batch.zs_insert(tx_by_hash, *transaction_hash, transaction_location);
+
+            // Mark all transparent inputs as spent
+            for (input_index, input) in transaction.inputs().iter().enumerate() {
+                match input {
+                    transparent::Input::PrevOut { outpoint, .. } => {
+                        if self.transparent_indexing {
+                            // The spent output is either already on disk, was
+                            // just created earlier in this same block, or was
+                            // created by an earlier block in the same batch.
+                            let spent_utxo = new_outputs
+                                .get(outpoint)
+                                .cloned()
+                                .or_else(|| pending_outputs.get(outpoint).cloned())
+                                .or_else(|| self.utxo(outpoint));
+
+                            if let Some(spent_utxo) = spent_utxo {
+                                if let Some(address) =
+                                    spent_utxo.output.lock_script.address(self.network)
+                                {
+                                    batch.delete_cf(
+                                        utxo_loc_by_transparent_addr_loc,
+                                        AddressUtxoKey {
+                                            address,
+                                            outpoint: *outpoint,
+                                        }
+                                        .as_bytes(),
+                                    );
+                                }
+                            }
+                        }
 
-            // Index each transaction, spent inputs, nullifiers
-            // TODO: move computation into FinalizedBlock as with transparent outputs
-            for (transaction_index, (transaction, transaction_hash)) in block
-                .transactions
-                .iter()
-                .zip(transaction_hashes.into_iter())
-                .enumerate()
-            {
-                let transaction_location = TransactionLocation {
-                    height,
-                    index: transaction_index
-                        .try_into()
-                        .expect("no more than 4 billion transactions per block"),
-                };
-                batch.zs_insert(tx_by_hash, transaction_hash, transaction_location);
-
-                // Mark all transparent inputs as spent
-                for input in transaction.inputs() {
-                    match input {
-                        transparent::Input::PrevOut { outpoint, .. } => {
-                            batch.delete_cf(utxo_by_outpoint, outpoint.as_bytes());
+                        if self.transparent_indexing {
+                            batch.zs_insert(
+                                spending_tx_loc_by_outpoint,
+                                *outpoint,
+                                SpendingTransactionLocation {
+                                    hash: *transaction_hash,
+                                    input_index: input_index
+                                        .try_into()
+                                        .expect("no more than 4 billion inputs per transaction"),
+                                },
+                            );
                         }
-                        // Coinbase inputs represent new coins,
-                        // so there are no UTXOs to mark as spent.
-                        transparent::Input::Coinbase { .. } => {}
+
+                        batch.delete_cf(utxo_by_outpoint, outpoint.as_bytes());
                     }
+                    // Coinbase inputs represent new coins,
+                    // so there are no UTXOs to mark as spent.
+                    transparent::Input::Coinbase { .. } => {}
                 }
+            }
 
-                // Mark sprout and sapling nullifiers as spent
-                for sprout_nullifier in transaction.sprout_nullifiers() {
-                    batch.zs_insert(sprout_nullifiers, sprout_nullifier, ());
-                }
-                for sapling_nullifier in transaction.sapling_nullifiers() {
-                    batch.zs_insert(sapling_nullifiers, sapling_nullifier, ());
-                }
+            // Mark sprout and sapling nullifiers as spent
+            for sprout_nullifier in transaction.sprout_nullifiers() {
+                batch.zs_insert(sprout_nullifiers, sprout_nullifier, ());
+            }
+            for sapling_nullifier in transaction.sapling_nullifiers() {
+                batch.zs_insert(sapling_nullifiers, sapling_nullifier, ());
+            }
+
+            // Record the note commitment tree anchors used by this transaction,
+            // so later transactions and blocks can be checked against them
+            for sprout_anchor in transaction.sprout_anchors() {
+                batch.zs_insert(sprout_anchors, sprout_anchor, ());
+            }
+            for sapling_anchor in transaction.sapling_anchors() {
+                batch.zs_insert(sapling_anchors, sapling_anchor, ());
+            }
+            if let Some(orchard_anchor) = transaction.orchard_anchor() {
+                batch.zs_insert(orchard_anchors, orchard_anchor, ());
             }
+        }
+
+        chain_value_pool
+    }
 
-            batch
+    /// Commits a run of queued, contiguous finalized blocks to the state in a
+    /// single RocksDB write batch.
+    ///
+    /// It's the caller's responsibility to ensure that `queued_blocks` are
+    /// contiguous and in order. This function is called by
+    /// [`FinalizedState::queue_and_commit_finalized`], which ensures order,
+    /// and splits large runs into batches of at most
+    /// [`FinalizedState::write_batch_limit`] blocks.
+    ///
+    /// Returns the transparent outputs of every block in `queued_blocks`
+    /// that was actually committed, or an empty [`HashMap`] if the whole
+    /// batch failed to commit: a batch is written to RocksDB atomically, so
+    /// either all of its blocks are committed, or none of them are.
+    fn commit_finalized_batch(
+        &mut self,
+        queued_blocks: Vec<QueuedFinalized>,
+    ) -> HashMap<transparent::OutPoint, Utxo> {
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut pending_outputs = HashMap::new();
+        let mut expected_height = match self.finalized_tip_height() {
+            Some(tip_height) => (tip_height + 1).expect("height fits in a u32"),
+            None => block::Height(0),
         };
+        let mut expected_previous_hash = self.finalized_tip_hash();
+        let mut expected_chain_value_pool = self.finalized_value_pool();
 
-        let batch = prepare_commit();
+        for (finalized, _) in queued_blocks.iter() {
+            block_precommit_metrics(finalized);
+            self.chain_history_metrics(finalized);
 
-        let result = self.db.write(batch).map(|()| hash);
+            expected_chain_value_pool = self.prepare_commit(
+                &mut batch,
+                finalized,
+                expected_height,
+                expected_previous_hash,
+                expected_chain_value_pool,
+                &pending_outputs,
+            );
+
+            for input in finalized.block.transactions.iter().flat_map(|t| t.inputs()) {
+                if let transparent::Input::PrevOut { outpoint, .. } = input {
+                    pending_outputs.remove(outpoint);
+                }
+            }
+            pending_outputs.extend(finalized.new_outputs.clone());
 
-        if result.is_ok() && self.is_at_stop_height(height) {
-            tracing::info!(?height, ?hash, "stopping at configured height");
-            // We'd like to drop the database here, because that closes the
-            // column families and the database. But Rust's ownership rules
-            // make that difficult, so we just flush instead.
-            self.db.flush().expect("flush is successful");
-            self.delete_ephemeral();
-            std::process::exit(0);
+            expected_previous_hash = finalized.hash;
+            expected_height = (expected_height + 1).unwrap_or(expected_height);
         }
 
-        result.map_err(Into::into)
-    }
+        let commit_start = std::time::Instant::now();
+        let result = self.db.write(batch);
+        metrics::histogram!(
+            "state.finalized.block.commit.latency.seconds",
+            commit_start.elapsed().as_secs_f64()
+        );
 
-    /// Commit a finalized block to the state.
-    ///
-    /// It's the caller's responsibility to ensure that blocks are committed in
-    /// order. This function is called by [`queue`], which ensures order.
-    /// It is intentionally not exposed as part of the public API of the
-    /// [`FinalizedState`].
-    fn commit_finalized(&mut self, queued_block: QueuedFinalized) {
-        let (finalized, rsp_tx) = queued_block;
-        let result = self.commit_finalized_direct(finalized);
-        let _ = rsp_tx.send(result.map_err(Into::into));
+        if result.is_ok() {
+            self.disk_size_metrics();
+        }
+
+        let mut newly_committed_outputs = HashMap::new();
+
+        for (finalized, rsp_tx) in queued_blocks {
+            let height = finalized.height;
+            let hash = finalized.hash;
+
+            let block_result: Result<block::Hash, BoxError> = match &result {
+                Ok(()) => Ok(hash),
+                Err(error) => Err(error.to_string().into()),
+            };
+
+            let mut stop_here = false;
+
+            if result.is_ok() {
+                newly_committed_outputs.extend(finalized.new_outputs.clone());
+
+                if let Some(retention_depth) = self.pruning {
+                    if let Some(prune_height) = height.0.checked_sub(retention_depth) {
+                        self.prune_block_body(block::Height(prune_height));
+                    }
+                }
+
+                stop_here = self.is_at_stop_height(height);
+            }
+
+            let _ = rsp_tx.send(block_result);
+
+            if stop_here {
+                tracing::info!(?height, ?hash, "stopping at configured height");
+                // We'd like to drop the database here, because that closes
+                // the column families and the database. But Rust's
+                // ownership rules make that difficult, so we just flush
+                // instead.
+                self.db.flush().expect("flush is successful");
+                self.delete_ephemeral();
+                std::process::exit(0);
+            }
+        }
+
+        newly_committed_outputs
     }
 
     /// Returns the tip height and hash if there is one.
@@ -335,8 +800,77 @@ impl FinalizedState {
         self.db.zs_get(&height_by_hash, &hash)
     }
 
-    /// Returns the given block if it exists.
+    /// Returns the given block if it exists, and its body hasn't been pruned.
     pub fn block(&self, hash_or_height: HashOrHeight) -> Option<Arc<Block>> {
+        self.stored_block(hash_or_height)?.block()
+    }
+
+    /// Returns the header of the given block if it exists, whether or not its
+    /// body has been pruned.
+    pub fn block_header(&self, hash_or_height: HashOrHeight) -> Option<block::Header> {
+        Some(self.stored_block(hash_or_height)?.header())
+    }
+
+    /// Returns an iterator over the blocks in `start..=end`, reading
+    /// lazily from the database rather than collecting the whole range
+    /// into memory up front.
+    ///
+    /// Skips heights whose block body has been pruned (see
+    /// [`Config::pruning`]), rather than stopping the iteration. Use
+    /// [`FinalizedState::headers_in_range`] to read headers for a range that
+    /// includes pruned blocks.
+    ///
+    /// Useful for bootstrap export, reindexing, and lightwalletd-style bulk
+    /// readers that only need to walk the chain once, in height order,
+    /// without holding every block of the range in memory at the same time.
+    /// RocksDB's reads are synchronous, like the rest of this type's read
+    /// methods, so this returns a plain [`Iterator`] rather than a
+    /// [`futures::Stream`](https://docs.rs/futures/*/futures/stream/trait.Stream.html);
+    /// wrap it in `futures::stream::iter` if an async caller needs one.
+    pub fn blocks_in_range(
+        &self,
+        start: block::Height,
+        end: block::Height,
+    ) -> impl Iterator<Item = Arc<Block>> + '_ {
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+        let start_bytes = start.as_bytes();
+
+        self.db
+            .iterator_cf(
+                block_by_height,
+                rocksdb::IteratorMode::From(&start_bytes, rocksdb::Direction::Forward),
+            )
+            .take_while(move |(height_bytes, _)| block::Height::from_bytes(height_bytes) <= end)
+            .filter_map(|(_, block_bytes)| StoredBlock::from_bytes(block_bytes).block())
+    }
+
+    /// Returns an iterator over the headers of the blocks in `start..=end`,
+    /// reading lazily from the database rather than collecting the whole
+    /// range into memory up front.
+    ///
+    /// Unlike [`FinalizedState::blocks_in_range`], this also returns headers
+    /// for blocks whose body has been pruned, since headers are never
+    /// pruned. See that method for why this returns an [`Iterator`] rather
+    /// than a [`futures::Stream`](https://docs.rs/futures/*/futures/stream/trait.Stream.html).
+    pub fn headers_in_range(
+        &self,
+        start: block::Height,
+        end: block::Height,
+    ) -> impl Iterator<Item = block::Header> + '_ {
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+        let start_bytes = start.as_bytes();
+
+        self.db
+            .iterator_cf(
+                block_by_height,
+                rocksdb::IteratorMode::From(&start_bytes, rocksdb::Direction::Forward),
+            )
+            .take_while(move |(height_bytes, _)| block::Height::from_bytes(height_bytes) <= end)
+            .map(|(_, block_bytes)| StoredBlock::from_bytes(block_bytes).header())
+    }
+
+    /// Returns the [`StoredBlock`] for the given block, if it exists.
+    fn stored_block(&self, hash_or_height: HashOrHeight) -> Option<StoredBlock> {
         let height_by_hash = self.db.cf_handle("height_by_hash").unwrap();
         let block_by_height = self.db.cf_handle("block_by_height").unwrap();
         let height = hash_or_height.height_or_else(|hash| self.db.zs_get(height_by_hash, &hash))?;
@@ -344,6 +878,26 @@ impl FinalizedState {
         self.db.zs_get(block_by_height, &height)
     }
 
+    /// Drops the transaction data for the block at `height`, keeping only its
+    /// header, if that block exists and hasn't already been pruned.
+    ///
+    /// The UTXO set and nullifier indexes are unaffected: they're stored in
+    /// their own column families, separate from the block body.
+    ///
+    /// This writes directly via [`rocksdb::DB::put_cf`], rather than through
+    /// [`DiskSerialize::zs_insert`], because `self.db` is a shared
+    /// [`Arc`] (see [`FinalizedState::db`]) and `zs_insert` takes `&mut self`.
+    fn prune_block_body(&self, height: block::Height) {
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+
+        if let Some(StoredBlock::Full(block)) = self.db.zs_get(block_by_height, &height) {
+            let pruned = StoredBlock::Pruned(block.header);
+            self.db
+                .put_cf(block_by_height, height.as_bytes(), pruned.as_bytes())
+                .expect("expected that disk errors would not occur");
+        }
+    }
+
     /// Returns the `transparent::Output` pointed to by the given
     /// `transparent::OutPoint` if it is present.
     pub fn utxo(&self, outpoint: &transparent::OutPoint) -> Option<Utxo> {
@@ -351,35 +905,266 @@ impl FinalizedState {
         self.db.zs_get(utxo_by_outpoint, outpoint)
     }
 
+    /// Returns the transaction hash and input index that spent the given
+    /// `transparent::OutPoint`, if it has been spent by a block in the
+    /// finalized state.
+    ///
+    /// Returns `None` if the `transparent_indexing` option is disabled in
+    /// the [`Config`], even if the outpoint has been spent.
+    pub fn spending_transaction(
+        &self,
+        outpoint: &transparent::OutPoint,
+    ) -> Option<(transaction::Hash, u32)> {
+        if !self.transparent_indexing {
+            return None;
+        }
+
+        let spending_tx_loc_by_outpoint = self
+            .db
+            .cf_handle("spending_tx_loc_by_outpoint")
+            .unwrap();
+        self.db
+            .zs_get(spending_tx_loc_by_outpoint, outpoint)
+            .map(|location: SpendingTransactionLocation| (location.hash, location.input_index))
+    }
+
+    /// Computes summary statistics for the finalized UTXO set: the total
+    /// number of UTXOs, their total transparent value, and a digest of the
+    /// set.
+    ///
+    /// Scans every entry in `utxo_by_outpoint`, so this is relatively
+    /// expensive compared to the other read methods on this type.
+    pub fn utxo_set_info(&self) -> crate::UtxoSetInfo {
+        let utxo_by_outpoint = self.db.cf_handle("utxo_by_outpoint").unwrap();
+
+        let mut utxo_count: u64 = 0;
+        let mut values = Vec::new();
+        let mut digest = zebra_chain::serialization::sha256d::Writer::default();
+
+        for (key, value) in self
+            .db
+            .iterator_cf(utxo_by_outpoint, rocksdb::IteratorMode::Start)
+        {
+            let utxo: Utxo = FromDisk::from_bytes(&value);
+
+            utxo_count += 1;
+            values.push(utxo.output.value);
+
+            digest
+                .write_all(&key)
+                .and_then(|_| digest.write_all(&value))
+                .expect("writing to a digest is infallible");
+        }
+
+        let total_value = values
+            .into_iter()
+            .sum::<Result<Amount<NonNegative>, _>>()
+            .expect("total UTXO set value never exceeds the maximum Amount");
+
+        crate::UtxoSetInfo {
+            utxo_count,
+            total_value,
+            digest: digest.finish(),
+        }
+    }
+
+    /// Returns the UTXOs currently indexed for `address`.
+    ///
+    /// Returns an empty `Vec` if `transparent_indexing` is disabled, even if
+    /// the address has UTXOs: without the index, finding them would require
+    /// scanning every `utxo_by_outpoint` entry.
+    pub fn address_utxos(&self, address: &transparent::Address) -> Vec<(transparent::OutPoint, Utxo)> {
+        if !self.transparent_indexing {
+            return Vec::new();
+        }
+
+        let utxo_loc_by_transparent_addr_loc = self
+            .db
+            .cf_handle("utxo_loc_by_transparent_addr_loc")
+            .unwrap();
+        let prefix = address.as_bytes();
+
+        self.db
+            .iterator_cf(
+                utxo_loc_by_transparent_addr_loc,
+                rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward),
+            )
+            .take_while(|(key, _)| key.starts_with(&prefix[..]))
+            .map(|(key, _)| {
+                let outpoint = transparent::OutPoint::from_bytes(&key[prefix.len()..]);
+                let utxo = self
+                    .utxo(&outpoint)
+                    .expect("indexed outpoint has a matching entry in utxo_by_outpoint");
+
+                (outpoint, utxo)
+            })
+            .collect()
+    }
+
+    /// Returns the total value of the UTXOs currently indexed for `address`.
+    ///
+    /// Returns zero if `transparent_indexing` is disabled.
+    pub fn address_balance(&self, address: &transparent::Address) -> Amount<NonNegative> {
+        self.address_utxos(address)
+            .iter()
+            .map(|(_, utxo)| utxo.output.value)
+            .sum::<Result<Amount<NonNegative>, _>>()
+            .expect("total balance of an address never exceeds the maximum Amount")
+    }
+
     /// Returns the finalized hash for a given `block::Height` if it is present.
     pub fn hash(&self, height: block::Height) -> Option<block::Hash> {
         let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
         self.db.zs_get(hash_by_height, &height)
     }
 
-    /// Returns the given transaction if it exists.
-    pub fn transaction(&self, hash: transaction::Hash) -> Option<Arc<Transaction>> {
+    /// Returns `true` if `sprout_anchor` is a known Sprout note commitment
+    /// tree anchor, used by some finalized or non-finalized transaction.
+    pub fn contains_sprout_anchor(&self, sprout_anchor: &sprout::tree::Root) -> bool {
+        let sprout_anchors = self.db.cf_handle("sprout_anchors").unwrap();
+        self.db.zs_get(sprout_anchors, sprout_anchor).is_some()
+    }
+
+    /// Returns `true` if `sapling_anchor` is a known Sapling note commitment
+    /// tree anchor, used by some finalized or non-finalized transaction.
+    pub fn contains_sapling_anchor(&self, sapling_anchor: &sapling::tree::Root) -> bool {
+        let sapling_anchors = self.db.cf_handle("sapling_anchors").unwrap();
+        self.db.zs_get(sapling_anchors, sapling_anchor).is_some()
+    }
+
+    /// Returns `true` if `orchard_anchor` is a known Orchard note commitment
+    /// tree anchor, used by some finalized or non-finalized transaction.
+    pub fn contains_orchard_anchor(&self, orchard_anchor: &orchard::tree::Root) -> bool {
+        let orchard_anchors = self.db.cf_handle("orchard_anchors").unwrap();
+        self.db.zs_get(orchard_anchors, orchard_anchor).is_some()
+    }
+
+    /// Returns the given transaction and its confirmation height, if it
+    /// exists.
+    pub fn transaction(
+        &self,
+        hash: transaction::Hash,
+    ) -> Option<(Arc<Transaction>, block::Height)> {
         let tx_by_hash = self.db.cf_handle("tx_by_hash").unwrap();
         self.db
             .zs_get(tx_by_hash, &hash)
-            .map(|TransactionLocation { index, height }| {
-                let block = self
-                    .block(height.into())
-                    .expect("block will exist if TransactionLocation does");
+            .and_then(|TransactionLocation { index, height }| {
+                // The block body is absent if it has been pruned.
+                let block = self.block(height.into())?;
 
-                block.transactions[index as usize].clone()
+                Some((block.transactions[index as usize].clone(), height))
             })
     }
 
-    /// If the database is `ephemeral`, delete it.
+    /// Walks the height index, hash index, and block index from genesis,
+    /// looking for the first height where they stop agreeing with each
+    /// other.
+    ///
+    /// This detects the kind of corruption that can follow a crash or disk
+    /// error partway through a commit: a height with no matching hash, a
+    /// hash that doesn't map back to the height that produced it, or a
+    /// missing block body. It doesn't check the UTXO set or nullifier
+    /// indexes against the transactions in each block: doing that
+    /// thoroughly would mean re-deriving them by replaying every block's
+    /// transactions, which needs the same validation context as
+    /// re-applying them from scratch. A gap in the height or block index is
+    /// the symptom a torn write here actually leaves behind, since
+    /// [`FinalizedState::prepare_commit`] writes every column family for a
+    /// block into the same RocksDB batch.
+    pub fn check(&self) -> ConsistencyReport {
+        let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
+
+        let mut last_consistent_height = None;
+        let mut first_inconsistent_height = None;
+
+        for (height_bytes, hash_bytes) in self
+            .db
+            .iterator_cf(hash_by_height, rocksdb::IteratorMode::Start)
+        {
+            let height = block::Height::from_bytes(&height_bytes);
+            let hash = block::Hash::from_bytes(&hash_bytes);
+
+            let expected_height = match last_consistent_height {
+                Some(last) => (last + 1).expect("height fits in a u32"),
+                None => block::Height(0),
+            };
+            if height != expected_height {
+                first_inconsistent_height = Some(expected_height);
+                break;
+            }
+
+            let is_consistent =
+                self.height(hash) == Some(height) && self.stored_block(height.into()).is_some();
+
+            if !is_consistent {
+                first_inconsistent_height = Some(height);
+                break;
+            }
+
+            last_consistent_height = Some(height);
+        }
+
+        ConsistencyReport {
+            last_consistent_height,
+            first_inconsistent_height,
+        }
+    }
+
+    /// Deletes every `hash_by_height`, `height_by_hash`, `block_by_height`,
+    /// and `value_pool_by_height` entry above `height`, so
+    /// [`FinalizedState::finalized_tip_height`] reports `height` again.
+    ///
+    /// Use this to recover from the corruption a [`ConsistencyReport`]
+    /// describes, by repairing back to its `last_consistent_height`.
+    ///
+    /// This doesn't roll back the `utxo_by_outpoint`,
+    /// `utxo_loc_by_transparent_addr_loc`, or nullifier column families:
+    /// unwinding their effects would mean replaying every removed block's
+    /// transactions in reverse, which needs the same validation context as
+    /// re-applying them. After calling this, restart Zebra: it re-downloads
+    /// and re-validates the truncated blocks from the network, which also
+    /// repairs those column families.
+    pub fn repair(&mut self, height: block::Height) -> Result<(), BoxError> {
+        let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
+        let height_by_hash = self.db.cf_handle("height_by_hash").unwrap();
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+        let value_pool_by_height = self.db.cf_handle("value_pool_by_height").unwrap();
+
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for (height_bytes, hash_bytes) in self
+            .db
+            .iterator_cf(hash_by_height, rocksdb::IteratorMode::Start)
+        {
+            if block::Height::from_bytes(&height_bytes) <= height {
+                continue;
+            }
+
+            batch.delete_cf(hash_by_height, &height_bytes);
+            batch.delete_cf(height_by_hash, &hash_bytes);
+            batch.delete_cf(block_by_height, &height_bytes);
+            batch.delete_cf(value_pool_by_height, &height_bytes);
+        }
+
+        self.db.write(batch)?;
+
+        Ok(())
+    }
+
+    /// If the database is `ephemeral`, delete it, unless a
+    /// [`ReadStateService`](super::read::ReadStateService) is still holding a
+    /// clone of the shared database handle.
     fn delete_ephemeral(&self) {
-        if self.ephemeral {
+        if self.ephemeral_in_memory {
+            // The database only exists in an in-memory rocksdb `Env`, so
+            // there are no files on disk to delete: everything is dropped
+            // along with the database handle itself.
+            return;
+        }
+
+        if self.ephemeral && Arc::strong_count(&self.db) == 1 {
             let path = self.db.path();
             tracing::debug!("removing temporary database files {:?}", path);
-            // We'd like to use `rocksdb::Env::mem_env` for ephemeral databases,
-            // but the Zcash blockchain might not fit in memory. So we just
-            // delete the database files instead.
-            //
             // We'd like to call `DB::destroy` here, but calling destroy on a
             // live DB is undefined behaviour:
             // https://github.com/facebook/rocksdb/wiki/RocksDB-FAQ#basic-readwrite
@@ -403,6 +1188,290 @@ impl Drop for FinalizedState {
     }
 }
 
+/// A read-only view of a finalized state, opened by
+/// [`FinalizedState::new_read_only`] as a RocksDB "secondary" instance.
+///
+/// Exposes a handful of [`FinalizedState`]'s read methods: enough for an
+/// external tool to look up the chain tip, blocks, transactions, and UTXOs.
+/// It has no write methods: only the running node holds the read-write
+/// "primary" RocksDB instance that can commit blocks.
+pub struct ReadOnlyFinalizedState {
+    /// The underlying secondary database handle.
+    db: Arc<rocksdb::DB>,
+}
+
+impl ReadOnlyFinalizedState {
+    /// Refreshes this view with any blocks the primary has committed since
+    /// it was opened, or since the last call to this method.
+    pub fn catch_up_with_primary(&self) -> Result<(), BoxError> {
+        self.db.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
+    /// Returns the finalized tip height and hash, or `None` if the database
+    /// is empty (or hasn't been caught up with the primary yet).
+    pub fn tip(&self) -> Option<(block::Height, block::Hash)> {
+        let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
+        self.db
+            .iterator_cf(hash_by_height, rocksdb::IteratorMode::End)
+            .next()
+            .map(|(height_bytes, hash_bytes)| {
+                let height = block::Height::from_bytes(height_bytes);
+                let hash = block::Hash::from_bytes(hash_bytes);
+
+                (height, hash)
+            })
+    }
+
+    /// Returns the given block, if it exists and its body hasn't been pruned.
+    pub fn block(&self, hash_or_height: HashOrHeight) -> Option<Arc<Block>> {
+        let height_by_hash = self.db.cf_handle("height_by_hash").unwrap();
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+        let height = hash_or_height.height_or_else(|hash| self.db.zs_get(height_by_hash, &hash))?;
+        let stored_block: StoredBlock = self.db.zs_get(block_by_height, &height)?;
+
+        stored_block.block()
+    }
+
+    /// Returns the given transaction and its confirmation height, if it
+    /// exists.
+    pub fn transaction(&self, hash: transaction::Hash) -> Option<(Arc<Transaction>, block::Height)> {
+        let tx_by_hash = self.db.cf_handle("tx_by_hash").unwrap();
+        self.db
+            .zs_get(tx_by_hash, &hash)
+            .and_then(|TransactionLocation { index, height }| {
+                let block = self.block(height.into())?;
+
+                Some((block.transactions[index as usize].clone(), height))
+            })
+    }
+
+    /// Returns the `transparent::Output` pointed to by the given
+    /// `transparent::OutPoint` if it is present.
+    pub fn utxo(&self, outpoint: &transparent::OutPoint) -> Option<Utxo> {
+        let utxo_by_outpoint = self.db.cf_handle("utxo_by_outpoint").unwrap();
+        self.db.zs_get(utxo_by_outpoint, outpoint)
+    }
+
+    /// Takes a consistent online backup of this view into the RocksDB
+    /// backup engine directory at `backup_path`, creating it if it doesn't
+    /// already exist.
+    ///
+    /// Each call only copies the SST files that changed since the last
+    /// backup taken into `backup_path`: RocksDB's backup engine hardlinks
+    /// (or, across filesystems, copies) unchanged files from the previous
+    /// backup, so repeated calls are incremental.
+    ///
+    /// This reads through the secondary database handle (see
+    /// [`open_secondary`]), so it doesn't contend with the primary for
+    /// RocksDB's single-writer lock, and can run while `zebrad` keeps
+    /// committing new blocks.
+    pub fn backup(&self, backup_path: &Path) -> Result<(), BoxError> {
+        let mut engine = rocksdb::backup::BackupEngine::open(
+            &rocksdb::backup::BackupEngineOptions::default(),
+            backup_path,
+        )?;
+        engine.create_new_backup(&self.db)?;
+        Ok(())
+    }
+}
+
+/// Opens the on-disk finalized state at `config`'s `cache_dir` as a
+/// read-only RocksDB secondary instance, for a separate process to read
+/// without stopping the node that owns it. See
+/// [`FinalizedState::new_read_only`] for details.
+pub fn open_secondary(
+    config: &Config,
+    network: Network,
+    secondary_path: &Path,
+) -> Result<ReadOnlyFinalizedState, BoxError> {
+    FinalizedState::new_read_only(config, network, secondary_path)
+}
+
+/// A report produced by [`FinalizedState::check`] (and the [`check`] free
+/// function), describing the first inconsistency found, if any, in the
+/// on-disk height, hash, and block indexes.
+///
+/// Zebra's consensus rules, and the way blocks are committed (see
+/// [`FinalizedState::prepare_commit`]), mean these indexes should always
+/// agree with each other; a mismatch points at on-disk corruption, most
+/// likely left behind by a crash or disk error during a write that RocksDB
+/// didn't fully and durably commit.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConsistencyReport {
+    /// The height of the last block whose `hash_by_height`,
+    /// `height_by_hash`, and `block_by_height` entries were all present and
+    /// agreed with each other, or `None` if even the genesis block is
+    /// missing or inconsistent.
+    pub last_consistent_height: Option<block::Height>,
+    /// The first height after `last_consistent_height` with a problem, or
+    /// `None` if every indexed height was consistent.
+    pub first_inconsistent_height: Option<block::Height>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if [`FinalizedState::check`] found no problems.
+    pub fn is_consistent(&self) -> bool {
+        self.first_inconsistent_height.is_none()
+    }
+}
+
+/// Checks the on-disk finalized state at `config`'s `cache_dir` for
+/// consistency. See [`FinalizedState::check`].
+pub fn check(config: &Config, network: Network) -> ConsistencyReport {
+    FinalizedState::new(config, network).check()
+}
+
+/// Repairs the on-disk finalized state at `config`'s `cache_dir` by
+/// truncating it back to `height`, typically the `last_consistent_height`
+/// from a [`ConsistencyReport`] returned by [`check`]. See
+/// [`FinalizedState::repair`].
+pub fn repair(config: &Config, network: Network, height: block::Height) -> Result<(), BoxError> {
+    FinalizedState::new(config, network).repair(height)
+}
+
+/// Forces a full compaction of the `block_by_height` column family in the
+/// on-disk finalized state at `config`'s `cache_dir`.
+///
+/// Intended to be run once after turning on [`Config::compress_blocks`] for
+/// an existing database, so every block body already on disk is rewritten
+/// with the new compression setting immediately, rather than whenever
+/// RocksDB's background compaction would otherwise get to it.
+pub fn compact_blocks(config: &Config, network: Network) -> Result<(), BoxError> {
+    let state = FinalizedState::new(config, network);
+    let block_by_height = state.db.cf_handle("block_by_height").unwrap();
+    state
+        .db
+        .compact_range_cf(block_by_height, None::<&[u8]>, None::<&[u8]>);
+    Ok(())
+}
+
+/// The name of the metadata file written alongside a snapshot's RocksDB
+/// checkpoint by [`export_snapshot`], and checked by [`import_snapshot`].
+const SNAPSHOT_METADATA_FILE: &str = "ZEBRA_SNAPSHOT_METADATA";
+
+/// Writes a portable snapshot of the finalized state at its current tip into
+/// `destination`, which must not already exist.
+///
+/// The snapshot is a RocksDB checkpoint: a lightweight, mostly-hardlinked
+/// copy of the database directory. Alongside it, this writes a small
+/// metadata file recording the on-disk [`DATABASE_FORMAT_VERSION`] and the
+/// tip height and hash the snapshot was taken at, so [`import_snapshot`] can
+/// verify both before a node starts using it.
+///
+/// This only exports the finalized state. Any non-finalized blocks held in
+/// memory aren't part of the snapshot; after importing, Zebra re-downloads
+/// and re-validates the last [`crate::MAX_BLOCK_REORG_HEIGHT`] blocks from
+/// the network, as it would after a restart.
+pub fn export_snapshot(config: &Config, network: Network, destination: &Path) -> Result<(), BoxError> {
+    let state = FinalizedState::new(config, network);
+    let (tip_height, tip_hash) = state
+        .tip()
+        .ok_or("cannot export a snapshot of an empty state")?;
+
+    rocksdb::checkpoint::Checkpoint::new(&state.db)?.create_checkpoint(destination)?;
+
+    let metadata = SnapshotMetadata {
+        format_version: DATABASE_FORMAT_VERSION,
+        tip_height,
+        tip_hash,
+    };
+    std::fs::write(
+        destination.join(SNAPSHOT_METADATA_FILE),
+        metadata.as_bytes(),
+    )?;
+
+    Ok(())
+}
+
+/// Imports the snapshot at `source`, written by [`export_snapshot`], as the
+/// finalized state for `network` at the cache directory described by
+/// `config`, which must not already exist.
+///
+/// Returns an error, without copying anything, if the snapshot's
+/// [`DATABASE_FORMAT_VERSION`] doesn't match the version this build of Zebra
+/// uses, since the on-disk encoding isn't guaranteed to be compatible
+/// across format versions.
+pub fn import_snapshot(config: &Config, network: Network, source: &Path) -> Result<block::Hash, BoxError> {
+    let metadata_bytes = std::fs::read(source.join(SNAPSHOT_METADATA_FILE))?;
+    let metadata = SnapshotMetadata::from_bytes(metadata_bytes);
+
+    if metadata.format_version != DATABASE_FORMAT_VERSION {
+        return Err(format!(
+            "snapshot format version {} doesn't match the current database format version {}",
+            metadata.format_version, DATABASE_FORMAT_VERSION,
+        )
+        .into());
+    }
+
+    let (destination, _db_options) = config.db_config(network);
+    if destination.exists() {
+        return Err(format!("state cache directory {:?} already exists", destination).into());
+    }
+
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    copy_dir_recursive(source, &destination)?;
+    // The metadata file isn't part of the database format, so it shouldn't
+    // end up inside the imported state directory.
+    let _res = std::fs::remove_file(destination.join(SNAPSHOT_METADATA_FILE));
+
+    Ok(metadata.tip_hash)
+}
+
+/// Restores the most recent backup (or the backup identified by
+/// `backup_id`, if given) from the RocksDB backup engine directory at
+/// `backup_path`, as the finalized state for `network` at the cache
+/// directory described by `config`, which must not already exist.
+///
+/// See [`ReadOnlyFinalizedState::backup`] for how backups are taken.
+pub fn restore_backup(
+    config: &Config,
+    network: Network,
+    backup_path: &Path,
+    backup_id: Option<u32>,
+) -> Result<(), BoxError> {
+    let mut engine = rocksdb::backup::BackupEngine::open(
+        &rocksdb::backup::BackupEngineOptions::default(),
+        backup_path,
+    )?;
+
+    let (destination, _db_options) = config.db_config(network);
+    if destination.exists() {
+        return Err(format!("state cache directory {:?} already exists", destination).into());
+    }
+
+    let restore_options = rocksdb::backup::RestoreOptions::default();
+    match backup_id {
+        Some(backup_id) => {
+            engine.restore_from_backup(&destination, &destination, &restore_options, backup_id)?
+        }
+        None => engine.restore_from_latest_backup(&destination, &destination, &restore_options)?,
+    }
+
+    Ok(())
+}
+
+/// Recursively copies the contents of `source` into `destination`, creating
+/// `destination` and any subdirectories as needed.
+fn copy_dir_recursive(source: &Path, destination: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(destination)?;
+
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let entry_destination = destination.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &entry_destination)?;
+        } else {
+            std::fs::copy(entry.path(), entry_destination)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn block_precommit_metrics(finalized: &FinalizedBlock) {
     let (hash, height, block) = (finalized.hash, finalized.height, finalized.block.as_ref());
 
@@ -2,11 +2,13 @@
 use std::{convert::TryInto, fmt::Debug, sync::Arc};
 
 use zebra_chain::{
+    amount::Amount,
     block,
     block::Block,
-    sapling,
+    orchard, sapling,
     serialization::{ZcashDeserialize, ZcashDeserializeInto, ZcashSerialize},
     sprout, transaction, transparent,
+    value_balance::ValueBalance,
 };
 
 use crate::Utxo;
@@ -88,6 +90,86 @@ impl FromDisk for Block {
     }
 }
 
+/// The value stored for a height in the `block_by_height` column family.
+///
+/// Every block is written in full when it's committed. If pruning is
+/// configured ([`Config::pruning`](crate::Config::pruning)),
+/// [`FinalizedState::prune_block_body`](super::FinalizedState::prune_block_body)
+/// later rewrites old entries to [`StoredBlock::Pruned`], dropping the
+/// transaction data but keeping the header, so Zebra can still validate
+/// `previous_block_hash` continuity and serve headers without keeping full
+/// block bodies on disk forever.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoredBlock {
+    /// The full, serialized block.
+    Full(Arc<Block>),
+    /// Just the block header, once the block's transaction data has been pruned.
+    Pruned(block::Header),
+}
+
+impl StoredBlock {
+    /// Returns the full block, or `None` if it has been pruned.
+    pub fn block(&self) -> Option<Arc<Block>> {
+        match self {
+            StoredBlock::Full(block) => Some(block.clone()),
+            StoredBlock::Pruned(_) => None,
+        }
+    }
+
+    /// Returns this block's header, whether or not it has been pruned.
+    pub fn header(&self) -> block::Header {
+        match self {
+            StoredBlock::Full(block) => block.header,
+            StoredBlock::Pruned(header) => *header,
+        }
+    }
+}
+
+/// On-disk tag byte identifying a [`StoredBlock::Full`] entry.
+const STORED_BLOCK_FULL_TAG: u8 = 0;
+/// On-disk tag byte identifying a [`StoredBlock::Pruned`] entry.
+const STORED_BLOCK_PRUNED_TAG: u8 = 1;
+
+impl IntoDisk for StoredBlock {
+    type Bytes = Vec<u8>;
+
+    fn as_bytes(&self) -> Self::Bytes {
+        match self {
+            StoredBlock::Full(block) => {
+                let mut bytes = vec![STORED_BLOCK_FULL_TAG];
+                bytes.extend(block.as_bytes());
+                bytes
+            }
+            StoredBlock::Pruned(header) => {
+                let mut bytes = vec![STORED_BLOCK_PRUNED_TAG];
+                bytes.extend(
+                    header
+                        .zcash_serialize_to_vec()
+                        .expect("serialization to vec doesn't fail"),
+                );
+                bytes
+            }
+        }
+    }
+}
+
+impl FromDisk for StoredBlock {
+    fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+        let (tag, body) = bytes.split_at(1);
+
+        match tag[0] {
+            STORED_BLOCK_FULL_TAG => StoredBlock::Full(Arc::new(Block::from_bytes(body))),
+            STORED_BLOCK_PRUNED_TAG => StoredBlock::Pruned(
+                block::Header::zcash_deserialize(body).expect(
+                    "deserialization format should match the serialization format used by IntoDisk",
+                ),
+            ),
+            tag => unreachable!("invalid StoredBlock tag byte on disk: {}", tag),
+        }
+    }
+}
+
 impl IntoDisk for TransactionLocation {
     type Bytes = [u8; 8];
 
@@ -132,6 +214,54 @@ impl IntoDisk for transaction::Hash {
     }
 }
 
+impl FromDisk for transaction::Hash {
+    fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let array = bytes.as_ref().try_into().unwrap();
+        Self(array)
+    }
+}
+
+/// The value stored for an outpoint in the `spending_tx_loc_by_outpoint`
+/// column family: the transaction that spent it, and the index of the
+/// spending input within that transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendingTransactionLocation {
+    /// The hash of the transaction that spent the output.
+    pub hash: transaction::Hash,
+    /// The index of the spending input within `hash`'s transaction.
+    pub input_index: u32,
+}
+
+impl IntoDisk for SpendingTransactionLocation {
+    type Bytes = [u8; 36];
+
+    fn as_bytes(&self) -> Self::Bytes {
+        let mut bytes = [0; 36];
+        bytes[0..32].copy_from_slice(&self.hash.0);
+        bytes[32..36].copy_from_slice(&self.input_index.to_be_bytes());
+        bytes
+    }
+}
+
+impl FromDisk for SpendingTransactionLocation {
+    fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+
+        let hash = {
+            let array: [u8; 32] = bytes[0..32].try_into().unwrap();
+            transaction::Hash(array)
+        };
+
+        let input_index = {
+            let mut array = [0; 4];
+            array.copy_from_slice(&bytes[32..36]);
+            u32::from_be_bytes(array)
+        };
+
+        SpendingTransactionLocation { hash, input_index }
+    }
+}
+
 impl IntoDisk for block::Hash {
     type Bytes = [u8; 32];
 
@@ -163,6 +293,30 @@ impl IntoDisk for sapling::Nullifier {
     }
 }
 
+impl IntoDisk for sprout::tree::Root {
+    type Bytes = [u8; 32];
+
+    fn as_bytes(&self) -> Self::Bytes {
+        (*self).into()
+    }
+}
+
+impl IntoDisk for sapling::tree::Root {
+    type Bytes = [u8; 32];
+
+    fn as_bytes(&self) -> Self::Bytes {
+        self.0
+    }
+}
+
+impl IntoDisk for orchard::tree::Root {
+    type Bytes = [u8; 32];
+
+    fn as_bytes(&self) -> Self::Bytes {
+        self.0
+    }
+}
+
 impl IntoDisk for () {
     type Bytes = [u8; 0];
 
@@ -171,6 +325,10 @@ impl IntoDisk for () {
     }
 }
 
+impl FromDisk for () {
+    fn from_bytes(_bytes: impl AsRef<[u8]>) -> Self {}
+}
+
 impl IntoDisk for block::Height {
     type Bytes = [u8; 4];
 
@@ -216,6 +374,36 @@ impl FromDisk for Utxo {
     }
 }
 
+impl IntoDisk for ValueBalance {
+    type Bytes = [u8; 32];
+
+    fn as_bytes(&self) -> Self::Bytes {
+        let mut bytes = [0; 32];
+        bytes[0..8].copy_from_slice(&self.transparent().to_bytes());
+        bytes[8..16].copy_from_slice(&self.sprout().to_bytes());
+        bytes[16..24].copy_from_slice(&self.sapling().to_bytes());
+        bytes[24..32].copy_from_slice(&self.orchard().to_bytes());
+        bytes
+    }
+}
+
+impl FromDisk for ValueBalance {
+    fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+        let amount_at = |range: std::ops::Range<usize>| {
+            Amount::try_from(i64::from_le_bytes(bytes[range].try_into().unwrap()))
+                .expect("db has serialized data")
+        };
+
+        ValueBalance::from_components(
+            amount_at(0..8),
+            amount_at(8..16),
+            amount_at(16..24),
+            amount_at(24..32),
+        )
+    }
+}
+
 impl IntoDisk for transparent::OutPoint {
     type Bytes = Vec<u8>;
 
@@ -225,6 +413,51 @@ impl IntoDisk for transparent::OutPoint {
     }
 }
 
+impl FromDisk for transparent::OutPoint {
+    fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        bytes
+            .as_ref()
+            .zcash_deserialize_into()
+            .expect("db has serialized data")
+    }
+}
+
+impl IntoDisk for transparent::Address {
+    // `Address::zcash_serialize` always writes the same number of bytes, so
+    // using it here gives every key in `utxo_loc_by_transparent_addr_loc` a
+    // fixed-length address prefix, which is what makes a prefix scan over an
+    // address's UTXOs possible.
+    type Bytes = Vec<u8>;
+
+    fn as_bytes(&self) -> Self::Bytes {
+        self.zcash_serialize_to_vec()
+            .expect("serialization to vec doesn't fail")
+    }
+}
+
+/// A key for the `utxo_loc_by_transparent_addr_loc` column family: a
+/// transparent address, followed by the location of one of its unspent
+/// outputs.
+///
+/// Keys in this column family are `address_bytes || outpoint_bytes`, so that
+/// [`FinalizedState::address_utxos`](super::FinalizedState::address_utxos)
+/// can find every UTXO for an address with a single prefix scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressUtxoKey {
+    pub address: transparent::Address,
+    pub outpoint: transparent::OutPoint,
+}
+
+impl IntoDisk for AddressUtxoKey {
+    type Bytes = Vec<u8>;
+
+    fn as_bytes(&self) -> Self::Bytes {
+        let mut bytes = self.address.as_bytes();
+        bytes.extend(self.outpoint.as_bytes());
+        bytes
+    }
+}
+
 /// Helper trait for inserting (Key, Value) pairs into rocksdb with a consistently
 /// defined format
 pub trait DiskSerialize {
@@ -235,6 +468,49 @@ pub trait DiskSerialize {
         V: IntoDisk;
 }
 
+/// The metadata written alongside a state snapshot, recording enough
+/// information for [`super::import_snapshot`] to check that the snapshot is
+/// usable before loading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotMetadata {
+    pub format_version: u32,
+    pub tip_height: block::Height,
+    pub tip_hash: block::Hash,
+}
+
+impl SnapshotMetadata {
+    /// Serializes this metadata to its fixed-size on-disk representation:
+    /// a 4-byte format version, followed by the tip's 4-byte height and
+    /// 32-byte hash.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + 4 + 32);
+        bytes.extend(self.format_version.to_be_bytes());
+        bytes.extend(self.tip_height.as_bytes());
+        bytes.extend(self.tip_hash.as_bytes());
+        bytes
+    }
+
+    /// Deserializes metadata previously produced by [`SnapshotMetadata::as_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// - if `bytes` isn't a valid [`SnapshotMetadata`]
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+        assert_eq!(bytes.len(), 4 + 4 + 32, "invalid snapshot metadata length");
+
+        let format_version = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+        let tip_height = block::Height::from_bytes(&bytes[4..8]);
+        let tip_hash = block::Hash::from_bytes(&bytes[8..40]);
+
+        SnapshotMetadata {
+            format_version,
+            tip_height,
+            tip_hash,
+        }
+    }
+}
+
 impl DiskSerialize for rocksdb::WriteBatch {
     fn zs_insert<K, V>(&mut self, cf: &rocksdb::ColumnFamily, key: K, value: V)
     where
@@ -247,6 +523,19 @@ impl DiskSerialize for rocksdb::WriteBatch {
     }
 }
 
+impl DiskSerialize for rocksdb::DB {
+    fn zs_insert<K, V>(&mut self, cf: &rocksdb::ColumnFamily, key: K, value: V)
+    where
+        K: IntoDisk + Debug,
+        V: IntoDisk,
+    {
+        let key_bytes = key.as_bytes();
+        let value_bytes = value.as_bytes();
+        rocksdb::DB::put_cf(self, cf, key_bytes, value_bytes)
+            .expect("expected that disk errors would not occur");
+    }
+}
+
 /// Helper trait for retrieving values from rocksdb column familys with a consistently
 /// defined format
 pub trait DiskDeserialize {
@@ -0,0 +1,147 @@
+//! A cloneable, read-only view of the chain state.
+//!
+//! [`ReadStateService`] answers block, transaction, and UTXO queries directly
+//! from a shared database handle and a snapshot of the non-finalized best
+//! chain, so that read-only callers (such as an RPC server or inbound peer
+//! requests) never queue behind the [`StateService`](super::super::StateService)
+//! that's processing block commits.
+//!
+//! Wiring this up to callers outside the crate is left for future work:
+//! [`crate::init`] still returns only a boxed `tower::Service`, since changing
+//! its signature to also return a [`ReadStateService`] would ripple into every
+//! one of its callers across `zebrad` and `zebra-consensus`. `zebra-rpc`
+//! doesn't implement any RPC methods yet in this snapshot either, so there's
+//! no read-only caller to wire up yet regardless.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use zebra_chain::{
+    block::{self, Block},
+    transaction::{self, Transaction},
+    transparent,
+};
+
+use crate::{service::non_finalized_state::NonFinalizedState, HashOrHeight, Utxo};
+
+use super::disk_format::{DiskDeserialize, StoredBlock, TransactionLocation};
+
+/// A cloneable handle to the finalized and non-finalized chain state, for
+/// answering read-only queries without going through the [`StateService`](
+/// super::super::StateService)'s request queue.
+///
+/// Cloning a [`ReadStateService`] is cheap: it shares the underlying database
+/// handle with every other clone, and the original [`FinalizedState`](
+/// super::FinalizedState).
+#[derive(Clone)]
+pub struct ReadStateService {
+    /// The shared, read-only handle to the on-disk finalized state.
+    db: Arc<rocksdb::DB>,
+    /// A live view of the current best non-finalized chain, updated by the
+    /// [`StateService`](super::super::StateService) whenever it changes.
+    mem: watch::Receiver<NonFinalizedState>,
+}
+
+impl ReadStateService {
+    /// Creates a new [`ReadStateService`], sharing `db` with the
+    /// [`FinalizedState`](super::FinalizedState) it was created from.
+    pub(in super::super) fn new(
+        db: Arc<rocksdb::DB>,
+        mem: watch::Receiver<NonFinalizedState>,
+    ) -> Self {
+        Self { db, mem }
+    }
+
+    /// Returns the tip height and hash of the current best chain.
+    pub fn tip(&self) -> Option<(block::Height, block::Hash)> {
+        self.mem.borrow().best_tip().or_else(|| self.finalized_tip())
+    }
+
+    /// Returns the block identified by `hash_or_height` if it's in the
+    /// current best chain, and its body hasn't been pruned.
+    pub fn block(&self, hash_or_height: HashOrHeight) -> Option<Arc<Block>> {
+        self.mem
+            .borrow()
+            .best_block(hash_or_height)
+            .or_else(|| self.finalized_stored_block(hash_or_height)?.block())
+    }
+
+    /// Returns the header of the block identified by `hash_or_height`, if it's
+    /// in the current best chain, whether or not its body has been pruned.
+    pub fn block_header(&self, hash_or_height: HashOrHeight) -> Option<block::Header> {
+        if let Some(block) = self.mem.borrow().best_block(hash_or_height) {
+            return Some(block.header.clone());
+        }
+
+        Some(self.finalized_stored_block(hash_or_height)?.header())
+    }
+
+    /// Returns the transaction identified by `hash`, and the height it was
+    /// confirmed at, if it exists in the current best chain.
+    pub fn transaction(
+        &self,
+        hash: transaction::Hash,
+    ) -> Option<(Arc<Transaction>, block::Height)> {
+        self.mem
+            .borrow()
+            .best_transaction(hash)
+            .or_else(|| self.finalized_transaction(hash))
+    }
+
+    /// Returns the [`Utxo`] pointed to by `outpoint`, if it exists in the
+    /// current best chain.
+    pub fn utxo(&self, outpoint: &transparent::OutPoint) -> Option<Utxo> {
+        self.mem
+            .borrow()
+            .any_utxo(outpoint)
+            .or_else(|| self.finalized_utxo(outpoint))
+    }
+
+    /// Returns the tip height and hash of the finalized state, ignoring any
+    /// non-finalized blocks.
+    fn finalized_tip(&self) -> Option<(block::Height, block::Hash)> {
+        let hash_by_height = self.db.cf_handle("hash_by_height").unwrap();
+        self.db
+            .iterator_cf(hash_by_height, rocksdb::IteratorMode::End)
+            .next()
+            .map(|(height_bytes, hash_bytes)| {
+                (
+                    block::Height::from_bytes(height_bytes),
+                    block::Hash::from_bytes(hash_bytes),
+                )
+            })
+    }
+
+    /// Returns the [`StoredBlock`] identified by `hash_or_height` from the
+    /// finalized state, if it exists.
+    fn finalized_stored_block(&self, hash_or_height: HashOrHeight) -> Option<StoredBlock> {
+        let height_by_hash = self.db.cf_handle("height_by_hash").unwrap();
+        let block_by_height = self.db.cf_handle("block_by_height").unwrap();
+        let height = hash_or_height.height_or_else(|hash| self.db.zs_get(height_by_hash, &hash))?;
+
+        self.db.zs_get(block_by_height, &height)
+    }
+
+    /// Returns the finalized transaction identified by `hash`, and the height
+    /// it was confirmed at, if it exists.
+    fn finalized_transaction(
+        &self,
+        hash: transaction::Hash,
+    ) -> Option<(Arc<Transaction>, block::Height)> {
+        let tx_by_hash = self.db.cf_handle("tx_by_hash").unwrap();
+        let TransactionLocation { index, height } = self.db.zs_get(tx_by_hash, &hash)?;
+        let block = self
+            .finalized_stored_block(height.into())
+            .expect("block will exist if TransactionLocation does")
+            .block()?;
+
+        Some((block.transactions[index as usize].clone(), height))
+    }
+
+    /// Returns the finalized [`Utxo`] pointed to by `outpoint`, if it exists.
+    fn finalized_utxo(&self, outpoint: &transparent::OutPoint) -> Option<Utxo> {
+        let utxo_by_outpoint = self.db.cf_handle("utxo_by_outpoint").unwrap();
+        self.db.zs_get(utxo_by_outpoint, outpoint)
+    }
+}
@@ -0,0 +1,96 @@
+//! The single source of truth for the on-disk column family layout.
+//!
+//! [`FinalizedState::new`](super::FinalizedState::new) opens exactly the
+//! column families listed here, and
+//! [`Request::DatabaseLayout`](crate::Request::DatabaseLayout) reports on
+//! them, so the two can never drift apart.
+
+/// A description of a single RocksDB column family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnFamilyLayout {
+    /// The column family's name, as passed to RocksDB.
+    pub name: &'static str,
+
+    /// A human-readable description of the keys and values stored in this
+    /// column family.
+    pub schema: &'static str,
+}
+
+/// The current schema version of the on-disk database format.
+///
+/// This must be bumped whenever a column family is added, removed, or has
+/// its key/value encoding changed in a way that isn't backwards-compatible.
+///
+/// Re-exported from [`crate::constants`], which is also what
+/// [`Config::db_config`](crate::Config::db_config) uses to pick the on-disk
+/// cache directory, so the directory and the column family layout can never
+/// drift out of sync with each other.
+pub(crate) use crate::constants::DATABASE_FORMAT_VERSION;
+
+/// The column families making up the finalized state database, in the order
+/// they are opened in.
+pub const COLUMN_FAMILIES: &[ColumnFamilyLayout] = &[
+    ColumnFamilyLayout {
+        name: "hash_by_height",
+        schema: "block::Height -> block::Hash",
+    },
+    ColumnFamilyLayout {
+        name: "height_by_hash",
+        schema: "block::Hash -> block::Height",
+    },
+    ColumnFamilyLayout {
+        name: "block_by_height",
+        schema: "block::Height -> StoredBlock (a full Block, or just its \
+                 Header if pruning has dropped the transaction data)",
+    },
+    ColumnFamilyLayout {
+        name: "tx_by_hash",
+        schema: "transaction::Hash -> TransactionLocation",
+    },
+    ColumnFamilyLayout {
+        name: "utxo_by_outpoint",
+        schema: "transparent::OutPoint -> transparent::Output",
+    },
+    ColumnFamilyLayout {
+        name: "sprout_nullifiers",
+        schema: "sprout::Nullifier -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "sapling_nullifiers",
+        schema: "sapling::Nullifier -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "utxo_loc_by_transparent_addr_loc",
+        schema: "transparent::Address + transparent::OutPoint -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "spending_tx_loc_by_outpoint",
+        schema: "transparent::OutPoint -> SpendingTransactionLocation (spending \
+                 transaction::Hash + input index)",
+    },
+    ColumnFamilyLayout {
+        name: "sprout_anchors",
+        schema: "sprout::tree::Root -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "sapling_anchors",
+        schema: "sapling::tree::Root -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "orchard_anchors",
+        schema: "orchard::tree::Root -> ()",
+    },
+    ColumnFamilyLayout {
+        name: "value_pool_by_height",
+        schema: "block::Height -> ValueBalance (the cumulative Sprout, \
+                 Sapling, and Orchard value pool balances, from genesis \
+                 to this height, inclusive)",
+    },
+];
+
+/// Returns the column family names, in the order they should be opened in.
+///
+/// This is the list actually passed to `rocksdb::DB::open_cf_descriptors`.
+pub fn column_family_names() -> impl Iterator<Item = &'static str> {
+    COLUMN_FAMILIES.iter().map(|cf| cf.name)
+}
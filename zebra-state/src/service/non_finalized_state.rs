@@ -7,13 +7,21 @@ mod queued_blocks;
 
 pub use queued_blocks::QueuedBlocks;
 
-use std::{collections::BTreeSet, mem, ops::Deref, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    mem,
+    ops::Deref,
+    sync::Arc,
+};
 
 use zebra_chain::{
     block::{self, Block},
+    orchard,
     parameters::{Network, NetworkUpgrade::Canopy},
+    sapling, sprout,
     transaction::{self, Transaction},
     transparent,
+    value_balance::ValueBalance,
 };
 
 use crate::{FinalizedBlock, HashOrHeight, PreparedBlock, Utxo};
@@ -21,12 +29,15 @@ use crate::{FinalizedBlock, HashOrHeight, PreparedBlock, Utxo};
 use self::chain::Chain;
 
 /// The state of the chains in memory, incuding queued blocks.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct NonFinalizedState {
     /// Verified, non-finalized chains, in ascending order.
     ///
     /// The best chain is `chain_set.last()` or `chain_set.iter().next_back()`.
     pub chain_set: BTreeSet<Box<Chain>>,
+    /// Block hashes marked invalid by [`NonFinalizedState::invalidate_block`],
+    /// and not yet reconsidered by [`NonFinalizedState::reconsider_block`].
+    invalidated_blocks: HashSet<block::Hash>,
     /// The configured Zcash network
     pub network: Network,
 }
@@ -124,6 +135,46 @@ impl NonFinalizedState {
             .any(|chain| chain.height_by_hash.contains_key(hash))
     }
 
+    /// Marks `hash` as invalid, and discards every non-finalized chain that
+    /// contains it, so the best chain is reselected from what remains.
+    ///
+    /// Any block committed later with this hash is rejected, until a
+    /// matching [`NonFinalizedState::reconsider_block`] call. This only
+    /// affects the non-finalized, in-memory chains: a block that has already
+    /// been finalized to disk can't be invalidated, since Zebra treats the
+    /// finalized tip as immutable. Operators recovering from a bad block
+    /// that's already finalized need to restore from a backup or resync
+    /// instead.
+    pub fn invalidate_block(&mut self, hash: block::Hash) {
+        self.invalidated_blocks.insert(hash);
+        self.chain_set
+            .retain(|chain| !chain.height_by_hash.contains_key(&hash));
+
+        metrics::gauge!("state.memory.chain.count", self.chain_set.len() as _);
+        if let Some(best_chain) = self.best_chain() {
+            metrics::gauge!(
+                "state.memory.best.chain.length",
+                best_chain.blocks.len() as _
+            );
+        }
+    }
+
+    /// Un-marks `hash` as invalid, so blocks with this hash can be committed
+    /// again.
+    ///
+    /// This doesn't revive any chain [`NonFinalizedState::invalidate_block`]
+    /// already discarded: the blocks that made up it need to be
+    /// re-downloaded and re-committed from scratch.
+    pub fn reconsider_block(&mut self, hash: block::Hash) {
+        self.invalidated_blocks.remove(&hash);
+    }
+
+    /// Returns `true` if `hash` has been marked invalid by
+    /// [`NonFinalizedState::invalidate_block`] and not yet reconsidered.
+    pub fn is_invalidated(&self, hash: &block::Hash) -> bool {
+        self.invalidated_blocks.contains(hash)
+    }
+
     /// Remove and return the first chain satisfying the given predicate.
     fn take_chain_if<F>(&mut self, predicate: F) -> Option<Box<Chain>>
     where
@@ -163,6 +214,50 @@ impl NonFinalizedState {
         None
     }
 
+    /// Returns `true` if `sprout_anchor` was used by a transaction in any chain.
+    pub fn any_sprout_anchor_contains(&self, sprout_anchor: &sprout::tree::Root) -> bool {
+        self.chain_set
+            .iter()
+            .any(|chain| chain.contains_sprout_anchor(sprout_anchor))
+    }
+
+    /// Returns `true` if `sapling_anchor` was used by a transaction in any chain.
+    pub fn any_sapling_anchor_contains(&self, sapling_anchor: &sapling::tree::Root) -> bool {
+        self.chain_set
+            .iter()
+            .any(|chain| chain.contains_sapling_anchor(sapling_anchor))
+    }
+
+    /// Returns `true` if `orchard_anchor` was used by a transaction in any chain.
+    pub fn any_orchard_anchor_contains(&self, orchard_anchor: &orchard::tree::Root) -> bool {
+        self.chain_set
+            .iter()
+            .any(|chain| chain.contains_orchard_anchor(orchard_anchor))
+    }
+
+    /// Returns the partial chain value pools for the chain with tip
+    /// `parent_hash`, or `ValueBalance::zero()` if `parent_hash` is the
+    /// finalized tip, or isn't part of any known non-finalized chain.
+    ///
+    /// See [`Chain::partial_chain_value_pools`] for why this isn't the value
+    /// of the pools since genesis: callers that need the cumulative balance
+    /// must add the finalized state's persisted balance themselves, for
+    /// example with [`FinalizedState::finalized_value_pool`](
+    /// crate::service::finalized_state::FinalizedState::finalized_value_pool).
+    pub fn chain_value_pools(&self, parent_hash: block::Hash) -> ValueBalance {
+        self.chain_set
+            .iter()
+            .find(|chain| chain.non_finalized_tip_hash() == parent_hash)
+            .map(|chain| chain.partial_chain_value_pools())
+            .or_else(|| {
+                self.chain_set
+                    .iter()
+                    .find_map(|chain| chain.fork(parent_hash))
+                    .map(|chain| chain.partial_chain_value_pools())
+            })
+            .unwrap_or_else(ValueBalance::zero)
+    }
+
     /// Returns the `block` with the given hash in any chain.
     pub fn any_block_by_hash(&self, hash: block::Hash) -> Option<Arc<Block>> {
         for chain in self.chain_set.iter().rev() {
@@ -225,13 +320,19 @@ impl NonFinalizedState {
         None
     }
 
-    /// Returns the given transaction if it exists in the best chain.
-    pub fn best_transaction(&self, hash: transaction::Hash) -> Option<Arc<Transaction>> {
+    /// Returns the given transaction and its confirmation height, if it
+    /// exists in the best chain.
+    pub fn best_transaction(
+        &self,
+        hash: transaction::Hash,
+    ) -> Option<(Arc<Transaction>, block::Height)> {
         let best_chain = self.best_chain()?;
-        best_chain
-            .tx_by_hash
-            .get(&hash)
-            .map(|(height, index)| best_chain.blocks[height].block.transactions[*index].clone())
+        best_chain.tx_by_hash.get(&hash).map(|(height, index)| {
+            (
+                best_chain.blocks[height].block.transactions[*index].clone(),
+                *height,
+            )
+        })
     }
 
     /// Return the non-finalized portion of the current best chain
@@ -6,8 +6,8 @@ use std::{
 
 use tracing::{debug_span, instrument, trace};
 use zebra_chain::{
-    block, primitives::Groth16Proof, sapling, sprout, transaction, transparent,
-    work::difficulty::PartialCumulativeWork,
+    block, orchard, primitives::Groth16Proof, sapling, sprout, transaction, transparent,
+    value_balance::ValueBalance, work::difficulty::PartialCumulativeWork,
 };
 
 use crate::{PreparedBlock, Utxo};
@@ -20,11 +20,23 @@ pub struct Chain {
 
     pub created_utxos: HashMap<transparent::OutPoint, Utxo>,
     spent_utxos: HashSet<transparent::OutPoint>,
-    sprout_anchors: HashSet<sprout::tree::Root>,
-    sapling_anchors: HashSet<sapling::tree::Root>,
+    /// Note commitment tree anchors used by the transactions in this chain, with
+    /// a reference count, because unlike nullifiers, the same anchor can be
+    /// used by multiple transactions.
+    sprout_anchors: HashMap<sprout::tree::Root, u32>,
+    sapling_anchors: HashMap<sapling::tree::Root, u32>,
+    orchard_anchors: HashMap<orchard::tree::Root, u32>,
     sprout_nullifiers: HashSet<sprout::Nullifier>,
     sapling_nullifiers: HashSet<sapling::Nullifier>,
+    orchard_nullifiers: HashSet<orchard::Nullifier>,
     partial_cumulative_work: PartialCumulativeWork,
+    /// The sum of the value changes caused by the blocks in this chain,
+    /// relative to the value pools at this chain's root.
+    ///
+    /// Unlike `partial_cumulative_work`, this is not the value of the pools
+    /// since genesis: it is only the change caused by the blocks currently
+    /// in this in-memory chain.
+    partial_chain_value_pools: ValueBalance,
 }
 
 impl Chain {
@@ -115,6 +127,27 @@ impl Chain {
     pub fn is_empty(&self) -> bool {
         self.blocks.is_empty()
     }
+
+    /// Returns `true` if `sprout_anchor` was used by a transaction in this chain.
+    pub fn contains_sprout_anchor(&self, sprout_anchor: &sprout::tree::Root) -> bool {
+        self.sprout_anchors.contains_key(sprout_anchor)
+    }
+
+    /// Returns `true` if `sapling_anchor` was used by a transaction in this chain.
+    pub fn contains_sapling_anchor(&self, sapling_anchor: &sapling::tree::Root) -> bool {
+        self.sapling_anchors.contains_key(sapling_anchor)
+    }
+
+    /// Returns `true` if `orchard_anchor` was used by a transaction in this chain.
+    pub fn contains_orchard_anchor(&self, orchard_anchor: &orchard::tree::Root) -> bool {
+        self.orchard_anchors.contains_key(orchard_anchor)
+    }
+
+    /// Returns the sum of the value changes caused by the blocks in this
+    /// chain, relative to the value pools at this chain's root.
+    pub fn partial_chain_value_pools(&self) -> ValueBalance {
+        self.partial_chain_value_pools
+    }
 }
 
 /// Helper trait to organize inverse operations done on the `Chain` type. Used to
@@ -158,6 +191,14 @@ impl UpdateWith<PreparedBlock> for Chain {
             .expect("work has already been validated");
         self.partial_cumulative_work += block_work;
 
+        // add this block's value balance changes to the partial chain value pools
+        let block_value_pool_change = block
+            .chain_value_pool_change()
+            .expect("value balances have already been validated");
+        self.partial_chain_value_pools = (self.partial_chain_value_pools
+            + block_value_pool_change)
+            .expect("value pool changes have already been validated");
+
         // for each transaction in block
         for (transaction_index, (transaction, transaction_hash)) in block
             .transactions
@@ -221,6 +262,14 @@ impl UpdateWith<PreparedBlock> for Chain {
             .expect("work has already been validated");
         self.partial_cumulative_work -= block_work;
 
+        // remove this block's value balance changes from the partial chain value pools
+        let block_value_pool_change = block
+            .chain_value_pool_change()
+            .expect("value balances have already been validated");
+        self.partial_chain_value_pools = (self.partial_chain_value_pools
+            - block_value_pool_change)
+            .expect("value pool changes have already been validated");
+
         // for each transaction in block
         for (transaction, transaction_hash) in
             block.transactions.iter().zip(transaction_hashes.iter())
@@ -303,12 +352,17 @@ impl UpdateWith<Option<transaction::JoinSplitData<Groth16Proof>>> for Chain {
         joinsplit_data: &Option<transaction::JoinSplitData<Groth16Proof>>,
     ) {
         if let Some(joinsplit_data) = joinsplit_data {
-            for sprout::JoinSplit { nullifiers, .. } in joinsplit_data.joinsplits() {
+            for sprout::JoinSplit {
+                nullifiers, anchor, ..
+            } in joinsplit_data.joinsplits()
+            {
                 let span = debug_span!("revert_chain_state_with", ?nullifiers);
                 let _entered = span.enter();
                 trace!("Adding sprout nullifiers.");
                 self.sprout_nullifiers.insert(nullifiers[0]);
                 self.sprout_nullifiers.insert(nullifiers[1]);
+
+                *self.sprout_anchors.entry(*anchor).or_insert(0) += 1;
             }
         }
     }
@@ -319,7 +373,10 @@ impl UpdateWith<Option<transaction::JoinSplitData<Groth16Proof>>> for Chain {
         joinsplit_data: &Option<transaction::JoinSplitData<Groth16Proof>>,
     ) {
         if let Some(joinsplit_data) = joinsplit_data {
-            for sprout::JoinSplit { nullifiers, .. } in joinsplit_data.joinsplits() {
+            for sprout::JoinSplit {
+                nullifiers, anchor, ..
+            } in joinsplit_data.joinsplits()
+            {
                 let span = debug_span!("revert_chain_state_with", ?nullifiers);
                 let _entered = span.enter();
                 trace!("Removing sprout nullifiers.");
@@ -331,25 +388,51 @@ impl UpdateWith<Option<transaction::JoinSplitData<Groth16Proof>>> for Chain {
                     self.sprout_nullifiers.remove(&nullifiers[1]),
                     "nullifiers must be present if block was"
                 );
+
+                remove_anchor_reference(&mut self.sprout_anchors, anchor);
             }
         }
     }
 }
 
+/// Decrement the reference count for `anchor` in `anchors`, removing the
+/// entry entirely once no transaction in the chain still uses it.
+fn remove_anchor_reference<Root: Eq + std::hash::Hash>(
+    anchors: &mut HashMap<Root, u32>,
+    anchor: &Root,
+) {
+    let count = anchors
+        .get_mut(anchor)
+        .expect("anchor must be present if block was");
+    *count -= 1;
+    if *count == 0 {
+        anchors.remove(anchor);
+    }
+}
+
 impl<AnchorV> UpdateWith<Option<sapling::ShieldedData<AnchorV>>> for Chain
 where
     AnchorV: sapling::AnchorVariant + Clone,
+    sapling::Spend<sapling::PerSpendAnchor>: From<(sapling::Spend<AnchorV>, AnchorV::Shared)>,
 {
     fn update_chain_state_with(&mut self, shielded_data: &Option<sapling::ShieldedData<AnchorV>>) {
         if let Some(shielded_data) = shielded_data {
             for nullifier in shielded_data.nullifiers() {
                 self.sapling_nullifiers.insert(*nullifier);
             }
+
+            for anchor in shielded_data.anchors() {
+                *self.sapling_anchors.entry(anchor).or_insert(0) += 1;
+            }
         }
     }
 
     fn revert_chain_state_with(&mut self, shielded_data: &Option<sapling::ShieldedData<AnchorV>>) {
         if let Some(shielded_data) = shielded_data {
+            for anchor in shielded_data.anchors() {
+                remove_anchor_reference(&mut self.sapling_anchors, &anchor);
+            }
+
             for nullifier in shielded_data.nullifiers() {
                 assert!(
                     self.sapling_nullifiers.remove(nullifier),
@@ -360,6 +443,34 @@ where
     }
 }
 
+impl UpdateWith<Option<orchard::ShieldedData>> for Chain {
+    fn update_chain_state_with(&mut self, shielded_data: &Option<orchard::ShieldedData>) {
+        if let Some(shielded_data) = shielded_data {
+            for nullifier in shielded_data.nullifiers() {
+                self.orchard_nullifiers.insert(*nullifier);
+            }
+
+            *self
+                .orchard_anchors
+                .entry(shielded_data.shared_anchor)
+                .or_insert(0) += 1;
+        }
+    }
+
+    fn revert_chain_state_with(&mut self, shielded_data: &Option<orchard::ShieldedData>) {
+        if let Some(shielded_data) = shielded_data {
+            remove_anchor_reference(&mut self.orchard_anchors, &shielded_data.shared_anchor);
+
+            for nullifier in shielded_data.nullifiers() {
+                assert!(
+                    self.orchard_nullifiers.remove(nullifier),
+                    "nullifier must be present if block was"
+                );
+            }
+        }
+    }
+}
+
 impl PartialEq for Chain {
     fn eq(&self, other: &Self) -> bool {
         self.partial_cmp(other) == Some(Ordering::Equal)
@@ -67,6 +67,24 @@ async fn test_populated_state_responds_correctly(
 
         if ind == LAST_BLOCK_HEIGHT as usize {
             transcript.push((Request::Tip, Ok(Response::Tip(Some((height, hash))))));
+
+            let expected_locator = crate::util::block_locator_heights(height)
+                .into_iter()
+                .filter_map(|locator_height| {
+                    zebra_test::vectors::MAINNET_BLOCKS
+                        .get(&locator_height.0)
+                        .map(|block_bytes| {
+                            block_bytes
+                                .zcash_deserialize_into::<Arc<Block>>()
+                                .unwrap()
+                                .hash()
+                        })
+                })
+                .collect();
+            transcript.push((
+                Request::BlockLocator,
+                Ok(Response::BlockLocator(expected_locator)),
+            ));
         }
 
         // Consensus-critical bug in zcashd: transactions in the genesis block
@@ -77,7 +95,7 @@ async fn test_populated_state_responds_correctly(
 
                 transcript.push((
                     Request::Transaction(transaction_hash),
-                    Ok(Response::Transaction(Some(transaction.clone()))),
+                    Ok(Response::Transaction(Some((transaction.clone(), height)))),
                 ));
 
                 let from_coinbase = transaction.is_coinbase();
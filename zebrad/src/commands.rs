@@ -1,11 +1,17 @@
 //! Zebrad Subcommands
 
+mod addressbook;
 mod generate;
 mod start;
+mod state_backup;
+mod state_snapshot;
 mod version;
 
 use self::ZebradCmd::*;
-use self::{generate::GenerateCmd, start::StartCmd, version::VersionCmd};
+use self::{
+    addressbook::AddressBookCmd, generate::GenerateCmd, start::StartCmd,
+    state_backup::StateBackupCmd, state_snapshot::StateSnapshotCmd, version::VersionCmd,
+};
 
 use crate::config::ZebradConfig;
 
@@ -20,6 +26,10 @@ pub const CONFIG_FILE: &str = "zebrad.toml";
 /// Zebrad Subcommands
 #[derive(Command, Debug, Options)]
 pub enum ZebradCmd {
+    /// The `addressbook` subcommand
+    #[options(help = "crawl the network and print the address book as JSON")]
+    AddressBook(AddressBookCmd),
+
     /// The `generate` subcommand
     #[options(help = "generate a skeleton configuration")]
     Generate(GenerateCmd),
@@ -32,6 +42,14 @@ pub enum ZebradCmd {
     #[options(help = "start the application")]
     Start(StartCmd),
 
+    /// The `state-backup` subcommand
+    #[options(help = "take an online backup of the finalized state, or restore one")]
+    StateBackup(StateBackupCmd),
+
+    /// The `state-snapshot` subcommand
+    #[options(help = "export or import a finalized state snapshot")]
+    StateSnapshot(StateSnapshotCmd),
+
     /// The `version` subcommand
     #[options(help = "display version information")]
     Version(VersionCmd),
@@ -45,7 +63,8 @@ impl ZebradCmd {
         match self {
             // List all the commands, so new commands have to make a choice here
             Start(_) => true,
-            Generate(_) | Help(_) | Version(_) => false,
+            AddressBook(_) | Generate(_) | Help(_) | StateBackup(_) | StateSnapshot(_)
+            | Version(_) => false,
         }
     }
 }
@@ -53,9 +72,12 @@ impl ZebradCmd {
 impl Runnable for ZebradCmd {
     fn run(&self) {
         match self {
+            AddressBook(cmd) => cmd.run(),
             Generate(cmd) => cmd.run(),
             ZebradCmd::Help(cmd) => cmd.run(),
             Start(cmd) => cmd.run(),
+            StateBackup(cmd) => cmd.run(),
+            StateSnapshot(cmd) => cmd.run(),
             Version(cmd) => cmd.run(),
         }
     }
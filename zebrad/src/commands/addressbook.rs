@@ -0,0 +1,65 @@
+//! `addressbook` subcommand - crawls the network and prints the address book
+
+use std::time::Duration;
+
+use abscissa_core::{Command, Options, Runnable};
+use color_eyre::eyre::Report;
+
+use crate::components::tokio::{RuntimeRun, TokioComponent};
+use crate::prelude::*;
+
+/// The default amount of time to crawl the network before printing the
+/// address book, in seconds.
+const DEFAULT_CRAWL_SECONDS: u64 = 10;
+
+/// `addressbook` subcommand
+#[derive(Command, Debug, Options)]
+pub struct AddressBookCmd {
+    /// How long to crawl the network for peers before printing the address
+    /// book, in seconds.
+    #[options(help = "how long to crawl for peers before printing the address book, in seconds")]
+    crawl_seconds: Option<u64>,
+}
+
+impl AddressBookCmd {
+    async fn print_address_book(&self) -> Result<(), Report> {
+        let config = app_config().clone();
+
+        // This subcommand only inspects the address book, so it never needs
+        // to answer inbound requests.
+        let inbound = tower::service_fn(|_req| async move {
+            Ok::<zebra_network::Response, zebra_network::BoxError>(zebra_network::Response::Nil)
+        });
+
+        let (_peer_set, address_book, _peer_set_readiness, _peer_event_receiver) =
+            zebra_network::init(config.network.clone(), inbound).await;
+
+        let crawl_seconds = self.crawl_seconds.unwrap_or(DEFAULT_CRAWL_SECONDS);
+        info!(crawl_seconds, "crawling the network for peers");
+        tokio::time::sleep(Duration::from_secs(crawl_seconds)).await;
+
+        let peers = address_book.lock().unwrap().peer_info();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&peers).expect("address book should be serializable")
+        );
+
+        Ok(())
+    }
+}
+
+impl Runnable for AddressBookCmd {
+    /// Crawl the network, then print the address book.
+    fn run(&self) {
+        let rt = app_writer()
+            .state_mut()
+            .components
+            .get_downcast_mut::<TokioComponent>()
+            .expect("TokioComponent should be available")
+            .rt
+            .take();
+
+        rt.expect("runtime should not already be taken")
+            .run(self.print_address_book());
+    }
+}
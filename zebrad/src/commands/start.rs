@@ -41,6 +41,15 @@ pub struct StartCmd {
     /// Filter strings
     #[options(free)]
     filters: Vec<String>,
+
+    /// A transparent address to mine to, as a convenience for local
+    /// regtest/testnet setups.
+    ///
+    /// Zebra doesn't include a miner yet (see #1113), so this only checks
+    /// that the address is valid for the configured network and logs it;
+    /// it doesn't actually start mining.
+    #[options(help = "a transparent address to mine to on regtest/testnet")]
+    mine_to_address: Option<String>,
 }
 
 impl StartCmd {
@@ -48,6 +57,25 @@ impl StartCmd {
         let config = app_config().clone();
         info!(?config);
 
+        if let Some(mine_to_address) = &self.mine_to_address {
+            let address: zebra_chain::transparent::Address = mine_to_address
+                .parse()
+                .map_err(|_| eyre!("--mine-to-address is not a valid transparent address"))?;
+
+            if config.network.network == zebra_chain::parameters::Network::Mainnet {
+                return Err(eyre!(
+                    "--mine-to-address is only supported on testnet, to avoid accidentally \
+                     mining to a throwaway address on mainnet"
+                ));
+            }
+
+            warn!(
+                ?address,
+                "--mine-to-address was set, but Zebra does not implement mining yet; \
+                 the address will be ignored"
+            );
+        }
+
         info!("initializing node state");
         let state = ServiceBuilder::new().buffer(20).service(zebra_state::init(
             config.state.clone(),
@@ -73,7 +101,8 @@ impl StartCmd {
             .buffer(20)
             .service(Inbound::new(setup_rx, state.clone(), verifier.clone()));
 
-        let (peer_set, address_book) = zebra_network::init(config.network.clone(), inbound).await;
+        let (peer_set, address_book, _peer_set_readiness, _peer_event_receiver) =
+            zebra_network::init(config.network.clone(), inbound).await;
         setup_tx
             .send((peer_set.clone(), address_book))
             .map_err(|_| eyre!("could not send setup data to inbound service"))?;
@@ -0,0 +1,83 @@
+//! `state-backup` subcommand - takes an online backup of the finalized
+//! state, or restores one
+
+use std::path::PathBuf;
+
+use abscissa_core::{Command, Options, Runnable};
+use color_eyre::eyre::{eyre, Report};
+
+use crate::prelude::*;
+
+/// `state-backup` subcommand
+#[derive(Command, Debug, Options)]
+pub struct StateBackupCmd {
+    /// Take an online backup of the running node's finalized state into the
+    /// RocksDB backup engine directory at PATH, rather than restoring one.
+    ///
+    /// This reads the database through a secondary RocksDB handle, so it can
+    /// run alongside a `zebrad start` process using the same cache directory.
+    #[options(help = "back up the finalized state to PATH, instead of restoring one")]
+    backup: Option<PathBuf>,
+
+    /// A private directory for the secondary RocksDB handle's own metadata,
+    /// used only with `--backup`. It doesn't need to be backed up itself.
+    #[options(help = "metadata directory for the secondary handle used by --backup")]
+    secondary_path: Option<PathBuf>,
+
+    /// Restore the backup at PATH, written by a previous `--backup` run, as
+    /// the initial finalized state.
+    #[options(help = "restore a finalized state backup from PATH, instead of backing one up")]
+    restore: Option<PathBuf>,
+
+    /// Restore this specific backup ID, rather than the most recent one.
+    /// Only used with `--restore`.
+    #[options(help = "restore backup ID N, instead of the most recent one")]
+    backup_id: Option<u32>,
+}
+
+impl StateBackupCmd {
+    fn run_inner(&self) -> Result<(), Report> {
+        let config = app_config().clone();
+
+        match (&self.backup, &self.restore) {
+            (Some(destination), None) => {
+                let secondary_path = self
+                    .secondary_path
+                    .as_ref()
+                    .ok_or_else(|| eyre!("--backup also requires --secondary-path PATH"))?;
+
+                let state = zebra_state::open_secondary(
+                    &config.state,
+                    config.network.network,
+                    secondary_path,
+                )?;
+                state.backup(destination)?;
+                println!("backed up state to {}", destination.display());
+                Ok(())
+            }
+            (None, Some(source)) => {
+                zebra_state::restore_backup(
+                    &config.state,
+                    config.network.network,
+                    source,
+                    self.backup_id,
+                )?;
+                println!("restored state backup from {}", source.display());
+                Ok(())
+            }
+            _ => Err(eyre!(
+                "specify exactly one of --backup PATH or --restore PATH"
+            )),
+        }
+    }
+}
+
+impl Runnable for StateBackupCmd {
+    /// Back up or restore a finalized state backup.
+    fn run(&self) {
+        if let Err(report) = self.run_inner() {
+            eprintln!("{}", report);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! `state-snapshot` subcommand - exports or imports a finalized state snapshot
+
+use std::path::PathBuf;
+
+use abscissa_core::{Command, Options, Runnable};
+use color_eyre::eyre::{eyre, Report};
+
+use crate::prelude::*;
+
+/// `state-snapshot` subcommand
+#[derive(Command, Debug, Options)]
+pub struct StateSnapshotCmd {
+    /// Write a snapshot of the current finalized state to this path, rather
+    /// than importing one.
+    #[options(help = "export a state snapshot to PATH, instead of importing one")]
+    export: Option<PathBuf>,
+
+    /// Load a previously exported snapshot from this path, as the initial
+    /// finalized state.
+    #[options(help = "import a state snapshot from PATH, instead of exporting one")]
+    import: Option<PathBuf>,
+}
+
+impl StateSnapshotCmd {
+    fn run_inner(&self) -> Result<(), Report> {
+        let config = app_config().clone();
+
+        match (&self.export, &self.import) {
+            (Some(destination), None) => {
+                zebra_state::export_snapshot(&config.state, config.network.network, destination)?;
+                println!("exported state snapshot to {}", destination.display());
+                Ok(())
+            }
+            (None, Some(source)) => {
+                let tip_hash =
+                    zebra_state::import_snapshot(&config.state, config.network.network, source)?;
+                println!("imported state snapshot at tip {}", tip_hash);
+                Ok(())
+            }
+            _ => Err(eyre!(
+                "specify exactly one of --export PATH or --import PATH"
+            )),
+        }
+    }
+}
+
+impl Runnable for StateSnapshotCmd {
+    /// Export or import a finalized state snapshot.
+    fn run(&self) {
+        if let Err(report) = self.run_inner() {
+            eprintln!("{}", report);
+            std::process::exit(1);
+        }
+    }
+}
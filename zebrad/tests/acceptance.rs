@@ -46,7 +46,7 @@ const LAUNCH_DELAY: Duration = Duration::from_secs(10);
 
 fn default_test_config() -> Result<ZebradConfig> {
     let auto_port_ipv4_local = zebra_network::Config {
-        listen_addr: "127.0.0.1:0".parse()?,
+        listen_addrs: vec!["127.0.0.1:0".parse()?],
         crawl_new_peer_interval: Duration::from_secs(30),
         ..zebra_network::Config::default()
     };
@@ -1079,12 +1079,12 @@ fn zebra_zcash_listener_conflict() -> Result<()> {
 
     // Write a configuration that has our created network listen_addr
     let mut config = default_test_config()?;
-    config.network.listen_addr = listen_addr.parse().unwrap();
+    config.network.listen_addrs = vec![listen_addr.parse().unwrap()];
     let dir1 = TempDir::new("zebrad_tests")?.with_config(&mut config)?;
     let regex1 = format!(r"Opened Zcash protocol endpoint at {}", listen_addr);
 
     // From another folder create a configuration with the same listener.
-    // `network.listen_addr` will be the same in the 2 nodes.
+    // `network.listen_addrs` will be the same in the 2 nodes.
     // (But since the config is ephemeral, they will have different state paths.)
     let dir2 = TempDir::new("zebrad_tests")?.with_config(&mut config)?;
 